@@ -0,0 +1,7 @@
+pub mod rpc;
+pub mod state;
+pub mod tasks;
+pub mod types;
+
+pub use state::*;
+pub use types::*;
@@ -0,0 +1,27 @@
+use crate::{
+    batch::{self, Batch, BatchError, BatchId},
+    http_error::HttpError,
+    logger::{self},
+    user::auth_guard,
+};
+use ic_cdk::update;
+
+#[update]
+async fn batch_cancel(batch_id: BatchId) -> Result<Batch, HttpError> {
+    let address = auth_guard()?;
+    logger::debug("batch_cancel");
+
+    let batch = batch::get(&batch_id).map_err(HttpError::not_found)?;
+
+    // Only creator can cancel the batch
+    if batch.creator != address.to_string() {
+        return Err(HttpError::forbidden("Only creator can cancel the batch"));
+    }
+
+    let batch = batch::cancel(&batch_id).map_err(|err| match err {
+        BatchError::CantBeCancelled(msg) => HttpError::bad_request(msg),
+        err => HttpError::internal_server_error(err),
+    })?;
+
+    Ok(batch)
+}
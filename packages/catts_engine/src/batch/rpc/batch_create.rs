@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+
+use crate::{
+    batch::{self, tasks::create_attestations::MULTI_ATTEST_CHUNK_SIZE, Batch, BatchError},
+    chain_config::{self},
+    eth_address::EthAddress,
+    http_error::HttpError,
+    logger::{self},
+    recipe::{self, RecipeId, RecipePublishState, RecipeRecipientRule},
+    run::{get_cyclesfee_for_chain, get_min_gasfee_for_chain, get_onchain_query_fee},
+    user::auth_guard,
+};
+use candid::Nat;
+use ic_cdk::{api::canister_balance, update};
+
+const MAX_BATCH_RECIPIENTS: usize = 500;
+
+fn parse_recipients(recipients: &[String]) -> Result<Vec<EthAddress>, BatchError> {
+    if recipients.is_empty() || recipients.len() > MAX_BATCH_RECIPIENTS {
+        return Err(BatchError::InvalidRecipients(format!(
+            "A batch must have between 1 and {} recipients",
+            MAX_BATCH_RECIPIENTS
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    let mut addresses = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        let address = EthAddress::new(recipient)
+            .map_err(|err| BatchError::InvalidRecipients(format!("{}: {}", recipient, err)))?;
+        if !seen.insert(address.to_string()) {
+            return Err(BatchError::InvalidRecipients(format!(
+                "{}: Duplicate recipient",
+                recipient
+            )));
+        }
+        addresses.push(address);
+    }
+
+    Ok(addresses)
+}
+
+#[update]
+async fn batch_create(
+    recipe_id: RecipeId,
+    chain_id: u32,
+    recipients: Vec<String>,
+    base_fee_per_gas: Nat,
+    max_priority_fee_per_gas: Nat,
+    gas_per_recipient: Nat,
+) -> Result<Batch, HttpError> {
+    let address = auth_guard()?;
+
+    let cycles_before = canister_balance();
+    logger::debug("batch_create");
+
+    let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+
+    if recipe.publish_state != RecipePublishState::Published {
+        return Err(HttpError::bad_request("Recipe is not published"));
+    }
+
+    // multiAttest creates the attestations with the canister as attester
+    if recipe.delegated_attester.is_some() {
+        return Err(HttpError::bad_request(
+            "Recipe attests by delegation, batches are not supported",
+        ));
+    }
+
+    chain_config::get(chain_id).map_err(|_| {
+        HttpError::internal_server_error(format!("Chain {} is not supported", chain_id).as_str())
+    })?;
+
+    if !recipe.supports_chain(chain_id) {
+        return Err(HttpError::bad_request(format!(
+            "Recipe does not support chain {}",
+            chain_id
+        )));
+    }
+
+    // Attestations would revert on chains without the schema
+    if recipe.is_schema_unregistered(chain_id) {
+        return Err(HttpError::bad_request(format!(
+            "Recipe schema is not registered on chain {}",
+            chain_id
+        )));
+    }
+
+    let recipients = parse_recipients(&recipients).map_err(HttpError::bad_request)?;
+
+    // Each recipient is also the subject of the queries
+    if recipe.recipient_rule() == RecipeRecipientRule::PayerOnly
+        && recipients.iter().any(|r| r.as_str() != address.as_str())
+    {
+        return Err(HttpError::bad_request(BatchError::InvalidRecipients(
+            "Recipe only attests for the paying user".to_string(),
+        )));
+    }
+
+    let mut batch =
+        Batch::new(&recipe_id, chain_id, &address, &recipients).map_err(HttpError::bad_request)?;
+
+    // Add 50% to the base fee per gas to account for gas fee volatility
+    let base_fee_per_gas = base_fee_per_gas * Nat::from(3_u8) / Nat::from(2_u8);
+
+    // One quote for the whole batch, every multiAttest transaction is charged
+    // at least the minimum gas fee and every recipient the cycles fee
+    let recipient_count = Nat::from(recipients.len() as u64);
+    let transaction_count = Nat::from(recipients.len().div_ceil(MULTI_ATTEST_CHUNK_SIZE) as u64);
+
+    let gas_fee = gas_per_recipient.clone()
+        * recipient_count.clone()
+        * (base_fee_per_gas.clone() + max_priority_fee_per_gas.clone());
+    let min_gas_fee = get_min_gasfee_for_chain(chain_id).unwrap() * transaction_count;
+    let gas_fee = gas_fee.max(min_gas_fee);
+
+    // The queries run for every recipient
+    let cycles_fee = (get_cyclesfee_for_chain(chain_id).unwrap()
+        + get_onchain_query_fee(&recipe, chain_id).unwrap())
+        * recipient_count;
+
+    let user_fee = gas_fee + cycles_fee;
+
+    logger::debug(
+        format!(
+            "base_fee_per_gas: {}, max_priority_fee_per_gas: {}, gas_per_recipient: {}",
+            base_fee_per_gas, max_priority_fee_per_gas, gas_per_recipient
+        )
+        .as_str(),
+    );
+
+    batch.gas_per_recipient = gas_per_recipient;
+    batch.base_fee_per_gas = base_fee_per_gas;
+    batch.max_priority_fee_per_gas = max_priority_fee_per_gas;
+    batch.user_fee = user_fee;
+
+    let batch = batch::create(batch);
+
+    let cycles_after = canister_balance();
+
+    logger::info(
+        format!(
+            "batch_create, cycles spent: {:?}",
+            cycles_before - cycles_after
+        )
+        .as_str(),
+    );
+
+    Ok(batch)
+}
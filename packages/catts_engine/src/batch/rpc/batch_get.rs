@@ -0,0 +1,10 @@
+use crate::{
+    batch::{self, Batch, BatchId},
+    http_error::HttpError,
+};
+use ic_cdk::query;
+
+#[query]
+async fn batch_get(batch_id: BatchId) -> Result<Batch, HttpError> {
+    batch::get(&batch_id).map_err(HttpError::not_found)
+}
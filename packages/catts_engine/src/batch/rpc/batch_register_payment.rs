@@ -0,0 +1,67 @@
+use ic_cdk::{api::canister_balance, update};
+
+use crate::{
+    batch::{self, tasks::register_payment::ProcessBatchPaymentArgs, Batch, BatchId},
+    http_error::HttpError,
+    logger::{self, info},
+    tasks::{add_task, Task, TaskType},
+    user::auth_guard,
+};
+
+const PROCESS_BATCH_PAYMENT_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const PROCESS_BATCH_PAYMENT_MAX_RETRIES: u32 = 3;
+
+#[update]
+async fn batch_register_payment(
+    batch_id: BatchId,
+    transaction_hash: String,
+    block_to_process: u128,
+) -> Result<Batch, HttpError> {
+    let address = auth_guard()?;
+
+    logger::debug("batch_register_payment");
+    let cycles_before = canister_balance();
+
+    let batch = batch::get(&batch_id).map_err(HttpError::not_found)?;
+
+    // Only creator can register payment
+    if batch.creator != address.to_string() {
+        return Err(HttpError::forbidden("Only creator can register payment"));
+    }
+
+    if batch.is_cancelled {
+        return Err(HttpError::bad_request("Batch is cancelled"));
+    }
+
+    let batch = batch::register_payment(&batch_id, &transaction_hash, block_to_process)
+        .map_err(HttpError::bad_request)?;
+
+    let args: Vec<u8> = bincode::serialize(&ProcessBatchPaymentArgs {
+        block_to_process,
+        from_address: address.as_byte_array(),
+        batch_id,
+    })
+    .unwrap();
+
+    add_task(
+        0, // Run ASAP
+        Task {
+            task_type: TaskType::ProcessBatchPayment,
+            args,
+            max_retries: PROCESS_BATCH_PAYMENT_MAX_RETRIES,
+            execute_count: 0,
+            retry_interval: PROCESS_BATCH_PAYMENT_RETRY_INTERVAL,
+        },
+    );
+
+    let cycles_after = canister_balance();
+    info(
+        format!(
+            "batch_register_payment, cycles spent: {:?}",
+            cycles_before - cycles_after
+        )
+        .as_str(),
+    );
+
+    Ok(batch)
+}
@@ -0,0 +1,4 @@
+pub mod batch_cancel;
+pub mod batch_create;
+pub mod batch_get;
+pub mod batch_register_payment;
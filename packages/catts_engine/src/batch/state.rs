@@ -0,0 +1,75 @@
+use super::types::{Batch, BatchError, BatchId};
+use crate::change_log::ChangeLogTypeName;
+use crate::eth_address::EthAddress;
+use crate::{change_log, eas, BATCHES};
+use blake2::digest::{Update, VariableOutput};
+use blake2::Blake2bVar;
+use candid::Nat;
+
+// Batches are paid through the same payment event as runs, the prefix keeps
+// batch ids apart from run ids
+const BATCH_ID_PREFIX: &[u8] = b"batch";
+
+pub fn generate_batch_id(creator: &EthAddress, created: u32) -> BatchId {
+    let mut hasher = Blake2bVar::new(12).unwrap();
+    hasher.update(BATCH_ID_PREFIX);
+    hasher.update(&creator.as_byte_array());
+    hasher.update(&created.to_be_bytes());
+    let mut buf = [0u8; 12];
+    hasher.finalize_variable(&mut buf).unwrap();
+    buf
+}
+
+pub fn create(batch: Batch) -> Batch {
+    BATCHES.with_borrow_mut(|batches| {
+        batches.insert(batch.id, batch.clone());
+    });
+    change_log::create(ChangeLogTypeName::Batch, batch.id, &batch).unwrap();
+    batch
+}
+
+pub fn update(batch: Batch) -> Result<Batch, BatchError> {
+    let old_batch = get(&batch.id)?;
+    BATCHES.with_borrow_mut(|batches| {
+        batches.insert(batch.id, batch.clone());
+    });
+    eas::index::add_batch(&batch);
+    change_log::update(ChangeLogTypeName::Batch, batch.id, &old_batch, &batch).unwrap();
+    Ok(batch)
+}
+
+pub fn cancel(batch_id: &BatchId) -> Result<Batch, BatchError> {
+    let mut batch = get(batch_id)?;
+
+    // Batches can only be cancelled if they are not paid yet
+    if batch.payment_transaction_hash.is_some() {
+        return Err(BatchError::CantBeCancelled(
+            "Batch is already paid".to_string(),
+        ));
+    }
+
+    batch.is_cancelled = true;
+
+    update(batch)
+}
+
+pub fn get(batch_id: &BatchId) -> Result<Batch, BatchError> {
+    BATCHES.with_borrow(|batches| batches.get(batch_id).ok_or(BatchError::NotFound))
+}
+
+pub fn register_payment(
+    batch_id: &BatchId,
+    transaction_hash: &str,
+    block_to_process: u128,
+) -> Result<Batch, BatchError> {
+    let mut batch = get(batch_id)?;
+
+    if batch.payment_transaction_hash.is_some() {
+        return Err(BatchError::AlreadyPaid);
+    }
+
+    batch.payment_transaction_hash = Some(transaction_hash.to_string());
+    batch.payment_block_number = Some(Nat::from(block_to_process));
+
+    update(batch)
+}
@@ -0,0 +1,218 @@
+use crate::{
+    batch::{self, BatchStatus},
+    eas::{
+        create_attest_request_data, create_multi_attestation, parse_processor_output, run_query,
+        schema,
+    },
+    eth_address::EthAddress,
+    logger::{self},
+    processor,
+    recipe::{self, Recipe},
+    tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
+};
+use anyhow::{anyhow, bail, Result};
+use candid::Nat;
+use ethers_core::abi::Token;
+use futures::Future;
+use ic_cdk::api::canister_balance;
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+use super::util::save_error_and_cancel;
+
+// Number of attestations per multiAttest transaction
+pub const MULTI_ATTEST_CHUNK_SIZE: usize = 25;
+
+const CREATE_ATTESTATIONS_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const CREATE_ATTESTATIONS_MAX_RETRIES: u32 = 3;
+const GET_ATTESTATION_UIDS_FIRST_TIME_INTERVAL: u64 = 5_000_000_000; // 5 seconds
+const GET_ATTESTATION_UIDS_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const GET_ATTESTATION_UIDS_MAX_RETRIES: u32 = 10;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GetBatchAttestationUidsArgs {
+    pub batch_id: [u8; 12],
+    pub transaction_hash: String,
+}
+
+pub struct CreateBatchAttestationsExecutor {}
+
+/// Runs the queries and the processor for one recipient, the recipient is also
+/// the subject of the queries.
+async fn create_recipient_request_data(recipe: &Recipe, recipient: &EthAddress) -> Result<Token> {
+    let mut query_response = Vec::new();
+    for query in recipe.queries.iter() {
+        let response = run_query(recipient, query)
+            .await
+            .map_err(|err| anyhow!("Error running EAS query: {}", err))?;
+        query_response.push(response);
+    }
+    let aggregated_response = format!("[{}]", query_response.join(","));
+
+    let processor_output = match processor::execute_isolated(
+        &recipe.processor,
+        &aggregated_response,
+        &recipe.processor_limits(),
+        recipe.stdlib_version,
+    )
+    .await
+    {
+        Ok(output) => output.output,
+        Err(failure) => bail!(failure.error.to_string()),
+    };
+
+    let attestation_output = parse_processor_output(&processor_output, &aggregated_response)?;
+
+    schema::validate_output(&recipe.schema, &attestation_output.data)
+        .map_err(|err| anyhow!("Processor output does not match the schema: {}", err))?;
+
+    create_attest_request_data(recipe, &attestation_output, recipient, None)
+}
+
+impl TaskExecutor for CreateBatchAttestationsExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let cycles_before = canister_balance();
+
+            let batch_id = crate::run::vec_to_run_id(task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            let batch = batch::get(&batch_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+            let recipe = recipe::get_by_id(&batch.recipe_id)
+                .map_err(|_| save_error_and_cancel(&batch_id, "Recipe not found".to_string()))?;
+
+            if batch.status() != BatchStatus::PaymentVerified {
+                return Err(TaskError::Cancel(
+                    "Batch has no pending recipients".to_string(),
+                ));
+            }
+
+            if recipe.queries.is_empty() {
+                return Err(save_error_and_cancel(
+                    &batch_id,
+                    "Recipe contains no queries".to_string(),
+                ));
+            }
+
+            // The next chunk of recipients that are neither attested nor failed
+            let chunk: Vec<(usize, EthAddress)> = batch
+                .recipients
+                .iter()
+                .enumerate()
+                .filter(|(_, recipient)| recipient.is_pending())
+                .take(MULTI_ATTEST_CHUNK_SIZE)
+                .map(|(index, recipient)| (index, EthAddress::from(recipient.address.as_str())))
+                .collect();
+
+            let mut request_data = Vec::new();
+            let mut included = Vec::new();
+            let mut errors = Vec::new();
+            for (index, recipient) in chunk.iter() {
+                match create_recipient_request_data(&recipe, recipient).await {
+                    Ok(data) => {
+                        request_data.push(data);
+                        included.push(*index);
+                    }
+                    Err(err) => {
+                        logger::debug(&format!("Batch recipient failed: {}", err));
+                        errors.push((*index, err.to_string()));
+                    }
+                }
+            }
+
+            let mut retry_error = None;
+            let transaction_hash = if request_data.is_empty() {
+                None
+            } else {
+                let gas = batch.gas_per_recipient.clone() * Nat::from(request_data.len() as u64);
+                match create_multi_attestation(
+                    &recipe,
+                    request_data,
+                    gas,
+                    batch.base_fee_per_gas.clone(),
+                    batch.max_priority_fee_per_gas.clone(),
+                    batch.chain_id,
+                )
+                .await
+                {
+                    Ok(hash) => Some(hash),
+                    Err(err) => {
+                        let error = format!("Error creating attestations: {}", err);
+                        if task.execute_count + 1 < task.max_retries {
+                            // The included recipients stay pending and are
+                            // attested again when the task is retried
+                            retry_error = Some(error);
+                        } else {
+                            for index in included.iter() {
+                                errors.push((*index, error.clone()));
+                            }
+                        }
+                        None
+                    }
+                }
+            };
+
+            // Uids of earlier chunks may have been saved while this chunk was
+            // processed, apply the results to the current state of the batch
+            let mut batch = batch::get(&batch_id).unwrap();
+            for (index, error) in errors {
+                batch.recipients[index].error = Some(error);
+            }
+            if let Some(ref transaction_hash) = transaction_hash {
+                for index in included.iter() {
+                    batch.recipients[*index].attestation_transaction_hash =
+                        Some(transaction_hash.clone());
+                }
+            }
+            let batch = batch::update(batch).unwrap();
+
+            if let Some(error) = retry_error {
+                return Err(TaskError::Retry(error));
+            }
+
+            if let Some(transaction_hash) = transaction_hash {
+                let args: Vec<u8> = bincode::serialize(&GetBatchAttestationUidsArgs {
+                    batch_id,
+                    transaction_hash,
+                })
+                .unwrap();
+                add_task(
+                    ic_cdk::api::time() + GET_ATTESTATION_UIDS_FIRST_TIME_INTERVAL,
+                    Task {
+                        task_type: TaskType::GetBatchAttestationUids,
+                        args,
+                        max_retries: GET_ATTESTATION_UIDS_MAX_RETRIES,
+                        execute_count: 0,
+                        retry_interval: GET_ATTESTATION_UIDS_RETRY_INTERVAL,
+                    },
+                );
+            }
+
+            // Continue with the next chunk
+            if batch.recipients.iter().any(|r| r.is_pending()) {
+                add_task(
+                    0, // Run ASAP
+                    Task {
+                        task_type: TaskType::CreateBatchAttestations,
+                        args: batch_id.to_vec(),
+                        max_retries: CREATE_ATTESTATIONS_MAX_RETRIES,
+                        execute_count: 0,
+                        retry_interval: CREATE_ATTESTATIONS_RETRY_INTERVAL,
+                    },
+                );
+            }
+
+            let cycles_after = canister_balance();
+            logger::info(
+                format!(
+                    "create_batch_attestations, cycles spent: {:?}",
+                    cycles_before - cycles_after
+                )
+                .as_str(),
+            );
+
+            Ok(())
+        })
+    }
+}
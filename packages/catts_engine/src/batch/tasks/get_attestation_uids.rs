@@ -0,0 +1,119 @@
+use std::pin::Pin;
+
+use crate::{
+    batch::{self, BatchId},
+    chain_config::{self},
+    evm::rpc::eth_get_transaction_receipt,
+    logger::{self},
+    tasks::{Task, TaskError, TaskExecutor},
+};
+use candid::Nat;
+use futures::Future;
+use ic_cdk::api::canister_balance;
+
+use super::{create_attestations::GetBatchAttestationUidsArgs, util::save_error_and_cancel};
+
+// Attested(address,address,bytes32,bytes32)
+const EAS_ATTESTED_EVENT_SIGNATURE: &str =
+    "0x8bf46bf4cfd674fa735a3d63ec1c9ad4153f033c290341f3a588b75685141b35";
+
+/// Saves the error on the recipients included in the transaction, they are
+/// not attested.
+fn fail_recipients(batch_id: &BatchId, transaction_hash: &str, error: &str) {
+    let mut batch = batch::get(batch_id).unwrap();
+    for recipient in batch.recipients.iter_mut().filter(|recipient| {
+        recipient.attestation_transaction_hash.as_deref() == Some(transaction_hash)
+    }) {
+        recipient.error = Some(error.to_string());
+    }
+    batch::update(batch).unwrap();
+}
+
+/// Retries the task, the recipients fail once the retries are exhausted.
+fn retry_or_fail(task: &Task, args: &GetBatchAttestationUidsArgs, reason: String) -> TaskError {
+    if task.execute_count + 1 < task.max_retries {
+        return TaskError::Retry(reason);
+    }
+    fail_recipients(
+        &args.batch_id,
+        &args.transaction_hash,
+        &format!("Attestation uids not found: {}", reason),
+    );
+    TaskError::Cancel(reason)
+}
+
+pub struct GetBatchAttestationUidsExecutor {}
+
+impl TaskExecutor for GetBatchAttestationUidsExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let cycles_before = canister_balance();
+
+            let args: GetBatchAttestationUidsArgs = bincode::deserialize(&task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            let batch = batch::get(&args.batch_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+            let chain_config = chain_config::get(batch.chain_id).map_err(|_| {
+                save_error_and_cancel(&args.batch_id, "Chain config not found".to_string())
+            })?;
+
+            let receipt = eth_get_transaction_receipt(&args.transaction_hash, &chain_config)
+                .await
+                .map_err(|err| retry_or_fail(&task, &args, err))?;
+
+            // A reverted multiAttest created none of the attestations
+            if receipt.status == Some(Nat::from(0u8)) {
+                let error = "Attestation transaction failed".to_string();
+                fail_recipients(&args.batch_id, &args.transaction_hash, &error);
+                return Err(TaskError::Cancel(error));
+            }
+
+            // multiAttest emits one Attested event per attestation, in the order
+            // of the requests. Resolvers may emit events of their own.
+            let eas_contract = chain_config.eas_contract.to_lowercase();
+            let uids: Vec<String> = receipt
+                .logs
+                .iter()
+                .filter(|log| {
+                    log.address.to_lowercase() == eas_contract
+                        && log.topics.first().is_some_and(|topic| {
+                            topic.to_lowercase() == EAS_ATTESTED_EVENT_SIGNATURE
+                        })
+                })
+                .map(|log| log.data.clone())
+                .collect();
+
+            if uids.is_empty() {
+                return Err(retry_or_fail(
+                    &task,
+                    &args,
+                    "No logs in transaction receipt".to_string(),
+                ));
+            }
+
+            let mut batch = batch::get(&args.batch_id).unwrap();
+            let mut uids = uids.into_iter();
+            for recipient in batch.recipients.iter_mut().filter(|recipient| {
+                recipient.attestation_transaction_hash.as_deref()
+                    == Some(args.transaction_hash.as_str())
+            }) {
+                recipient.attestation_uid = uids.next();
+            }
+            batch::update(batch).unwrap();
+
+            logger::debug("Batch attestation uids found");
+
+            let cycles_after = canister_balance();
+            logger::info(
+                format!(
+                    "get_batch_attestation_uids, cycles spent: {:?}",
+                    cycles_before - cycles_after
+                )
+                .as_str(),
+            );
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,4 @@
+pub mod create_attestations;
+pub mod get_attestation_uids;
+pub mod register_payment;
+pub mod util;
@@ -0,0 +1,124 @@
+use crate::batch::{self, Batch, BatchId, BatchStatus};
+use crate::chain_config::{self, ChainConfig};
+use crate::declarations::evm_rpc::LogEntry;
+use crate::eth_address::EthAddress;
+use crate::evm::rpc::get_run_payment_logs;
+use crate::logger::{self};
+use crate::run::tasks::register_payment::decode_payment_log_entry;
+use crate::tasks::{add_task, Task, TaskError, TaskExecutor, TaskType};
+use anyhow::{bail, Result};
+use futures::Future;
+use ic_cdk::api::canister_balance;
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+use super::util::save_error_and_cancel;
+
+const CREATE_ATTESTATIONS_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const CREATE_ATTESTATIONS_MAX_RETRIES: u32 = 3;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProcessBatchPaymentArgs {
+    pub block_to_process: u128,
+    pub from_address: [u8; 20],
+    pub batch_id: BatchId,
+}
+
+pub struct RegisterBatchPaymentExecutor {}
+
+impl TaskExecutor for RegisterBatchPaymentExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let cycles_before = canister_balance();
+
+            let args: ProcessBatchPaymentArgs = bincode::deserialize(&task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            let mut batch =
+                batch::get(&args.batch_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+            let chain_config = chain_config::get(batch.chain_id)
+                .map_err(|e| save_error_and_cancel(&args.batch_id, e.to_string()))?;
+
+            let payment_logs = get_run_payment_logs(args.block_to_process, &chain_config)
+                .await
+                .map_err(|e| TaskError::Retry(e.to_string()))?;
+
+            for entry in payment_logs {
+                if entry.transactionHash != batch.payment_transaction_hash {
+                    continue;
+                }
+
+                process_log_entry(&entry, &args, &chain_config)
+                    .map_err(|e| save_error_and_cancel(&args.batch_id, e.to_string()))?;
+
+                batch.payment_transaction_hash = entry.transactionHash;
+                batch.payment_block_number = entry.blockNumber;
+                batch.payment_log_index = entry.logIndex;
+
+                logger::info("Batch payment log entry processed successfully");
+
+                batch::update(batch).unwrap();
+
+                add_task(
+                    0, // Run ASAP
+                    Task {
+                        task_type: TaskType::CreateBatchAttestations,
+                        args: args.batch_id.to_vec(),
+                        max_retries: CREATE_ATTESTATIONS_MAX_RETRIES,
+                        execute_count: 0,
+                        retry_interval: CREATE_ATTESTATIONS_RETRY_INTERVAL,
+                    },
+                );
+
+                let cycles_after = canister_balance();
+                logger::info(
+                    format!(
+                        "register_batch_payment, cycles spent: {:?}",
+                        cycles_before - cycles_after
+                    )
+                    .as_str(),
+                );
+
+                return Ok(());
+            }
+
+            Err(TaskError::Cancel("No valid log entries found".to_string()))
+        })
+    }
+}
+
+fn process_log_entry(
+    entry: &LogEntry,
+    args: &ProcessBatchPaymentArgs,
+    chain_config: &ChainConfig,
+) -> Result<Batch> {
+    let from_address = EthAddress::from(args.from_address);
+    let (event_batch_id, event_amount) =
+        decode_payment_log_entry(entry, chain_config, &from_address)?;
+
+    if event_batch_id != args.batch_id {
+        bail!("Payment id does not match the expected batch id");
+    }
+
+    let batch = match batch::get(&event_batch_id) {
+        Ok(batch) => batch,
+        Err(_) => {
+            bail!("Found payment for non-existent batch");
+        }
+    };
+
+    if batch.status() == BatchStatus::PaymentPending {
+        bail!("No payment transaction is registered for this batch");
+    }
+
+    if batch.status() > BatchStatus::PaymentRegistered {
+        bail!("Batch payment is already verified");
+    }
+
+    if event_amount >= batch.user_fee {
+        Ok(batch)
+    } else {
+        bail!("Payment did not cover the cost of the batch");
+    }
+}
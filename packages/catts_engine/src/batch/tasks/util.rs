@@ -0,0 +1,9 @@
+use crate::{batch, logger, tasks::TaskError};
+
+pub fn save_error_and_cancel(batch_id: &[u8; 12], error: String) -> TaskError {
+    logger::debug(&format!("save_error_and_cancel: {}", error));
+    let mut batch = batch::get(batch_id).unwrap();
+    batch.error = Some(error.to_string());
+    batch::update(batch).unwrap();
+    TaskError::Cancel(error)
+}
@@ -0,0 +1,343 @@
+use crate::{
+    eth_address::EthAddress,
+    json::{bytes_to_hex_string_value, nat_to_hex_string_value, ToJsonValue},
+    recipe::{self, RecipeId},
+    time::time,
+};
+use candid::{CandidType, Decode, Encode, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use thiserror::Error;
+
+use super::state::generate_batch_id;
+
+#[derive(Error, Debug)]
+pub enum BatchError {
+    #[error("Batch not found")]
+    NotFound,
+    #[error("Recipe not found")]
+    RecipeNotFound,
+    #[error("Can't be cancelled: {0}")]
+    CantBeCancelled(String),
+    #[error("Already paid")]
+    AlreadyPaid,
+    #[error("Invalid recipients: {0}")]
+    InvalidRecipients(String),
+}
+
+pub type BatchId = [u8; 12];
+
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone)]
+pub struct BatchRecipient {
+    pub address: String,
+    // The multiAttest transaction that included the attestation
+    pub attestation_transaction_hash: Option<String>,
+    pub attestation_uid: Option<String>,
+    // Query, processor or schema error, the recipient is skipped
+    pub error: Option<String>,
+}
+
+impl BatchRecipient {
+    pub fn is_pending(&self) -> bool {
+        self.attestation_transaction_hash.is_none() && self.error.is_none()
+    }
+}
+
+/// A run of a recipe for many recipients, paid with one payment. The
+/// attestations are created in chunks with EAS multiAttest.
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone)]
+pub struct Batch {
+    pub id: BatchId,
+    pub recipe_id: RecipeId,
+    pub creator: String,
+    pub created: u32,
+    pub chain_id: u32,
+    pub recipients: Vec<BatchRecipient>,
+    // Estimated gas for one attestation, transactions are sent with the gas of
+    // the attestations they include
+    pub gas_per_recipient: Nat,
+    pub base_fee_per_gas: Nat,
+    pub max_priority_fee_per_gas: Nat,
+    pub user_fee: Nat,
+    pub payment_transaction_hash: Option<String>,
+    pub payment_block_number: Option<Nat>,
+    pub payment_log_index: Option<Nat>,
+    pub is_cancelled: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone, Copy, PartialEq, PartialOrd)]
+pub enum BatchStatus {
+    PaymentPending = 0,
+    PaymentRegistered = 1,
+    PaymentVerified = 2,
+    AttestationsCreated = 3,
+    Completed = 4,
+    // Some recipients failed, the others were attested
+    PartiallyCompleted = 5,
+    // No recipient was attested
+    Failed = 6,
+}
+
+impl Storable for Batch {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl ToJsonValue for BatchRecipient {
+    fn to_json_value(&self) -> Value {
+        let mut obj = serde_json::Map::new();
+
+        obj.insert("address".to_string(), json!(self.address));
+        if let Some(ref attestation_transaction_hash) = self.attestation_transaction_hash {
+            obj.insert(
+                "attestation_transaction_hash".to_string(),
+                Value::String(attestation_transaction_hash.to_string()),
+            );
+        }
+        if let Some(ref attestation_uid) = self.attestation_uid {
+            obj.insert(
+                "attestation_uid".to_string(),
+                Value::String(attestation_uid.to_string()),
+            );
+        }
+        if let Some(ref error) = self.error {
+            obj.insert("error".to_string(), Value::String(error.to_string()));
+        }
+
+        Value::Object(obj)
+    }
+}
+
+impl ToJsonValue for Batch {
+    fn to_json_value(&self) -> Value {
+        let mut obj = serde_json::Map::new();
+
+        obj.insert("id".to_string(), bytes_to_hex_string_value(&self.id));
+        obj.insert(
+            "recipe_id".to_string(),
+            bytes_to_hex_string_value(&self.recipe_id),
+        );
+        obj.insert("creator".to_string(), json!(self.creator));
+        obj.insert("created".to_string(), json!(self.created));
+        obj.insert("chain_id".to_string(), json!(self.chain_id));
+        obj.insert(
+            "recipients".to_string(),
+            Value::Array(
+                self.recipients
+                    .iter()
+                    .map(|recipient| recipient.to_json_value())
+                    .collect(),
+            ),
+        );
+        obj.insert(
+            "gas_per_recipient".to_string(),
+            nat_to_hex_string_value(&self.gas_per_recipient),
+        );
+        obj.insert(
+            "base_fee_per_gas".to_string(),
+            nat_to_hex_string_value(&self.base_fee_per_gas),
+        );
+        obj.insert(
+            "max_priority_fee_per_gas".to_string(),
+            nat_to_hex_string_value(&self.max_priority_fee_per_gas),
+        );
+        obj.insert(
+            "user_fee".to_string(),
+            nat_to_hex_string_value(&self.user_fee),
+        );
+        if let Some(ref payment_transaction_hash) = self.payment_transaction_hash {
+            obj.insert(
+                "payment_transaction_hash".to_string(),
+                Value::String(payment_transaction_hash.to_string()),
+            );
+        }
+        if let Some(ref payment_block_number) = self.payment_block_number {
+            obj.insert(
+                "payment_block_number".to_string(),
+                nat_to_hex_string_value(payment_block_number),
+            );
+        }
+        if let Some(ref payment_log_index) = self.payment_log_index {
+            obj.insert(
+                "payment_log_index".to_string(),
+                nat_to_hex_string_value(payment_log_index),
+            );
+        }
+        obj.insert("is_cancelled".to_string(), json!(self.is_cancelled));
+        if let Some(ref error) = self.error {
+            obj.insert("error".to_string(), Value::String(error.to_string()));
+        }
+
+        Value::Object(obj)
+    }
+}
+
+impl ToJsonValue for &Batch {
+    fn to_json_value(&self) -> Value {
+        (*self).to_json_value()
+    }
+}
+
+impl Batch {
+    pub fn new(
+        recipe_id: &[u8; 12],
+        chain_id: u32,
+        creator: &EthAddress,
+        recipients: &[EthAddress],
+    ) -> Result<Self, BatchError> {
+        // A batch must be created with a valid recipe
+        recipe::get_by_id(recipe_id).map_err(|_| BatchError::RecipeNotFound)?;
+
+        let created = time();
+        let id = generate_batch_id(creator, created);
+
+        let batch = Self {
+            id,
+            recipe_id: *recipe_id,
+            creator: creator.to_string(),
+            created,
+            chain_id,
+            recipients: recipients
+                .iter()
+                .map(|address| BatchRecipient {
+                    address: address.to_string(),
+                    attestation_transaction_hash: None,
+                    attestation_uid: None,
+                    error: None,
+                })
+                .collect(),
+            gas_per_recipient: Nat::from(0_u8),
+            base_fee_per_gas: Nat::from(0_u8),
+            max_priority_fee_per_gas: Nat::from(0_u8),
+            user_fee: Nat::from(0_u8),
+            payment_transaction_hash: None,
+            payment_block_number: None,
+            payment_log_index: None,
+            is_cancelled: false,
+            error: None,
+        };
+
+        Ok(batch)
+    }
+
+    pub fn status(&self) -> BatchStatus {
+        if self.payment_log_index.is_some() {
+            let recipients = &self.recipients;
+            if self.error.is_some() {
+                return BatchStatus::Failed;
+            }
+            if recipients.iter().any(|r| r.is_pending()) {
+                return BatchStatus::PaymentVerified;
+            }
+            if recipients.iter().any(|r| {
+                r.attestation_transaction_hash.is_some()
+                    && r.attestation_uid.is_none()
+                    && r.error.is_none()
+            }) {
+                return BatchStatus::AttestationsCreated;
+            }
+            if recipients.iter().all(|r| r.error.is_some()) {
+                return BatchStatus::Failed;
+            }
+            if recipients.iter().any(|r| r.error.is_some()) {
+                return BatchStatus::PartiallyCompleted;
+            }
+            return BatchStatus::Completed;
+        }
+        if self.payment_transaction_hash.is_some() {
+            return BatchStatus::PaymentRegistered;
+        }
+        BatchStatus::PaymentPending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipient(uid: Option<&str>, error: Option<&str>) -> BatchRecipient {
+        BatchRecipient {
+            address: "0x1111111111111111111111111111111111111111".to_string(),
+            attestation_transaction_hash: uid.map(|_| "0xabc".to_string()),
+            attestation_uid: uid.map(|uid| uid.to_string()),
+            error: error.map(|error| error.to_string()),
+        }
+    }
+
+    fn batch(recipients: Vec<BatchRecipient>) -> Batch {
+        Batch {
+            id: [1u8; 12],
+            recipe_id: [2u8; 12],
+            creator: "0x2222222222222222222222222222222222222222".to_string(),
+            created: 0,
+            chain_id: 10,
+            recipients,
+            gas_per_recipient: Nat::from(0_u8),
+            base_fee_per_gas: Nat::from(0_u8),
+            max_priority_fee_per_gas: Nat::from(0_u8),
+            user_fee: Nat::from(0_u8),
+            payment_transaction_hash: Some("0xdef".to_string()),
+            payment_block_number: Some(Nat::from(1_u8)),
+            payment_log_index: Some(Nat::from(0_u8)),
+            is_cancelled: false,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn status_completed() {
+        let batch = batch(vec![
+            recipient(Some("0x1"), None),
+            recipient(Some("0x2"), None),
+        ]);
+        assert_eq!(batch.status(), BatchStatus::Completed);
+    }
+
+    #[test]
+    fn status_partially_completed() {
+        let batch = batch(vec![
+            recipient(Some("0x1"), None),
+            recipient(None, Some("error")),
+        ]);
+        assert_eq!(batch.status(), BatchStatus::PartiallyCompleted);
+    }
+
+    #[test]
+    fn status_failed_when_all_recipients_failed() {
+        let batch = batch(vec![
+            recipient(None, Some("error")),
+            recipient(None, Some("error")),
+        ]);
+        assert_eq!(batch.status(), BatchStatus::Failed);
+    }
+
+    #[test]
+    fn status_reverted_chunk() {
+        // Recipients of a reverted transaction keep its hash next to the error
+        let mut reverted = recipient(None, Some("Attestation transaction failed"));
+        reverted.attestation_transaction_hash = Some("0xabc".to_string());
+
+        let failed = batch(vec![reverted.clone(), reverted.clone()]);
+        assert_eq!(failed.status(), BatchStatus::Failed);
+
+        let partial = batch(vec![recipient(Some("0x1"), None), reverted]);
+        assert_eq!(partial.status(), BatchStatus::PartiallyCompleted);
+    }
+
+    #[test]
+    fn status_failed_on_batch_error() {
+        let mut batch = batch(vec![recipient(None, None)]);
+        batch.error = Some("Recipe not found".to_string());
+        assert_eq!(batch.status(), BatchStatus::Failed);
+    }
+}
@@ -178,6 +178,25 @@ pub fn render_run_assets(run_id_hex: String) -> Result<Vec<Asset>, AssetError> {
     Ok(vec![index_asset, ogimage_asset])
 }
 
+/// Certifies the signed attestation of an off-chain run as JSON.
+pub fn render_run_attestation_asset(run_id_hex: String) -> Result<Vec<Asset>, AssetError> {
+    let run_id = hex::decode(&run_id_hex).map_err(|_| AssetError::InvalidArgument)?;
+    let run_id = run::vec_to_run_id(run_id).map_err(|_| AssetError::InvalidArgument)?;
+    let run = run::get(&run_id).map_err(|_| AssetError::NotFound)?;
+    let offchain_attestation = run.offchain_attestation.ok_or(AssetError::NotFound)?;
+
+    let attestation_asset = Asset {
+        url_path: format!("/run/{}/attestation.json", run_id_hex),
+        content: offchain_attestation.into_bytes(),
+        encoding: ContentEncoding::Identity,
+        content_type: ContentType::JSON,
+    };
+
+    certify_and_update_assets(vec![attestation_asset.clone()]);
+
+    Ok(vec![attestation_asset])
+}
+
 pub fn render_default_assets(url_path: &str) -> Result<Vec<Asset>, AssetError> {
     let index_asset = render_index_html(
         url_path.to_string(),
@@ -16,6 +16,8 @@ pub fn init_chain_configs() {
                 name: "Sepolia".to_string(),
                 eth_usd_price: "0x123".to_string(),
                 eas_contract: "0xC2679fBD37d54388Ce493F1DB75320D236e1815e".to_string(),
+                eas_version: "0.26".to_string(),
+                schema_registry_contract: "0x0a7E2Ff54e76B8E6659aedc9103FB21c038050D0".to_string(),
                 payment_contract: "0xe498539Cad0E4325b88d6F6a1B89af7e4C8dF404".to_string(),
                 rpc_services: RpcServices::Custom {
                     chainId: 11155111,
@@ -37,6 +39,8 @@ pub fn init_chain_configs() {
                 name: "Optimism".to_string(),
                 eth_usd_price: "0x123".to_string(),
                 eas_contract: "0x4200000000000000000000000000000000000021".to_string(),
+                eas_version: "1.0.1".to_string(),
+                schema_registry_contract: "0x4200000000000000000000000000000000000020".to_string(),
                 payment_contract: "0x15a9a0f3bf24f9ff438f18f83ecc8b7cb2e15f9a".to_string(),
                 rpc_services: RpcServices::Custom {
                     chainId: 10,
@@ -58,6 +62,8 @@ pub fn init_chain_configs() {
                 name: "Base".to_string(),
                 eth_usd_price: "0x123".to_string(),
                 eas_contract: "0x4200000000000000000000000000000000000021".to_string(),
+                eas_version: "1.0.1".to_string(),
+                schema_registry_contract: "0x4200000000000000000000000000000000000020".to_string(),
                 payment_contract: "0x839ADFdFd2B038C3e4429abe54ac4DBB620a0BD1".to_string(),
                 rpc_services: RpcServices::Custom {
                     chainId: 8453,
@@ -79,6 +85,8 @@ pub fn init_chain_configs() {
                 name: "Arbitrum One".to_string(),
                 eth_usd_price: "0x123".to_string(),
                 eas_contract: "0xbD75f629A22Dc1ceD33dDA0b68c546A1c035c458".to_string(),
+                eas_version: "0.26".to_string(),
+                schema_registry_contract: "0xA310da9c5B885E7fb3fbA9D66E9Ba6Df512b78eB".to_string(),
                 payment_contract: "0x5601FE396f901442b1EAcAE5844431B7A4e2587D".to_string(),
                 rpc_services: RpcServices::Custom {
                     chainId: 42161,
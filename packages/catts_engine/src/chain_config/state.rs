@@ -7,6 +7,13 @@ pub fn get(chain_id: u32) -> Result<ChainConfig, ChainConfigError> {
         .ok_or(ChainConfigError::NotFound)
 }
 
+pub fn list() -> Vec<ChainConfig> {
+    let mut configs: Vec<ChainConfig> =
+        CHAIN_CONFIGS.with_borrow(|configs| configs.values().cloned().collect());
+    configs.sort_by_key(|config| config.chain_id);
+    configs
+}
+
 pub fn _set(config: ChainConfig) {
     CHAIN_CONFIGS.with_borrow_mut(|configs| {
         configs.insert(config.chain_id, config);
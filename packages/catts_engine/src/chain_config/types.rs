@@ -15,6 +15,10 @@ pub struct ChainConfig {
     pub name: String,
     pub eth_usd_price: String,
     pub eas_contract: String,
+    // Version of the EAS contract, part of the EIP-712 domain of off-chain
+    // attestations
+    pub eas_version: String,
+    pub schema_registry_contract: String,
     pub payment_contract: String,
     pub rpc_services: RpcServices,
     pub default_rpc_service: RpcService,
@@ -20,6 +20,7 @@ pub struct IndexedChangeLogItem {
 
 #[derive(Serialize, Deserialize, CandidType, Clone)]
 pub enum ChangeLogTypeName {
+    Batch,
     Recipe,
     Run,
     User,
@@ -0,0 +1,350 @@
+use crate::{
+    chain_config::ChainConfig,
+    eth_address::EthAddress,
+    evm::rpc::eth_call,
+    logger::{self},
+    recipe::{Recipe, RecipeId},
+    ETH_EAS_CONTRACT,
+};
+use anyhow::{anyhow, bail, Result};
+use candid::CandidType;
+use ethers_core::abi::Token;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::{decode_schema_data, eip712::to_hex, get_schema_uid};
+
+/// An attestation as stored by the EAS contract of a chain.
+pub struct OnchainAttestation {
+    pub uid: [u8; 32],
+    pub schema: [u8; 32],
+    pub time: u64,
+    pub expiration_time: u64,
+    pub revocation_time: u64,
+    pub ref_uid: [u8; 32],
+    pub recipient: [u8; 20],
+    pub attester: [u8; 20],
+    pub revocable: bool,
+    pub data: Vec<u8>,
+}
+
+fn bytes32(token: &Token) -> Result<[u8; 32]> {
+    match token {
+        Token::FixedBytes(bytes) => bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("Expected 32 bytes")),
+        _ => bail!("Expected bytes32"),
+    }
+}
+
+fn uint64(token: &Token) -> Result<u64> {
+    match token {
+        Token::Uint(value) if value.bits() <= 64 => Ok(value.as_u64()),
+        _ => bail!("Expected uint64"),
+    }
+}
+
+fn address(token: &Token) -> Result<[u8; 20]> {
+    match token {
+        Token::Address(address) => Ok(address.0),
+        _ => bail!("Expected address"),
+    }
+}
+
+impl OnchainAttestation {
+    /// Decodes the Attestation struct returned by getAttestation.
+    fn from_tokens(tokens: &[Token]) -> Result<Self> {
+        let fields = match tokens.first() {
+            Some(Token::Tuple(fields)) if fields.len() == 10 => fields,
+            _ => bail!("Unexpected getAttestation output"),
+        };
+
+        Ok(Self {
+            uid: bytes32(&fields[0])?,
+            schema: bytes32(&fields[1])?,
+            time: uint64(&fields[2])?,
+            expiration_time: uint64(&fields[3])?,
+            revocation_time: uint64(&fields[4])?,
+            ref_uid: bytes32(&fields[5])?,
+            recipient: address(&fields[6])?,
+            attester: address(&fields[7])?,
+            revocable: match fields[8] {
+                Token::Bool(revocable) => revocable,
+                _ => bail!("Expected bool"),
+            },
+            data: match fields[9] {
+                Token::Bytes(ref data) => data.clone(),
+                _ => bail!("Expected bytes"),
+            },
+        })
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revocation_time != 0
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expiration_time != 0 && self.expiration_time < now
+    }
+}
+
+/// Reads an attestation from the EAS contract of the chain. Returns None if
+/// no attestation exists with the uid, EAS returns an empty struct then.
+pub async fn get_attestation(
+    uid: &[u8; 32],
+    chain_config: &ChainConfig,
+) -> Result<Option<OnchainAttestation>> {
+    logger::debug("get_attestation");
+
+    let output = eth_call(
+        chain_config.eas_contract.clone(),
+        &Arc::clone(&ETH_EAS_CONTRACT),
+        "getAttestation",
+        &[Token::FixedBytes(uid.to_vec())],
+        chain_config,
+    )
+    .await?;
+
+    let attestation = OnchainAttestation::from_tokens(&output)?;
+    if attestation.uid == [0u8; 32] {
+        return Ok(None);
+    }
+    Ok(Some(attestation))
+}
+
+/// The result of verifying an attestation read back from EAS.
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone)]
+pub struct AttestationVerification {
+    pub uid: String,
+    pub chain_id: u32,
+    pub schema_uid: String,
+    // The recipe of the run or batch that created the attestation
+    pub recipe_id: Option<RecipeId>,
+    pub attester: String,
+    pub recipient: String,
+    pub time: u64,
+    pub expiration_time: u64,
+    pub revocation_time: u64,
+    pub ref_uid: String,
+    pub revocable: bool,
+    // ABI encoded attestation data, 0x prefixed
+    pub data: String,
+    // JSON array of schema items, set when the data matches the recipe schema
+    pub decoded_data: Option<String>,
+    pub is_valid: bool,
+    // Reasons the attestation is not a valid C-ATTS attestation
+    pub errors: Vec<String>,
+}
+
+/// Checks an attestation the engine created for a run or batch of the recipe
+/// against the recipe and the run or batch: the schema and data match, the
+/// canister or the delegated attester of the recipe attested it, it was made
+/// out to `recipient` and is neither revoked nor expired at `now`.
+pub fn verify_attestation(
+    attestation: &OnchainAttestation,
+    recipe: &Recipe,
+    canister_attester: &EthAddress,
+    recipient: &EthAddress,
+    chain_id: u32,
+    now: u64,
+) -> Result<AttestationVerification> {
+    let mut errors = Vec::new();
+    let mut decoded_data = None;
+
+    let schema_uid = get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?;
+    if attestation.schema != schema_uid {
+        errors.push("Schema does not belong to the recipe".to_string());
+    }
+
+    // Delegated attestations are signed by the attester of the recipe
+    let delegated_attester = recipe
+        .delegated_attester
+        .as_deref()
+        .map(EthAddress::new)
+        .transpose()?;
+    let attester = delegated_attester.as_ref().unwrap_or(canister_attester);
+    if attestation.attester != attester.as_byte_array() {
+        errors.push(format!("Attester is not {}", attester.as_str()));
+    }
+
+    match decode_schema_data(&recipe.schema, &attestation.data) {
+        Ok(items) => decoded_data = Some(serde_json::to_string(&items)?),
+        Err(err) => errors.push(format!("Data does not match the schema: {}", err)),
+    }
+
+    if attestation.recipient != recipient.as_byte_array() {
+        errors.push(format!("Recipient is not {}", recipient.as_str()));
+    }
+
+    if attestation.is_revoked() {
+        errors.push("Attestation is revoked".to_string());
+    }
+
+    if attestation.is_expired(now) {
+        errors.push("Attestation is expired".to_string());
+    }
+
+    Ok(AttestationVerification {
+        uid: to_hex(&attestation.uid),
+        chain_id,
+        schema_uid: to_hex(&attestation.schema),
+        recipe_id: Some(recipe.id),
+        attester: to_hex(&attestation.attester),
+        recipient: to_hex(&attestation.recipient),
+        time: attestation.time,
+        expiration_time: attestation.expiration_time,
+        revocation_time: attestation.revocation_time,
+        ref_uid: to_hex(&attestation.ref_uid),
+        revocable: attestation.revocable,
+        data: to_hex(&attestation.data),
+        decoded_data,
+        is_valid: errors.is_empty(),
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::RecipePublishState;
+    use ethers_core::{abi::encode, types::U256};
+
+    const SCHEMA: &str = "uint256 score";
+    const RESOLVER: &str = "0x0000000000000000000000000000000000000000";
+    const CANISTER: &str = "0x1111111111111111111111111111111111111111";
+    const OTHER: &str = "0x2222222222222222222222222222222222222222";
+    const RECIPIENT: &str = "0x3333333333333333333333333333333333333333";
+    const NOW: u64 = 1_700_000_000;
+
+    fn recipe(delegated_attester: Option<&str>) -> Recipe {
+        Recipe {
+            id: [1u8; 12],
+            name: "test-recipe".to_string(),
+            creator: OTHER.to_string(),
+            maintainers: None,
+            pending_creator: None,
+            created: 0,
+            description: None,
+            keywords: None,
+            queries: vec![],
+            processor: "return [];".to_string(),
+            processor_limits: None,
+            stdlib_version: None,
+            integer_strings: None,
+            tests: None,
+            schema: SCHEMA.to_string(),
+            resolver: RESOLVER.to_string(),
+            validity_seconds: None,
+            recipient_rule: None,
+            delegated_attester: delegated_attester.map(str::to_string),
+            chain_ids: None,
+            schema_registrations: None,
+            revokable: false,
+            publish_state: RecipePublishState::Published,
+        }
+    }
+
+    fn attestation(attester: &str) -> OnchainAttestation {
+        OnchainAttestation {
+            uid: [2u8; 32],
+            schema: get_schema_uid(SCHEMA, RESOLVER, false).unwrap(),
+            time: NOW,
+            expiration_time: 0,
+            revocation_time: 0,
+            ref_uid: [0u8; 32],
+            recipient: EthAddress::new(RECIPIENT).unwrap().as_byte_array(),
+            attester: EthAddress::new(attester).unwrap().as_byte_array(),
+            revocable: false,
+            data: encode(&[Token::Uint(U256::from(42))]),
+        }
+    }
+
+    fn verify(attestation: &OnchainAttestation, recipe: &Recipe) -> AttestationVerification {
+        let canister = EthAddress::new(CANISTER).unwrap();
+        let recipient = EthAddress::new(RECIPIENT).unwrap();
+        verify_attestation(attestation, recipe, &canister, &recipient, 10, NOW).unwrap()
+    }
+
+    #[test]
+    fn verify_valid_attestation() {
+        let verification = verify(&attestation(CANISTER), &recipe(None));
+        assert!(verification.is_valid, "{:?}", verification.errors);
+        assert_eq!(verification.recipe_id, Some([1u8; 12]));
+        assert!(verification.decoded_data.unwrap().contains("score"));
+    }
+
+    #[test]
+    fn verify_delegated_attestation() {
+        let verification = verify(&attestation(OTHER), &recipe(Some(OTHER)));
+        assert!(verification.is_valid, "{:?}", verification.errors);
+    }
+
+    #[test]
+    fn verify_forged_attester() {
+        let verification = verify(&attestation(OTHER), &recipe(None));
+        assert!(!verification.is_valid);
+        assert_eq!(
+            verification.errors,
+            vec![format!("Attester is not {}", CANISTER)]
+        );
+    }
+
+    #[test]
+    fn verify_forged_delegated_attester() {
+        let verification = verify(&attestation(CANISTER), &recipe(Some(OTHER)));
+        assert!(!verification.is_valid);
+        assert_eq!(
+            verification.errors,
+            vec![format!("Attester is not {}", OTHER)]
+        );
+    }
+
+    #[test]
+    fn verify_other_recipient() {
+        let mut attestation = attestation(CANISTER);
+        attestation.recipient = EthAddress::new(OTHER).unwrap().as_byte_array();
+        let verification = verify(&attestation, &recipe(None));
+        assert!(!verification.is_valid);
+        assert_eq!(
+            verification.errors,
+            vec![format!("Recipient is not {}", RECIPIENT)]
+        );
+    }
+
+    #[test]
+    fn verify_forged_schema() {
+        let mut attestation = attestation(CANISTER);
+        attestation.schema = [4u8; 32];
+        let verification = verify(&attestation, &recipe(None));
+        assert!(!verification.is_valid);
+        assert_eq!(
+            verification.errors,
+            vec!["Schema does not belong to the recipe".to_string()]
+        );
+    }
+
+    #[test]
+    fn verify_forged_data() {
+        let mut attestation = attestation(CANISTER);
+        attestation.data = vec![1, 2, 3];
+        let verification = verify(&attestation, &recipe(None));
+        assert!(!verification.is_valid);
+        assert!(verification.decoded_data.is_none());
+    }
+
+    #[test]
+    fn verify_revoked_and_expired_attestation() {
+        let mut attestation = attestation(CANISTER);
+        attestation.revocation_time = NOW - 10;
+        attestation.expiration_time = NOW - 1;
+        let verification = verify(&attestation, &recipe(None));
+        assert_eq!(
+            verification.errors,
+            vec![
+                "Attestation is revoked".to_string(),
+                "Attestation is expired".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{
+    chain_config::{self, ChainConfig},
+    eth_address::EthAddress,
+    evm::{
+        rpc::{eth_call, eth_transaction},
+        util::{nat_to_u256, u256_to_nat},
+    },
+    logger::{self},
+    recipe::Recipe,
+    run::{Run, RunDelegation},
+    time::time,
+    ETH_EAS_CONTRACT,
+};
+use anyhow::{anyhow, bail, Result};
+use ethers_core::{
+    abi::{encode, ethereum_types::H160, Token},
+    types::{Signature, H256, U256},
+    utils::{hex, keccak256},
+};
+use serde_json::{json, Value};
+use std::{str::FromStr, sync::Arc};
+
+use super::{
+    attestation_ref_uid,
+    eip712::{domain_json, signing_hash, to_hex},
+    encode_abi_data, expiration_time, get_schema_uid, parse_uid, run_gas_settings,
+    AttestationOutput,
+};
+
+// Domain name of the EAS contract, attestations are signed for the EAS
+// version of the chain
+const EIP712_DOMAIN_NAME: &str = "EAS";
+
+// Time the attester has to sign a prepared attestation
+pub const DELEGATION_SIGNATURE_TTL_SECONDS: u32 = 7 * 24 * 60 * 60; // 7 days
+
+const ATTEST_TYPE: &str = "Attest(bytes32 schema,address recipient,uint64 expirationTime,bool revocable,bytes32 refUID,bytes data,uint256 nonce)";
+
+/// The message of a delegated attestation, signed by the attester as EIP-712
+/// typed data.
+pub struct DelegatedAttestationMessage {
+    pub schema: [u8; 32],
+    pub recipient: [u8; 20],
+    pub expiration_time: u64,
+    pub revocable: bool,
+    pub ref_uid: [u8; 32],
+    pub data: Vec<u8>,
+    pub nonce: U256,
+}
+
+impl DelegatedAttestationMessage {
+    pub fn new(
+        recipe: &Recipe,
+        output: &AttestationOutput,
+        recipient: &EthAddress,
+        run_ref_uid: Option<&str>,
+        nonce: U256,
+    ) -> Result<Self> {
+        Ok(Self {
+            schema: get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?,
+            recipient: recipient.as_byte_array(),
+            expiration_time: expiration_time(recipe, output)?,
+            revocable: recipe.revokable,
+            ref_uid: attestation_ref_uid(run_ref_uid, output)?,
+            data: encode_abi_data(&output.data, recipe.integer_strings())?,
+            nonce,
+        })
+    }
+
+    /// Restores the message prepared for a run.
+    pub fn from_run_delegation(recipe: &Recipe, delegation: &RunDelegation) -> Result<Self> {
+        Ok(Self {
+            schema: get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?,
+            recipient: EthAddress::new(&delegation.recipient)?.as_byte_array(),
+            expiration_time: delegation.expiration_time,
+            revocable: delegation.revocable,
+            ref_uid: parse_uid(&delegation.ref_uid)?,
+            data: hex::decode(&delegation.data).map_err(|_| anyhow!("Invalid delegation data"))?,
+            nonce: nat_to_u256(&delegation.nonce),
+        })
+    }
+
+    fn struct_hash(&self) -> [u8; 32] {
+        keccak256(encode(&[
+            Token::FixedBytes(keccak256(ATTEST_TYPE).to_vec()),
+            Token::FixedBytes(self.schema.to_vec()),
+            Token::Address(H160(self.recipient)),
+            Token::Uint(self.expiration_time.into()),
+            Token::Bool(self.revocable),
+            Token::FixedBytes(self.ref_uid.to_vec()),
+            Token::FixedBytes(keccak256(&self.data).to_vec()),
+            Token::Uint(self.nonce),
+        ]))
+    }
+
+    /// The EIP-712 hash the attester signs.
+    pub fn signing_hash(&self, chain_config: &ChainConfig) -> Result<[u8; 32]> {
+        signing_hash(EIP712_DOMAIN_NAME, chain_config, &self.struct_hash())
+    }
+
+    /// The typed data to sign with `eth_signTypedData_v4`.
+    pub fn to_typed_data_json(&self, chain_config: &ChainConfig) -> Value {
+        json!({
+            "domain": domain_json(EIP712_DOMAIN_NAME, chain_config),
+            "primaryType": "Attest",
+            "types": {
+                "Attest": [
+                    { "name": "schema", "type": "bytes32" },
+                    { "name": "recipient", "type": "address" },
+                    { "name": "expirationTime", "type": "uint64" },
+                    { "name": "revocable", "type": "bool" },
+                    { "name": "refUID", "type": "bytes32" },
+                    { "name": "data", "type": "bytes" },
+                    { "name": "nonce", "type": "uint256" },
+                ],
+            },
+            "message": {
+                "schema": to_hex(&self.schema),
+                "recipient": to_hex(&self.recipient),
+                "expirationTime": self.expiration_time.to_string(),
+                "revocable": self.revocable,
+                "refUID": to_hex(&self.ref_uid),
+                "data": to_hex(&self.data),
+                "nonce": self.nonce.to_string(),
+            },
+        })
+    }
+
+    pub fn to_run_delegation(
+        &self,
+        attester: &EthAddress,
+        chain_config: &ChainConfig,
+    ) -> RunDelegation {
+        RunDelegation {
+            attester: attester.to_string(),
+            recipient: to_hex(&self.recipient),
+            expiration_time: self.expiration_time,
+            revocable: self.revocable,
+            ref_uid: to_hex(&self.ref_uid),
+            data: to_hex(&self.data),
+            nonce: u256_to_nat(&self.nonce),
+            typed_data: self.to_typed_data_json(chain_config).to_string(),
+            expires: Some(time() + DELEGATION_SIGNATURE_TTL_SECONDS),
+            submitted: None,
+        }
+    }
+
+    /// The DelegatedAttestationRequest of attestByDelegation.
+    fn to_request(&self, signature: &Signature, attester: &EthAddress) -> Token {
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        signature.r.to_big_endian(&mut r);
+        signature.s.to_big_endian(&mut s);
+
+        Token::Tuple(vec![
+            Token::FixedBytes(self.schema.to_vec()), // schema
+            Token::Tuple(vec![
+                Token::Address(H160(self.recipient)),     // recipient
+                Token::Uint(self.expiration_time.into()), // expirationTime
+                Token::Bool(self.revocable),              // revocable
+                Token::FixedBytes(self.ref_uid.to_vec()), // refUID
+                Token::Bytes(self.data.clone()),          // data
+                Token::Uint((0).into()),                  // value
+            ]),
+            Token::Tuple(vec![
+                Token::Uint(signature.v.into()), // v
+                Token::FixedBytes(r.to_vec()),   // r
+                Token::FixedBytes(s.to_vec()),   // s
+            ]),
+            Token::Address(H160(attester.as_byte_array())), // attester
+        ])
+    }
+}
+
+/// The current EAS nonce of the attester, delegated attestations must be
+/// signed with it.
+async fn get_attester_nonce(attester: &EthAddress, chain_config: &ChainConfig) -> Result<U256> {
+    let output = eth_call(
+        chain_config.eas_contract.clone(),
+        &Arc::clone(&ETH_EAS_CONTRACT),
+        "getNonce",
+        &[Token::Address(H160(attester.as_byte_array()))],
+        chain_config,
+    )
+    .await?;
+
+    match output.first() {
+        Some(Token::Uint(nonce)) => Ok(*nonce),
+        _ => bail!("Unexpected getNonce output"),
+    }
+}
+
+/// Prepares a delegated attestation for the attester of the recipe to sign.
+pub async fn prepare_delegated_attestation(
+    recipe: &Recipe,
+    output: &AttestationOutput,
+    recipient: &EthAddress,
+    run_ref_uid: Option<&str>,
+    attester: &EthAddress,
+    chain_config: &ChainConfig,
+) -> Result<RunDelegation> {
+    logger::debug("prepare_delegated_attestation");
+
+    let nonce = get_attester_nonce(attester, chain_config).await?;
+    let message = DelegatedAttestationMessage::new(recipe, output, recipient, run_ref_uid, nonce)?;
+
+    Ok(message.to_run_delegation(attester, chain_config))
+}
+
+/// Parses a 65 byte hex signature and checks that the attester of the
+/// delegation signed it.
+pub fn verify_delegation_signature(
+    recipe: &Recipe,
+    delegation: &RunDelegation,
+    signature: &str,
+    chain_config: &ChainConfig,
+) -> Result<Signature> {
+    let mut signature =
+        Signature::from_str(signature).map_err(|_| anyhow!("Invalid signature format"))?;
+
+    // EAS expects a recovery id of 27 or 28
+    if signature.v < 27 {
+        signature.v += 27;
+    }
+
+    let message = DelegatedAttestationMessage::from_run_delegation(recipe, delegation)?;
+    let hash = H256::from(message.signing_hash(chain_config)?);
+    let signer = signature
+        .recover(hash)
+        .map_err(|_| anyhow!("Signature can't be recovered"))?;
+
+    let attester = EthAddress::new(&delegation.attester)?;
+    if signer.0 != attester.as_byte_array() {
+        bail!("Signature is not from the attester {}", delegation.attester);
+    }
+
+    Ok(signature)
+}
+
+/// Sends the delegated attestation of the run with the signature of the
+/// attester, the canister pays the gas as relayer. Returns the transaction
+/// hash.
+pub async fn create_delegated_attestation(
+    recipe: &Recipe,
+    run: &Run,
+    signature: &Signature,
+) -> Result<String> {
+    logger::debug("create_delegated_attestation");
+
+    let delegation = run
+        .delegation
+        .as_ref()
+        .ok_or(anyhow!("Run has no delegated attestation"))?;
+    let message = DelegatedAttestationMessage::from_run_delegation(recipe, delegation)?;
+    let attester = EthAddress::new(&delegation.attester)?;
+
+    let (gas, base_fee_per_gas, max_priority_fee_per_gas) = run_gas_settings(run, run.chain_id)?;
+
+    let chain_config = chain_config::get(run.chain_id)?;
+
+    // EAS rejects the signature once the attester has used the nonce, also
+    // by attesting elsewhere
+    let nonce = get_attester_nonce(&attester, &chain_config).await?;
+    if nonce != message.nonce {
+        bail!(
+            "The attestation was signed for nonce {}, the attester nonce is now {}",
+            message.nonce,
+            nonce
+        );
+    }
+
+    Ok(eth_transaction(
+        chain_config.eas_contract.clone(),
+        &Arc::clone(&ETH_EAS_CONTRACT),
+        "attestByDelegation",
+        &[message.to_request(signature, &attester)],
+        gas,
+        base_fee_per_gas,
+        Some(max_priority_fee_per_gas),
+        &chain_config,
+    )
+    .await?)
+}
@@ -0,0 +1,48 @@
+use crate::{chain_config::ChainConfig, eth_address::EthAddress};
+use anyhow::Result;
+use ethers_core::{
+    abi::{encode, ethereum_types::H160, Token},
+    utils::keccak256,
+};
+use serde_json::{json, Value};
+
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// Hash of the EIP-712 domain of the EAS contract of the chain.
+pub fn domain_separator(name: &str, chain_config: &ChainConfig) -> Result<[u8; 32]> {
+    let verifying_contract = EthAddress::new(&chain_config.eas_contract)?;
+    Ok(keccak256(encode(&[
+        Token::FixedBytes(keccak256(EIP712_DOMAIN_TYPE).to_vec()),
+        Token::FixedBytes(keccak256(name).to_vec()),
+        Token::FixedBytes(keccak256(&chain_config.eas_version).to_vec()),
+        Token::Uint(chain_config.chain_id.into()),
+        Token::Address(H160(verifying_contract.as_byte_array())),
+    ])))
+}
+
+/// The EIP-712 domain as JSON, in the format expected by wallets.
+pub fn domain_json(name: &str, chain_config: &ChainConfig) -> Value {
+    json!({
+        "name": name,
+        "version": chain_config.eas_version,
+        "chainId": chain_config.chain_id.to_string(),
+        "verifyingContract": chain_config.eas_contract,
+    })
+}
+
+/// The hash that is signed, from the domain and the hash of the message.
+pub fn signing_hash(
+    name: &str,
+    chain_config: &ChainConfig,
+    struct_hash: &[u8; 32],
+) -> Result<[u8; 32]> {
+    let mut message = vec![0x19, 0x01];
+    message.extend_from_slice(&domain_separator(name, chain_config)?);
+    message.extend_from_slice(struct_hash);
+    Ok(keccak256(message))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", ethers_core::utils::hex::encode(bytes))
+}
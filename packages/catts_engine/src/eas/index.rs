@@ -0,0 +1,104 @@
+use candid::{CandidType, Decode, Encode};
+use ethers_core::utils::hex;
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+use crate::{batch::Batch, recipe::RecipeId, run::Run, ATTESTATION_INDEX, BATCHES, RUNS};
+
+use super::parse_uid;
+
+// Keys are the zero padded chain id followed by the hex encoded attestation
+// uid. Only on-chain attestations created by runs and batches of the engine
+// are indexed.
+
+/// The run or batch data an attestation created by the engine is checked
+/// against.
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug)]
+pub struct IndexedAttestation {
+    pub recipe_id: RecipeId,
+    pub recipient: String,
+}
+
+impl Storable for IndexedAttestation {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+fn key(chain_id: u32, uid: &[u8; 32]) -> String {
+    format!("{:010}{}", chain_id, hex::encode(uid))
+}
+
+fn insert(chain_id: u32, uid: &str, recipe_id: RecipeId, recipient: &str) {
+    let Ok(uid) = parse_uid(uid) else {
+        return;
+    };
+    ATTESTATION_INDEX.with_borrow_mut(|index| {
+        index.insert(
+            key(chain_id, &uid),
+            IndexedAttestation {
+                recipe_id,
+                recipient: recipient.to_string(),
+            },
+        );
+    });
+}
+
+/// Indexes the attestation uids of the run, on all of its chains. Off-chain
+/// attestations are not stored by EAS and are not indexed.
+pub fn add_run(run: &Run) {
+    if run.is_offchain() {
+        return;
+    }
+    if let Some(ref uid) = run.attestation_uid {
+        insert(run.chain_id, uid, run.recipe_id, run.recipient());
+    }
+    for chain in run.additional_chains.iter().flatten() {
+        if let Some(ref uid) = chain.attestation_uid {
+            insert(chain.chain_id, uid, run.recipe_id, run.recipient());
+        }
+    }
+}
+
+/// Indexes the attestation uids of the batch recipients.
+pub fn add_batch(batch: &Batch) {
+    for recipient in batch.recipients.iter() {
+        if let Some(ref uid) = recipient.attestation_uid {
+            insert(batch.chain_id, uid, batch.recipe_id, &recipient.address);
+        }
+    }
+}
+
+/// An attestation created by the engine, none for attestations created by
+/// anyone else.
+pub fn get(chain_id: u32, uid: &[u8; 32]) -> Option<IndexedAttestation> {
+    ATTESTATION_INDEX.with_borrow(|index| index.get(&key(chain_id, uid)))
+}
+
+/// Recreates the index from the runs and batches in stable memory.
+pub fn rebuild_index() {
+    ATTESTATION_INDEX.with_borrow_mut(|index| {
+        let keys: Vec<String> = index.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            index.remove(&key);
+        }
+    });
+
+    RUNS.with_borrow(|runs| {
+        for (_, run) in runs.iter() {
+            add_run(&run);
+        }
+    });
+    BATCHES.with_borrow(|batches| {
+        for (_, batch) in batches.iter() {
+            add_batch(&batch);
+        }
+    });
+}
@@ -0,0 +1,855 @@
+pub mod attestation;
+pub mod delegation;
+pub mod eip712;
+pub mod index;
+pub mod offchain;
+pub mod onchain_query;
+pub mod registry;
+pub mod rpc;
+pub mod schema;
+
+use crate::{
+    chain_config::{self},
+    eth_address::EthAddress,
+    evm::rpc::eth_transaction,
+    graphql::replace_dynamic_variables,
+    logger::{self},
+    recipe::{Recipe, RecipeIntegerStrings, RecipeQuery},
+    run::Run,
+    time::time,
+    ETH_DEFAULT_CALL_CYCLES, ETH_EAS_CONTRACT, QUERY_PROXY_URL,
+};
+use anyhow::{anyhow, bail, Result};
+use blake2::{
+    digest::{Update, VariableOutput},
+    Blake2bVar,
+};
+use candid::{CandidType, Nat};
+use ethers_core::{
+    abi::{decode, encode, encode_packed, ethereum_types::H160, Address, ParamType, Token},
+    types::{I256, U256},
+    utils::{hex, keccak256},
+};
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpMethod, TransformContext,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{str::FromStr, sync::Arc};
+use thiserror::Error;
+
+use schema::{parse_type, SchemaType};
+
+// Struct to represent the SchemaItem
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchemaItem {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_field: String, // Note: Use `type_field` because `type` is a reserved word in Rust
+    pub value: Value,
+}
+
+/// Processor output is either a JSON array of schema items or an object with
+/// the schema items as `data`, next to options for the attestation.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawProcessorOutput {
+    Items(Vec<Value>),
+    Object {
+        data: Vec<Value>,
+        #[serde(rename = "expirationTime")]
+        expiration_time: Option<Value>,
+        #[serde(rename = "refUID")]
+        ref_uid: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug)]
+pub struct AttestationOutput {
+    // JSON array of schema items
+    pub data: String,
+    // Unix time in seconds
+    pub expiration_time: Option<u64>,
+    // 0x prefixed, lowercase
+    pub ref_uid: Option<String>,
+}
+
+/// Parses processor output. A refUID returned by the processor has to point
+/// at an attestation found in the query result the processor consumed.
+pub fn parse_processor_output(output: &str, query_result: &str) -> Result<AttestationOutput> {
+    let raw: RawProcessorOutput = serde_json::from_str(output).map_err(|_| {
+        anyhow!("Output must be a JSON array of schema items or an object with the items as data")
+    })?;
+
+    let (data, expiration_time, ref_uid) = match raw {
+        RawProcessorOutput::Items(items) => (items, None, None),
+        RawProcessorOutput::Object {
+            data,
+            expiration_time,
+            ref_uid,
+        } => (data, expiration_time, ref_uid),
+    };
+
+    let expiration_time = match expiration_time {
+        None | Some(Value::Null) => None,
+        Some(value) => {
+            let (negative, time) = parse_integer(&value, RecipeIntegerStrings::Decimal)
+                .map_err(|e| anyhow!("Invalid expirationTime: {}", e))?;
+            if negative || time > U256::from(u64::MAX) {
+                bail!("Invalid expirationTime: {} is out of range", value);
+            }
+            Some(time.as_u64())
+        }
+    };
+
+    let ref_uid = match ref_uid {
+        None => None,
+        Some(ref_uid) => {
+            let ref_uid = parse_uid(&ref_uid).map_err(|e| anyhow!("Invalid refUID: {}", e))?;
+            let query_result: Value = serde_json::from_str(query_result)
+                .map_err(|_| anyhow!("Invalid refUID: query result is not valid JSON"))?;
+            let ref_uid_hex = format!("0x{}", hex::encode(ref_uid));
+            if !contains_attestation(&query_result, &ref_uid) {
+                bail!(
+                    "refUID {} is not an attestation found in the query result",
+                    ref_uid_hex
+                );
+            }
+            Some(ref_uid_hex)
+        }
+    };
+
+    Ok(AttestationOutput {
+        data: Value::Array(data).to_string(),
+        expiration_time,
+        ref_uid,
+    })
+}
+
+/// Whether an object in the query result identifies the attestation by its
+/// `id` (EAS GraphQL API) or `uid` field.
+fn contains_attestation(value: &Value, uid: &[u8; 32]) -> bool {
+    match value {
+        Value::Object(obj) => {
+            ["id", "uid"].iter().any(|field| {
+                obj.get(*field)
+                    .and_then(Value::as_str)
+                    .and_then(|id| parse_uid(id).ok())
+                    .is_some_and(|id| &id == uid)
+            }) || obj.values().any(|value| contains_attestation(value, uid))
+        }
+        Value::Array(values) => values.iter().any(|value| contains_attestation(value, uid)),
+        _ => false,
+    }
+}
+
+pub fn parse_uid(uid: &str) -> Result<[u8; 32]> {
+    hex::decode(uid)
+        .ok()
+        .and_then(|uid| uid.try_into().ok())
+        .ok_or(anyhow!("{} is not a 32 byte hex string", uid))
+}
+
+/// The refUID of an attestation. A run refreshing a previous run references
+/// its attestation, otherwise the processor may reference one.
+pub fn attestation_ref_uid(
+    run_ref_uid: Option<&str>,
+    output: &AttestationOutput,
+) -> Result<[u8; 32]> {
+    match (run_ref_uid, output.ref_uid.as_deref()) {
+        (Some(run_ref_uid), Some(output_ref_uid))
+            if !run_ref_uid.eq_ignore_ascii_case(output_ref_uid) =>
+        {
+            bail!(
+                "refUID {} returned by the processor conflicts with the refreshed attestation {}",
+                output_ref_uid,
+                run_ref_uid
+            )
+        }
+        (Some(uid), _) | (None, Some(uid)) => parse_uid(uid),
+        (None, None) => Ok([0u8; 32]),
+    }
+}
+
+/// The expiration time of an attestation created now. A time returned by the
+/// processor takes precedence over the validity period of the recipe, 0 means
+/// the attestation doesn't expire.
+pub fn expiration_time(recipe: &Recipe, output: &AttestationOutput) -> Result<u64> {
+    let now = time() as u64;
+    match (output.expiration_time, recipe.validity_seconds) {
+        (Some(expiration_time), _) if expiration_time <= now => {
+            bail!("expirationTime {} is not in the future", expiration_time)
+        }
+        (Some(expiration_time), _) => Ok(expiration_time),
+        (None, Some(validity_seconds)) => Ok(now + validity_seconds),
+        (None, None) => Ok(0),
+    }
+}
+
+fn parse_hex_bytes(value: &Value) -> Result<Vec<u8>, String> {
+    let Value::String(value) = value else {
+        return Err(format!("expected a hex string, got {}", value));
+    };
+    hex::decode(value).map_err(|e| format!("invalid hex value \"{}\": {}", value, e))
+}
+
+// Integers are given as JSON numbers, decimal strings or 0x prefixed hex
+// strings. Unprefixed strings are hex for recipes saved before decimal strings
+// were supported. Returns the sign and the magnitude.
+fn parse_integer(
+    value: &Value,
+    integer_strings: RecipeIntegerStrings,
+) -> Result<(bool, U256), String> {
+    match value {
+        Value::Number(number) => {
+            if let Some(n) = number.as_u64() {
+                Ok((false, U256::from(n)))
+            } else if let Some(n) = number.as_i64() {
+                Ok((true, U256::from(n.unsigned_abs())))
+            } else {
+                Err(format!(
+                    "{} is not an integer, use a string for large numbers",
+                    number
+                ))
+            }
+        }
+        Value::String(string) => {
+            let (negative, digits) = match string.trim().strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, string.trim()),
+            };
+            let magnitude = match digits.strip_prefix("0x") {
+                Some(hex_digits) if !hex_digits.is_empty() => {
+                    U256::from_str_radix(hex_digits, 16).ok()
+                }
+                Some(_) => None,
+                None if digits.is_empty() => None,
+                None => match integer_strings {
+                    RecipeIntegerStrings::Hex => U256::from_str_radix(digits, 16).ok(),
+                    RecipeIntegerStrings::Decimal if digits.chars().all(|c| c.is_ascii_digit()) => {
+                        U256::from_dec_str(digits).ok()
+                    }
+                    RecipeIntegerStrings::Decimal => None,
+                },
+            }
+            .ok_or_else(|| format!("invalid integer \"{}\"", string))?;
+            Ok((negative && !magnitude.is_zero(), magnitude))
+        }
+        value => Err(format!("expected an integer, got {}", value)),
+    }
+}
+
+fn value_to_token(
+    kind: &SchemaType,
+    value: &Value,
+    path: &str,
+    integer_strings: RecipeIntegerStrings,
+) -> Result<Token> {
+    let error = |message: String| anyhow!("Field \"{}\": {}", path, message);
+
+    let token = match kind {
+        SchemaType::Address => match value {
+            Value::String(address) => Token::Address(
+                Address::from_str(address)
+                    .map_err(|e| error(format!("invalid address \"{}\": {}", address, e)))?,
+            ),
+            value => return Err(error(format!("expected an address, got {}", value))),
+        },
+        SchemaType::Bool => match value {
+            Value::Bool(value) => Token::Bool(*value),
+            value => return Err(error(format!("expected a bool, got {}", value))),
+        },
+        SchemaType::String => match value {
+            Value::String(value) => Token::String(value.clone()),
+            value => return Err(error(format!("expected a string, got {}", value))),
+        },
+        SchemaType::Bytes => Token::Bytes(parse_hex_bytes(value).map_err(error)?),
+        SchemaType::FixedBytes(size) => {
+            let bytes = parse_hex_bytes(value).map_err(error)?;
+            if bytes.len() != *size {
+                return Err(error(format!(
+                    "expected {} bytes, got {}",
+                    size,
+                    bytes.len()
+                )));
+            }
+            Token::FixedBytes(bytes)
+        }
+        SchemaType::Uint(bits) => {
+            let (negative, magnitude) = parse_integer(value, integer_strings).map_err(error)?;
+            if negative {
+                return Err(error(format!("{} can't be negative", kind)));
+            }
+            if *bits < 256 && magnitude >= U256::one() << *bits {
+                return Err(error(format!("{} is out of range for {}", value, kind)));
+            }
+            Token::Uint(magnitude)
+        }
+        SchemaType::Int(bits) => {
+            let (negative, magnitude) = parse_integer(value, integer_strings).map_err(error)?;
+            // Negative values go down to -2^(bits-1), positive values up to
+            // 2^(bits-1) - 1
+            let limit = U256::one() << (*bits - 1);
+            if magnitude > limit || (!negative && magnitude == limit) {
+                return Err(error(format!("{} is out of range for {}", value, kind)));
+            }
+            let raw = if negative {
+                I256::from_raw(magnitude).wrapping_neg().into_raw()
+            } else {
+                magnitude
+            };
+            Token::Int(raw)
+        }
+        SchemaType::Array(inner) => {
+            let Value::Array(values) = value else {
+                return Err(error(format!("expected an array, got {}", value)));
+            };
+            Token::Array(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        value_to_token(inner, value, &format!("{}[{}]", path, i), integer_strings)
+                    })
+                    .collect::<Result<Vec<Token>>>()?,
+            )
+        }
+        SchemaType::FixedArray(inner, size) => {
+            let Value::Array(values) = value else {
+                return Err(error(format!("expected an array, got {}", value)));
+            };
+            if values.len() != *size {
+                return Err(error(format!(
+                    "expected {} elements, got {}",
+                    size,
+                    values.len()
+                )));
+            }
+            Token::FixedArray(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        value_to_token(inner, value, &format!("{}[{}]", path, i), integer_strings)
+                    })
+                    .collect::<Result<Vec<Token>>>()?,
+            )
+        }
+        // Tuples are given as arrays in component order or as objects keyed by
+        // component name
+        SchemaType::Tuple(fields) => {
+            let values: Vec<&Value> = match value {
+                Value::Array(values) if values.len() == fields.len() => values.iter().collect(),
+                Value::Array(values) => {
+                    return Err(error(format!(
+                        "expected {} tuple components, got {}",
+                        fields.len(),
+                        values.len()
+                    )))
+                }
+                Value::Object(object) => fields
+                    .iter()
+                    .map(|field| {
+                        object.get(&field.name).ok_or_else(|| {
+                            error(format!("missing tuple component \"{}\"", field.name))
+                        })
+                    })
+                    .collect::<Result<Vec<&Value>>>()?,
+                value => return Err(error(format!("expected a tuple, got {}", value))),
+            };
+            Token::Tuple(
+                fields
+                    .iter()
+                    .zip(values)
+                    .enumerate()
+                    .map(|(i, (field, value))| {
+                        let component = if field.name.is_empty() {
+                            i.to_string()
+                        } else {
+                            field.name.clone()
+                        };
+                        value_to_token(
+                            &field.kind,
+                            value,
+                            &format!("{}.{}", path, component),
+                            integer_strings,
+                        )
+                    })
+                    .collect::<Result<Vec<Token>>>()?,
+            )
+        }
+    };
+
+    Ok(token)
+}
+
+/// ABI encodes processor output, a JSON array of schema items. Values are
+/// checked against the type of each item.
+pub fn encode_abi_data(json_data: &str, integer_strings: RecipeIntegerStrings) -> Result<Vec<u8>> {
+    let schema_items: Vec<SchemaItem> =
+        serde_json::from_str(json_data).map_err(|e| anyhow!("Failed to parse JSON: {}", e))?;
+
+    let tokens = schema_items
+        .iter()
+        .map(|item| {
+            let kind = parse_type(&item.type_field)
+                .map_err(|e| anyhow!("Field \"{}\": {}", item.name, e))?;
+            value_to_token(&kind, &item.value, &item.name, integer_strings)
+        })
+        .collect::<Result<Vec<Token>>>()?;
+
+    Ok(encode(&tokens))
+}
+
+// A schema value decoded from ABI encoded attestation data
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug)]
+pub struct DecodedSchemaItem {
+    pub name: String,
+    pub type_name: String,
+    pub value: String,
+}
+
+fn token_to_string(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("{:?}", address),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        Token::Int(int) => I256::from_raw(*int).to_string(),
+        Token::Array(tokens) | Token::FixedArray(tokens) => format!(
+            "[{}]",
+            tokens
+                .iter()
+                .map(token_to_string)
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        Token::Tuple(tokens) => format!(
+            "({})",
+            tokens
+                .iter()
+                .map(token_to_string)
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        token => token.to_string(),
+    }
+}
+
+/// Decodes ABI encoded data using the names and types of the schema items
+/// in the JSON data.
+pub fn decode_abi_data(json_data: &str, data: &[u8]) -> Result<Vec<DecodedSchemaItem>> {
+    let schema_items: Vec<SchemaItem> =
+        serde_json::from_str(json_data).map_err(|e| anyhow!("Failed to parse JSON: {}", e))?;
+
+    let param_types = schema_items
+        .iter()
+        .map(|item| {
+            parse_type(&item.type_field)
+                .map(|kind| kind.to_param_type())
+                .map_err(|e| anyhow!("Field \"{}\": {}", item.name, e))
+        })
+        .collect::<Result<Vec<ParamType>>>()?;
+
+    let tokens =
+        decode(&param_types, data).map_err(|e| anyhow!("Failed to decode ABI data: {}", e))?;
+
+    Ok(schema_items
+        .iter()
+        .zip(tokens.iter())
+        .map(|(item, token)| DecodedSchemaItem {
+            name: item.name.clone(),
+            type_name: item.type_field.clone(),
+            value: token_to_string(token),
+        })
+        .collect())
+}
+
+// The inverse of `value_to_token`. Integers become decimal strings to not lose
+// precision in JavaScript, tuples with named components become objects.
+fn token_to_value(kind: &SchemaType, token: Token) -> Value {
+    match (kind, token) {
+        (_, Token::Address(address)) => json!(format!("{:?}", address)),
+        (_, Token::Bytes(bytes)) | (_, Token::FixedBytes(bytes)) => {
+            json!(format!("0x{}", hex::encode(bytes)))
+        }
+        (_, Token::Int(int)) => json!(I256::from_raw(int).to_string()),
+        (_, Token::Uint(uint)) => json!(uint.to_string()),
+        (SchemaType::Array(inner), Token::Array(tokens))
+        | (SchemaType::FixedArray(inner, _), Token::FixedArray(tokens)) => Value::Array(
+            tokens
+                .into_iter()
+                .map(|token| token_to_value(inner, token))
+                .collect(),
+        ),
+        (SchemaType::Tuple(fields), Token::Tuple(tokens))
+            if fields.iter().all(|field| !field.name.is_empty()) =>
+        {
+            Value::Object(
+                fields
+                    .iter()
+                    .zip(tokens)
+                    .map(|(field, token)| (field.name.clone(), token_to_value(&field.kind, token)))
+                    .collect(),
+            )
+        }
+        (SchemaType::Tuple(fields), Token::Tuple(tokens)) => Value::Array(
+            fields
+                .iter()
+                .zip(tokens)
+                .map(|(field, token)| token_to_value(&field.kind, token))
+                .collect(),
+        ),
+        (_, Token::Bool(value)) => json!(value),
+        (_, Token::String(value)) => json!(value),
+        (_, token) => json!(token.to_string()),
+    }
+}
+
+/// Decodes attestation data using an EAS schema string, returning schema
+/// items in the same shape as processor output.
+pub fn decode_schema_data(schema: &str, data: &[u8]) -> Result<Vec<SchemaItem>> {
+    let fields = schema::parse(schema)?;
+    let param_types: Vec<ParamType> = fields
+        .iter()
+        .map(|field| field.kind.to_param_type())
+        .collect();
+
+    let tokens =
+        decode(&param_types, data).map_err(|e| anyhow!("Failed to decode ABI data: {}", e))?;
+
+    Ok(fields
+        .into_iter()
+        .zip(tokens)
+        .map(|(field, token)| SchemaItem {
+            value: token_to_value(&field.kind, token),
+            type_field: field.kind.to_string(),
+            name: field.name,
+        })
+        .collect())
+}
+
+#[derive(Error, Debug)]
+pub enum GetSchemaUidError {
+    #[error("Address parse error: {0}")]
+    AddressParseError(String),
+
+    #[error("Failed to encode ABI data: {0}")]
+    EncodePackedError(#[from] ethers_core::abi::EncodePackedError),
+}
+
+pub fn get_schema_uid(
+    schema: &str,
+    resolver_address: &str,
+    revokable: bool,
+) -> Result<[u8; 32], GetSchemaUidError> {
+    let schema_token = Token::String(schema.to_string());
+    let resolver_address: Address = resolver_address
+        .parse::<Address>()
+        .map_err(|e| GetSchemaUidError::AddressParseError(e.to_string()))?;
+    let resolver_address_token = Token::Address(resolver_address);
+    let revocable_token = Token::Bool(revokable);
+    let encoded = encode_packed(&[schema_token, resolver_address_token, revocable_token])
+        .map_err(GetSchemaUidError::EncodePackedError)?;
+    Ok(keccak256(encoded))
+}
+
+fn get_body(recipe_query: &RecipeQuery, address: &EthAddress) -> Result<String> {
+    let mut query_body = json!({
+        "url": replace_dynamic_variables(&recipe_query.url, address),
+    });
+
+    if let Some(headers) = &recipe_query.headers {
+        if let Value::Object(ref mut map) = query_body {
+            let processed_headers = replace_dynamic_variables(headers, address);
+            map.insert("headers".to_string(), Value::String(processed_headers));
+        }
+    }
+
+    if let Some(filter) = &recipe_query.filter {
+        if let Value::Object(ref mut map) = query_body {
+            let processed_filter = replace_dynamic_variables(filter, address);
+            map.insert("filter".to_string(), Value::String(processed_filter));
+        }
+    }
+
+    if let Some(body) = &recipe_query.body {
+        if let Value::Object(ref mut map) = query_body {
+            let processed_variables = replace_dynamic_variables(&body.variables, address);
+            let variables_json: Value = serde_json::from_str(&processed_variables)
+                .map_err(|e| anyhow!("Failed to parse variables into JSON: {}", e))?;
+            map.insert(
+                "body".to_string(),
+                json!({
+                    "query": body.query,
+                    "variables": variables_json,
+                }),
+            );
+        }
+    }
+
+    Ok(query_body.to_string())
+}
+
+pub async fn run_query(address: &EthAddress, recipe_query: &RecipeQuery) -> Result<String> {
+    logger::debug("run_query");
+
+    if let Some(ref onchain_query) = recipe_query.onchain {
+        return onchain_query::run_onchain_query(address, onchain_query).await;
+    }
+
+    let body = get_body(recipe_query, address)?.into_bytes();
+
+    let mut hasher = Blake2bVar::new(12).unwrap();
+    hasher.update(&body);
+    let mut cache_key = [0u8; 12];
+    hasher.finalize_variable(&mut cache_key).unwrap();
+    let cache_key = hex::encode(cache_key);
+
+    let url = format!("{}/{}", QUERY_PROXY_URL, cache_key);
+
+    let request = CanisterHttpRequestArgument {
+        url,
+        method: HttpMethod::POST,
+        headers: vec![],
+        body: Some(body),
+        max_response_bytes: Some(4_096),
+        transform: Some(TransformContext::from_name("transform".to_string(), vec![])),
+    };
+
+    logger::debug(&format!("run_query https outcall: url: {:?}", request.url));
+
+    match http_request(request, ETH_DEFAULT_CALL_CYCLES).await {
+        Ok((response,)) => {
+            logger::debug(&format!(
+                "run_query https outcall ok, response: {:?}",
+                String::from_utf8(response.body.clone())
+            ));
+            Ok(String::from_utf8(response.body)
+                .expect("Transformed response is not UTF-8 encoded."))
+        }
+        Err((r, m)) => {
+            logger::debug(&format!(
+                "run_query https outcall error, code: {:?}, message {:?}",
+                r, m
+            ));
+            bail!("Failed to run query. Code: {:?}, Message: {}", r, m)
+        }
+    }
+}
+
+pub fn create_attest_request(
+    recipe: &Recipe,
+    output: &AttestationOutput,
+    recipient: &EthAddress,
+    run_ref_uid: Option<&str>,
+) -> Result<Token> {
+    logger::debug("create_attest_request");
+
+    let schema_uid = get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?;
+
+    let attestation_request_data =
+        create_attest_request_data(recipe, output, recipient, run_ref_uid)?;
+
+    let schema_token = Token::FixedBytes(schema_uid.to_vec());
+
+    Ok(Token::Tuple(vec![schema_token, attestation_request_data]))
+}
+
+/// Creates the AttestationRequestData of one attestation, without the schema.
+pub fn create_attest_request_data(
+    recipe: &Recipe,
+    output: &AttestationOutput,
+    recipient: &EthAddress,
+    run_ref_uid: Option<&str>,
+) -> Result<Token> {
+    let encoded_abi_data = encode_abi_data(&output.data, recipe.integer_strings())?;
+
+    let expiration_time = expiration_time(recipe, output)?;
+
+    let ref_uid = attestation_ref_uid(run_ref_uid, output)?;
+
+    Ok(Token::Tuple(vec![
+        Token::Address(H160(recipient.as_byte_array())), // recipient
+        Token::Uint(expiration_time.into()),             // expirationTime
+        Token::Bool(recipe.revokable),                   // revocable
+        Token::FixedBytes(ref_uid.to_vec()),             // refUID
+        Token::Bytes(encoded_abi_data),                  // data
+        Token::Uint((0).into()),                         // value
+    ]))
+}
+
+// Transactions for a run are sent with the gas settings the run was paid with,
+// on an additional chain with the settings quoted for that chain
+fn run_gas_settings(run: &Run, chain_id: u32) -> Result<(Nat, Nat, Nat)> {
+    let chain = run.additional_chain(chain_id);
+
+    let gas = chain
+        .and_then(|chain| chain.gas.clone())
+        .or(run.gas.clone())
+        .ok_or(anyhow!("Recipe don't have a gas amount specified"))?;
+
+    let base_fee_per_gas = chain
+        .and_then(|chain| chain.base_fee_per_gas.clone())
+        .or(run.base_fee_per_gas.clone())
+        .ok_or(anyhow!(
+            "Run don't have a base_fee_per_gas amount specified"
+        ))?;
+
+    let max_priority_fee_per_gas = chain
+        .and_then(|chain| chain.max_priority_fee_per_gas.clone())
+        .or(run.max_priority_fee_per_gas.clone())
+        .ok_or(anyhow!(
+            "Run don't have a max_priority_fee_per_gas amount specified"
+        ))?;
+
+    Ok((gas, base_fee_per_gas, max_priority_fee_per_gas))
+}
+
+/// Creates the attestation of a run on a chain, returns the transaction hash.
+/// The refUID of a refreshed run differs per chain and is passed in.
+pub async fn create_attestation(
+    recipe: &Recipe,
+    run: &Run,
+    output: &AttestationOutput,
+    recipient: &EthAddress,
+    run_ref_uid: Option<&str>,
+    chain_id: u32,
+) -> Result<String> {
+    logger::debug("create_attestation");
+
+    let attest_request = create_attest_request(recipe, output, recipient, run_ref_uid)?;
+
+    let (gas, base_fee_per_gas, max_priority_fee_per_gas) = run_gas_settings(run, chain_id)?;
+
+    let chain_config = chain_config::get(chain_id)?;
+
+    Ok(eth_transaction(
+        chain_config.eas_contract.clone(),
+        &Arc::clone(&ETH_EAS_CONTRACT),
+        "attest",
+        &[attest_request],
+        gas,
+        base_fee_per_gas,
+        Some(max_priority_fee_per_gas),
+        &chain_config,
+    )
+    .await?)
+}
+
+/// Creates the attestations of the request data in one multiAttest
+/// transaction, returns the transaction hash.
+pub async fn create_multi_attestation(
+    recipe: &Recipe,
+    attestation_request_data: Vec<Token>,
+    gas: Nat,
+    base_fee_per_gas: Nat,
+    max_priority_fee_per_gas: Nat,
+    chain_id: u32,
+) -> Result<String> {
+    logger::debug("create_multi_attestation");
+
+    let schema_uid = get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?;
+
+    let multi_attest_request = Token::Array(vec![Token::Tuple(vec![
+        Token::FixedBytes(schema_uid.to_vec()),
+        Token::Array(attestation_request_data),
+    ])]);
+
+    let chain_config = chain_config::get(chain_id)?;
+
+    Ok(eth_transaction(
+        chain_config.eas_contract.clone(),
+        &Arc::clone(&ETH_EAS_CONTRACT),
+        "multiAttest",
+        &[multi_attest_request],
+        gas,
+        base_fee_per_gas,
+        Some(max_priority_fee_per_gas),
+        &chain_config,
+    )
+    .await?)
+}
+
+pub fn create_revocation_request(recipe: &Recipe, attestation_uid: &str) -> Result<Token> {
+    logger::debug("create_revocation_request");
+
+    let schema_uid = get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?;
+
+    let uid = parse_uid(attestation_uid)?;
+
+    let schema_token = Token::FixedBytes(schema_uid.to_vec());
+    let revocation_request_data = Token::Tuple(vec![
+        Token::FixedBytes(uid.to_vec()), // uid
+        Token::Uint((0).into()),         // value
+    ]);
+
+    Ok(Token::Tuple(vec![schema_token, revocation_request_data]))
+}
+
+/// Revokes the attestation of the run, returns the transaction hash.
+pub async fn revoke_attestation(recipe: &Recipe, run: &Run) -> Result<String> {
+    logger::debug("revoke_attestation");
+
+    let attestation_uid = run
+        .attestation_uid
+        .as_ref()
+        .ok_or(anyhow!("Run has no attestation uid"))?;
+
+    let revocation_request = create_revocation_request(recipe, attestation_uid)?;
+
+    let (gas, base_fee_per_gas, max_priority_fee_per_gas) = run_gas_settings(run, run.chain_id)?;
+
+    let chain_config = chain_config::get(run.chain_id)?;
+
+    Ok(eth_transaction(
+        chain_config.eas_contract.clone(),
+        &Arc::clone(&ETH_EAS_CONTRACT),
+        "revoke",
+        &[revocation_request],
+        gas,
+        base_fee_per_gas,
+        Some(max_priority_fee_per_gas),
+        &chain_config,
+    )
+    .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UID: &str = "0xabcdef1111111111111111111111111111111111111111111111111111111111";
+
+    fn output_with_ref_uid() -> String {
+        json!({ "data": [], "refUID": UID }).to_string()
+    }
+
+    #[test]
+    fn ref_uid_matches_attestation_id() {
+        // Ids are compared as bytes, regardless of case
+        let id = format!("0x{}", UID[2..].to_uppercase());
+        let query_result = json!([{ "data": { "attestations": [{ "id": id }] } }]).to_string();
+        let output = parse_processor_output(&output_with_ref_uid(), &query_result).unwrap();
+        assert_eq!(output.ref_uid.as_deref(), Some(UID));
+    }
+
+    #[test]
+    fn ref_uid_matches_attestation_uid() {
+        let query_result = json!([{ "uid": UID }]).to_string();
+        assert!(parse_processor_output(&output_with_ref_uid(), &query_result).is_ok());
+    }
+
+    #[test]
+    fn ref_uid_in_other_field_is_rejected() {
+        let query_result = json!([{
+            "data": { "attestations": [{ "id": "0x01", "refUID": UID, "data": UID }] }
+        }])
+        .to_string();
+        assert!(parse_processor_output(&output_with_ref_uid(), &query_result).is_err());
+    }
+
+    #[test]
+    fn ref_uid_in_invalid_query_result_is_rejected() {
+        let query_result = format!("not json {}", UID);
+        assert!(parse_processor_output(&output_with_ref_uid(), &query_result).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    chain_config::ChainConfig,
+    eth_address::EthAddress,
+    evm::rpc::{get_self_eth_address, sign_hash},
+    logger::{self},
+    recipe::Recipe,
+    time::time,
+};
+use anyhow::Result;
+use ethers_core::{
+    abi::{encode, ethereum_types::H160, Token},
+    utils::keccak256,
+};
+use serde_json::{json, Value};
+
+use super::{
+    attestation_ref_uid,
+    eip712::{domain_json, signing_hash, to_hex},
+    encode_abi_data, expiration_time, get_schema_uid, AttestationOutput,
+};
+
+// Off-chain attestations are created in the version 1 format of the EAS SDK,
+// without a salt
+const OFFCHAIN_ATTESTATION_VERSION: u16 = 1;
+
+const EIP712_DOMAIN_NAME: &str = "EAS Attestation";
+
+const ATTEST_TYPE: &str = "Attest(uint16 version,bytes32 schema,address recipient,uint64 time,uint64 expirationTime,bool revocable,bytes32 refUID,bytes data)";
+
+/// The message of an off-chain attestation, signed as EIP-712 typed data.
+pub struct OffchainAttestationMessage {
+    pub schema: [u8; 32],
+    pub recipient: [u8; 20],
+    pub time: u64,
+    pub expiration_time: u64,
+    pub revocable: bool,
+    pub ref_uid: [u8; 32],
+    pub data: Vec<u8>,
+}
+
+impl OffchainAttestationMessage {
+    pub fn new(
+        recipe: &Recipe,
+        output: &AttestationOutput,
+        recipient: &EthAddress,
+        run_ref_uid: Option<&str>,
+    ) -> Result<Self> {
+        Ok(Self {
+            schema: get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?,
+            recipient: recipient.as_byte_array(),
+            time: time() as u64,
+            expiration_time: expiration_time(recipe, output)?,
+            revocable: recipe.revokable,
+            ref_uid: attestation_ref_uid(run_ref_uid, output)?,
+            data: encode_abi_data(&output.data, recipe.integer_strings())?,
+        })
+    }
+
+    fn struct_hash(&self) -> [u8; 32] {
+        keccak256(encode(&[
+            Token::FixedBytes(keccak256(ATTEST_TYPE).to_vec()),
+            Token::Uint(OFFCHAIN_ATTESTATION_VERSION.into()),
+            Token::FixedBytes(self.schema.to_vec()),
+            Token::Address(H160(self.recipient)),
+            Token::Uint(self.time.into()),
+            Token::Uint(self.expiration_time.into()),
+            Token::Bool(self.revocable),
+            Token::FixedBytes(self.ref_uid.to_vec()),
+            Token::FixedBytes(keccak256(&self.data).to_vec()),
+        ]))
+    }
+
+    /// The EIP-712 hash that is signed.
+    pub fn signing_hash(&self, chain_config: &ChainConfig) -> Result<[u8; 32]> {
+        signing_hash(EIP712_DOMAIN_NAME, chain_config, &self.struct_hash())
+    }
+
+    /// The uid of the attestation, computed like the EAS SDK does for version 1
+    /// off-chain attestations. The attester is not part of the uid.
+    pub fn uid(&self) -> [u8; 32] {
+        let mut packed = Vec::new();
+        packed.extend_from_slice(&OFFCHAIN_ATTESTATION_VERSION.to_be_bytes());
+        packed.extend_from_slice(&self.schema);
+        packed.extend_from_slice(&self.recipient);
+        packed.extend_from_slice(&[0u8; 20]); // attester
+        packed.extend_from_slice(&self.time.to_be_bytes());
+        packed.extend_from_slice(&self.expiration_time.to_be_bytes());
+        packed.push(self.revocable as u8);
+        packed.extend_from_slice(&self.ref_uid);
+        packed.extend_from_slice(&self.data);
+        packed.extend_from_slice(&0u32.to_be_bytes()); // bump
+        keccak256(packed)
+    }
+
+    /// The signed attestation in the JSON format of the EAS SDK, with the
+    /// address of the signer added.
+    pub fn to_signed_json(
+        &self,
+        chain_config: &ChainConfig,
+        signature: &ethers_core::types::Signature,
+        signer: &str,
+    ) -> Value {
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        signature.r.to_big_endian(&mut r);
+        signature.s.to_big_endian(&mut s);
+
+        json!({
+            "signer": signer,
+            "sig": {
+                "version": OFFCHAIN_ATTESTATION_VERSION,
+                "uid": to_hex(&self.uid()),
+                "domain": domain_json(EIP712_DOMAIN_NAME, chain_config),
+                "primaryType": "Attest",
+                "types": {
+                    "Attest": [
+                        { "name": "version", "type": "uint16" },
+                        { "name": "schema", "type": "bytes32" },
+                        { "name": "recipient", "type": "address" },
+                        { "name": "time", "type": "uint64" },
+                        { "name": "expirationTime", "type": "uint64" },
+                        { "name": "revocable", "type": "bool" },
+                        { "name": "refUID", "type": "bytes32" },
+                        { "name": "data", "type": "bytes" },
+                    ],
+                },
+                "message": {
+                    "version": OFFCHAIN_ATTESTATION_VERSION,
+                    "schema": to_hex(&self.schema),
+                    "recipient": to_hex(&self.recipient),
+                    "time": self.time.to_string(),
+                    "expirationTime": self.expiration_time.to_string(),
+                    "revocable": self.revocable,
+                    "refUID": to_hex(&self.ref_uid),
+                    "data": to_hex(&self.data),
+                },
+                "signature": {
+                    "v": signature.v,
+                    "r": to_hex(&r),
+                    "s": to_hex(&s),
+                },
+            },
+        })
+    }
+}
+
+/// Signs an off-chain attestation with the canister key. Returns the uid and
+/// the signed attestation as JSON.
+pub async fn create_offchain_attestation(
+    recipe: &Recipe,
+    output: &AttestationOutput,
+    recipient: &EthAddress,
+    run_ref_uid: Option<&str>,
+    chain_config: &ChainConfig,
+) -> Result<(String, String)> {
+    logger::debug("create_offchain_attestation");
+
+    let message = OffchainAttestationMessage::new(recipe, output, recipient, run_ref_uid)?;
+    let signature = sign_hash(&message.signing_hash(chain_config)?).await;
+    let signer = get_self_eth_address().await;
+
+    Ok((
+        to_hex(&message.uid()),
+        message
+            .to_signed_json(chain_config, &signature, &signer)
+            .to_string(),
+    ))
+}
@@ -0,0 +1,358 @@
+use crate::{
+    chain_config::{self, ChainConfig},
+    eth_address::EthAddress,
+    evm::rpc::{eth_block_number, eth_get_logs},
+    graphql::replace_dynamic_variables,
+    logger::{self},
+    recipe::RecipeOnchainQuery,
+    ETH_EAS_CONTRACT,
+};
+use anyhow::{anyhow, Result};
+use ethers_core::{
+    abi::{decode, ethereum_types::H160, ParamType, Token},
+    types::I256,
+    utils::to_checksum,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use super::{
+    attestation::{get_attestation, OnchainAttestation},
+    eip712::to_hex,
+    parse_uid,
+    registry::get_schema,
+    schema::{self, SchemaType},
+};
+
+const ONCHAIN_QUERY_DEFAULT_TAKE: u32 = 10;
+
+// Blocks searched by one eth_getLogs call, and the number of calls made going
+// back from the latest block. Logs older than that are not found.
+const ONCHAIN_QUERY_LOG_BLOCK_RANGE: u64 = 10_000;
+const ONCHAIN_QUERY_MAX_LOG_PAGES: u32 = 10;
+
+// Integers up to 48 bits are plain numbers in ethers decoded data, larger
+// integers are BigNumber objects
+const MAX_SAFE_INTEGER_BITS: usize = 48;
+
+fn big_number(hex: String, negative: bool) -> Value {
+    let hex = if hex.len() % 2 == 1 {
+        format!("0{}", hex)
+    } else {
+        hex
+    };
+    json!({
+        "type": "BigNumber",
+        "hex": format!("{}0x{}", if negative { "-" } else { "" }, hex),
+    })
+}
+
+/// Converts a decoded token to the value the EAS SDK puts in decodedDataJson.
+fn token_to_ethers_value(kind: &SchemaType, token: Token) -> Value {
+    match (kind, token) {
+        (_, Token::Address(address)) => json!(to_checksum(&address, None)),
+        (_, Token::Bytes(bytes)) | (_, Token::FixedBytes(bytes)) => json!(to_hex(&bytes)),
+        (SchemaType::Uint(bits), Token::Uint(uint)) if *bits <= MAX_SAFE_INTEGER_BITS => {
+            json!(uint.low_u64())
+        }
+        (_, Token::Uint(uint)) => big_number(format!("{:x}", uint), false),
+        (SchemaType::Int(bits), Token::Int(int)) if *bits <= MAX_SAFE_INTEGER_BITS => {
+            json!(I256::from_raw(int).low_i64())
+        }
+        (_, Token::Int(int)) => {
+            let int = I256::from_raw(int);
+            big_number(format!("{:x}", int.unsigned_abs()), int.is_negative())
+        }
+        (SchemaType::Array(inner), Token::Array(tokens))
+        | (SchemaType::FixedArray(inner, _), Token::FixedArray(tokens)) => Value::Array(
+            tokens
+                .into_iter()
+                .map(|token| token_to_ethers_value(inner, token))
+                .collect(),
+        ),
+        (SchemaType::Tuple(fields), Token::Tuple(tokens)) => Value::Array(
+            fields
+                .iter()
+                .zip(tokens)
+                .map(|(field, token)| token_to_ethers_value(&field.kind, token))
+                .collect(),
+        ),
+        (_, Token::Bool(value)) => json!(value),
+        (_, Token::String(value)) => json!(value),
+        (_, token) => json!(token.to_string()),
+    }
+}
+
+/// Decodes attestation data into the decodedDataJson of the EAS GraphQL API.
+fn decoded_data_json(schema: &str, data: &[u8]) -> Result<String> {
+    let fields = schema::parse(schema)?;
+    let param_types: Vec<ParamType> = fields
+        .iter()
+        .map(|field| field.kind.to_param_type())
+        .collect();
+
+    let tokens =
+        decode(&param_types, data).map_err(|e| anyhow!("Failed to decode ABI data: {}", e))?;
+
+    let items: Vec<Value> = fields
+        .into_iter()
+        .zip(tokens)
+        .map(|(field, token)| {
+            let type_name = field.kind.to_string();
+            let value = token_to_ethers_value(&field.kind, token);
+            json!({
+                "name": field.name,
+                "type": type_name,
+                "signature": format!("{} {}", type_name, field.name),
+                "value": {
+                    "name": field.name,
+                    "type": type_name,
+                    "value": value,
+                },
+            })
+        })
+        .collect();
+
+    Ok(Value::Array(items).to_string())
+}
+
+/// An attestation in the shape of the EAS GraphQL API.
+fn attestation_to_graphql_value(attestation: &OnchainAttestation, schema: &str) -> Result<Value> {
+    Ok(json!({
+        "id": to_hex(&attestation.uid),
+        "schemaId": to_hex(&attestation.schema),
+        "attester": to_checksum(&H160(attestation.attester), None),
+        "recipient": to_checksum(&H160(attestation.recipient), None),
+        "refUID": to_hex(&attestation.ref_uid),
+        "revocable": attestation.revocable,
+        "revoked": attestation.is_revoked(),
+        "time": attestation.time,
+        "timeCreated": attestation.time,
+        "expirationTime": attestation.expiration_time,
+        "revocationTime": attestation.revocation_time,
+        "data": to_hex(&attestation.data),
+        "decodedDataJson": decoded_data_json(schema, &attestation.data)?,
+        "isOffchain": false,
+    }))
+}
+
+/// The uids of the most recent attestations of the schema for the recipient,
+/// read from the Attested logs of the EAS contract.
+async fn get_attested_uids(
+    schema_uid: &[u8; 32],
+    recipient: &EthAddress,
+    from_block: Option<u64>,
+    take: usize,
+    chain_config: &ChainConfig,
+) -> Result<Vec<[u8; 32]>> {
+    let event_signature = ETH_EAS_CONTRACT.event("Attested")?.signature();
+
+    let mut recipient_topic = [0u8; 32];
+    recipient_topic[12..].copy_from_slice(&recipient.as_byte_array());
+
+    // Attested(address indexed recipient, address indexed attester, bytes32 uid,
+    // bytes32 indexed schemaUID)
+    let topics = vec![
+        vec![to_hex(event_signature.as_bytes())],
+        vec![to_hex(&recipient_topic)],
+        vec![],
+        vec![to_hex(schema_uid)],
+    ];
+
+    // Pages back from the latest block until enough logs are found, never
+    // below the first block of the query
+    let first_block = from_block.unwrap_or(0);
+    let mut to_block = eth_block_number(chain_config).await?;
+    let mut uids = vec![];
+    for _ in 0..ONCHAIN_QUERY_MAX_LOG_PAGES {
+        if to_block < first_block {
+            break;
+        }
+        let page_from_block = to_block
+            .saturating_sub(ONCHAIN_QUERY_LOG_BLOCK_RANGE - 1)
+            .max(first_block);
+
+        let logs = eth_get_logs(
+            chain_config.eas_contract.clone(),
+            topics.clone(),
+            page_from_block,
+            to_block,
+            chain_config,
+        )
+        .await?;
+        for log in logs.iter().rev().take(take - uids.len()) {
+            uids.push(parse_uid(&log.data)?);
+        }
+
+        if uids.len() >= take || page_from_block == 0 {
+            break;
+        }
+        to_block = page_from_block - 1;
+    }
+
+    Ok(uids)
+}
+
+/// Upper bound on the EVM RPC calls made by the query, each one is paid for by
+/// the run.
+pub fn max_rpc_calls(query: &RecipeOnchainQuery) -> u32 {
+    match (&query.uid, &query.schema_uid) {
+        // The attestation and its schema
+        (Some(_), _) => 2,
+        // The latest block, the log pages, and each attestation and schema
+        (None, Some(_)) => {
+            let take = query.take.unwrap_or(ONCHAIN_QUERY_DEFAULT_TAKE);
+            1 + ONCHAIN_QUERY_MAX_LOG_PAGES + take * 2
+        }
+        (None, None) => 0,
+    }
+}
+
+/// Runs an on-chain query for the address. The result has the shape of the
+/// GraphQL query results, `{"attestations":[...]}`, for processors to handle
+/// both kinds of queries alike.
+pub async fn run_onchain_query(address: &EthAddress, query: &RecipeOnchainQuery) -> Result<String> {
+    logger::debug("run_onchain_query");
+
+    let chain_config = chain_config::get(query.chain_id)?;
+
+    let uids = match (&query.uid, &query.schema_uid) {
+        (Some(uid), _) => vec![parse_uid(uid)?],
+        (None, Some(schema_uid)) => {
+            let recipient = match query.recipient {
+                Some(ref recipient) => {
+                    EthAddress::new(&replace_dynamic_variables(recipient, address))?
+                }
+                None => EthAddress::new(address.as_str())?,
+            };
+            let take = query.take.unwrap_or(ONCHAIN_QUERY_DEFAULT_TAKE) as usize;
+            get_attested_uids(
+                &parse_uid(schema_uid)?,
+                &recipient,
+                query.from_block,
+                take,
+                &chain_config,
+            )
+            .await?
+        }
+        (None, None) => vec![],
+    };
+
+    let mut schemas: HashMap<[u8; 32], String> = HashMap::new();
+    let mut attestations = Vec::new();
+
+    for uid in uids.iter() {
+        let attestation = match get_attestation(uid, &chain_config).await? {
+            Some(attestation) => attestation,
+            None => continue,
+        };
+
+        // Attestations of a log query all share the same schema
+        let schema = match schemas.get(&attestation.schema) {
+            Some(schema) => schema.clone(),
+            None => {
+                let schema = get_schema(&attestation.schema, &chain_config)
+                    .await?
+                    .unwrap_or_default();
+                schemas.insert(attestation.schema, schema.clone());
+                schema
+            }
+        };
+
+        attestations.push(attestation_to_graphql_value(&attestation, &schema)?);
+    }
+
+    Ok(json!({ "attestations": attestations }).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::{
+        abi::encode,
+        types::{Address, U256},
+    };
+
+    fn value_of(schema: &str, tokens: Vec<Token>) -> Vec<Value> {
+        let json = decoded_data_json(schema, &encode(&tokens)).unwrap();
+        let items: Vec<Value> = serde_json::from_str(&json).unwrap();
+        items
+            .into_iter()
+            .map(|item| item["value"]["value"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_small_integers_are_numbers() {
+        let values = value_of(
+            "uint32 scorer_id,int8 delta",
+            vec![
+                Token::Uint(U256::from(335)),
+                Token::Int(I256::from(-5i64).into_raw()),
+            ],
+        );
+        assert_eq!(values, vec![json!(335), json!(-5)]);
+    }
+
+    #[test]
+    fn test_large_integers_are_big_numbers() {
+        let values = value_of(
+            "uint256 score,int256 balance",
+            vec![
+                Token::Uint(U256::from(1)),
+                Token::Int(I256::from(-255i64).into_raw()),
+            ],
+        );
+        assert_eq!(
+            values,
+            vec![
+                json!({"type": "BigNumber", "hex": "0x01"}),
+                json!({"type": "BigNumber", "hex": "-0xff"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arrays_and_tuples() {
+        let address = Address::from_low_u64_be(0xab);
+        let values = value_of(
+            "uint8[] levels,(address owner,bool active,uint256 amount) entry",
+            vec![
+                Token::Array(vec![Token::Uint(U256::from(1)), Token::Uint(U256::from(2))]),
+                Token::Tuple(vec![
+                    Token::Address(address),
+                    Token::Bool(true),
+                    Token::Uint(U256::from(256)),
+                ]),
+            ],
+        );
+        assert_eq!(
+            values,
+            vec![
+                json!([1, 2]),
+                json!([
+                    to_checksum(&address, None),
+                    true,
+                    {"type": "BigNumber", "hex": "0x0100"}
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decoded_data_json_fields() {
+        let json =
+            decoded_data_json("bytes32 id", &encode(&[Token::FixedBytes(vec![1; 32])])).unwrap();
+        let items: Vec<Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(items[0]["name"], "id");
+        assert_eq!(items[0]["type"], "bytes32");
+        assert_eq!(items[0]["signature"], "bytes32 id");
+        assert_eq!(items[0]["value"]["value"], json!(to_hex(&[1; 32])));
+    }
+
+    #[test]
+    fn test_decoded_data_json_invalid_data() {
+        assert!(decoded_data_json("uint256 score", &[1, 2, 3]).is_err());
+        assert!(decoded_data_json("not a schema", &[]).is_err());
+    }
+}
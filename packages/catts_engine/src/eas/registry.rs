@@ -0,0 +1,82 @@
+use crate::{
+    chain_config::ChainConfig,
+    eth_address::EthAddress,
+    evm::rpc::{eth_call, eth_transaction},
+    logger::{self},
+    recipe::Recipe,
+    ETH_SCHEMA_REGISTRY_CONTRACT,
+};
+use anyhow::{bail, Result};
+use candid::Nat;
+use ethers_core::abi::{ethereum_types::H160, Token};
+use std::sync::Arc;
+
+use super::get_schema_uid;
+
+/// Reads the schema string of a schema uid from the SchemaRegistry of the
+/// chain. Unregistered schemas are returned with a zero uid, None then.
+pub async fn get_schema(
+    schema_uid: &[u8; 32],
+    chain_config: &ChainConfig,
+) -> Result<Option<String>> {
+    logger::debug("get_schema");
+
+    let output = eth_call(
+        chain_config.schema_registry_contract.clone(),
+        &Arc::clone(&ETH_SCHEMA_REGISTRY_CONTRACT),
+        "getSchema",
+        &[Token::FixedBytes(schema_uid.to_vec())],
+        chain_config,
+    )
+    .await?;
+
+    match output.first() {
+        Some(Token::Tuple(record)) => match (record.first(), record.get(3)) {
+            (Some(Token::FixedBytes(uid)), Some(Token::String(schema))) => {
+                Ok((uid.as_slice() == schema_uid).then(|| schema.clone()))
+            }
+            _ => bail!("Unexpected getSchema output"),
+        },
+        _ => bail!("Unexpected getSchema output"),
+    }
+}
+
+/// Checks that the schema of the recipe is registered in the SchemaRegistry
+/// of the chain.
+pub async fn is_schema_registered(recipe: &Recipe, chain_config: &ChainConfig) -> Result<bool> {
+    logger::debug("is_schema_registered");
+
+    let schema_uid = get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?;
+
+    Ok(get_schema(&schema_uid, chain_config).await?.is_some())
+}
+
+/// Registers the schema of the recipe with the canister as registerer,
+/// returns the transaction hash.
+pub async fn register_schema(
+    recipe: &Recipe,
+    gas: Nat,
+    base_fee_per_gas: Nat,
+    max_priority_fee_per_gas: Nat,
+    chain_config: &ChainConfig,
+) -> Result<String> {
+    logger::debug("register_schema");
+
+    let resolver = EthAddress::new(&recipe.resolver)?;
+
+    Ok(eth_transaction(
+        chain_config.schema_registry_contract.clone(),
+        &Arc::clone(&ETH_SCHEMA_REGISTRY_CONTRACT),
+        "register",
+        &[
+            Token::String(recipe.schema.clone()),
+            Token::Address(H160(resolver.as_byte_array())),
+            Token::Bool(recipe.revokable),
+        ],
+        gas,
+        base_fee_per_gas,
+        Some(max_priority_fee_per_gas),
+        chain_config,
+    )
+    .await?)
+}
@@ -0,0 +1,13 @@
+use ethers_core::utils::hex;
+use ic_cdk::query;
+
+use crate::{eas::decode_schema_data, http_error::HttpError};
+
+/// Decodes ABI encoded attestation data into schema items, the JSON shape
+/// processors return.
+#[query]
+fn attestation_decode(schema: String, data_hex: String) -> Result<String, HttpError> {
+    let data = hex::decode(data_hex.trim()).map_err(HttpError::bad_request)?;
+    let items = decode_schema_data(&schema, &data).map_err(HttpError::bad_request)?;
+    serde_json::to_string(&items).map_err(HttpError::internal_server_error)
+}
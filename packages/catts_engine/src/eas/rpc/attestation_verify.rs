@@ -0,0 +1,65 @@
+use ic_cdk::update;
+
+use crate::{
+    chain_config::{self},
+    eas::{
+        attestation::{get_attestation, verify_attestation, AttestationVerification},
+        index, parse_uid,
+    },
+    eth_address::EthAddress,
+    evm::rpc::get_self_eth_address,
+    http_error::HttpError,
+    logger::{self},
+    rate_limit::{self, RateLimitedCall},
+    recipe::{self},
+    time::time,
+    user::auth_guard,
+};
+
+/// Reads an attestation created by a run or batch back from the EAS contract
+/// of the chain and checks that it is still a valid C-ATTS attestation.
+#[update]
+async fn attestation_verify(
+    chain_id: u32,
+    uid: String,
+) -> Result<AttestationVerification, HttpError> {
+    let address = auth_guard()?;
+
+    logger::debug("attestation_verify");
+
+    let chain_config = chain_config::get(chain_id)
+        .map_err(|_| HttpError::bad_request(format!("Chain {} is not supported", chain_id)))?;
+
+    let uid = parse_uid(uid.trim()).map_err(HttpError::bad_request)?;
+
+    // Only attestations recorded by the engine are verified, anything else is
+    // rejected before reading the chain
+    let indexed = index::get(chain_id, &uid).ok_or(HttpError::not_found(
+        "Attestation was not created by C-ATTS",
+    ))?;
+    let recipe = recipe::get_by_id(&indexed.recipe_id).map_err(HttpError::not_found)?;
+    let recipient =
+        EthAddress::new(&indexed.recipient).map_err(HttpError::internal_server_error)?;
+
+    rate_limit::check(&address, RateLimitedCall::AttestationVerify)?;
+
+    let attestation = get_attestation(&uid, &chain_config)
+        .await
+        .map_err(|err| {
+            HttpError::internal_server_error(format!("Error reading attestation: {}", err))
+        })?
+        .ok_or(HttpError::not_found("Attestation not found"))?;
+
+    let canister_attester =
+        EthAddress::new(&get_self_eth_address().await).map_err(HttpError::internal_server_error)?;
+
+    verify_attestation(
+        &attestation,
+        &recipe,
+        &canister_attester,
+        &recipient,
+        chain_id,
+        time() as u64,
+    )
+    .map_err(HttpError::internal_server_error)
+}
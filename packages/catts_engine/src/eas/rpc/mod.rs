@@ -0,0 +1,2 @@
+pub mod attestation_decode;
+pub mod attestation_verify;
@@ -0,0 +1,340 @@
+use core::fmt;
+
+use ethers_core::abi::ParamType;
+use thiserror::Error;
+use validator::ValidationError;
+
+use super::SchemaItem;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SchemaError {
+    #[error("Schema is empty")]
+    Empty,
+    #[error("Field {position} \"{field}\" must be a type followed by a name")]
+    InvalidField { position: usize, field: String },
+    #[error("Field {position} has an invalid name \"{name}\"")]
+    InvalidName { position: usize, name: String },
+    #[error("Field {position} has an unsupported type \"{type_name}\"")]
+    UnsupportedType { position: usize, type_name: String },
+    #[error("Field name \"{0}\" is used more than once")]
+    DuplicateName(String),
+    #[error("Output must be a JSON array of schema items: {0}")]
+    InvalidOutput(String),
+    #[error("Schema has {expected} fields, output has {actual}")]
+    FieldCount { expected: usize, actual: usize },
+    #[error("Output field {position}: expected name \"{expected}\", got \"{actual}\"")]
+    FieldName {
+        position: usize,
+        expected: String,
+        actual: String,
+    },
+    #[error("Output field \"{name}\": expected type {expected}, got \"{actual}\"")]
+    FieldType {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// The type of a schema field. Tuple components keep their names, `Display`
+/// formats the canonical ABI type without them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaType {
+    Address,
+    Bool,
+    String,
+    Bytes,
+    FixedBytes(usize),
+    Uint(usize),
+    Int(usize),
+    Array(Box<SchemaType>),
+    FixedArray(Box<SchemaType>, usize),
+    Tuple(Vec<SchemaField>),
+}
+
+impl fmt::Display for SchemaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaType::Address => write!(f, "address"),
+            SchemaType::Bool => write!(f, "bool"),
+            SchemaType::String => write!(f, "string"),
+            SchemaType::Bytes => write!(f, "bytes"),
+            SchemaType::FixedBytes(size) => write!(f, "bytes{}", size),
+            SchemaType::Uint(bits) => write!(f, "uint{}", bits),
+            SchemaType::Int(bits) => write!(f, "int{}", bits),
+            SchemaType::Array(inner) => write!(f, "{}[]", inner),
+            SchemaType::FixedArray(inner, size) => write!(f, "{}[{}]", inner, size),
+            SchemaType::Tuple(fields) => write!(
+                f,
+                "({})",
+                fields
+                    .iter()
+                    .map(|field| field.kind.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+        }
+    }
+}
+
+impl SchemaType {
+    pub fn to_param_type(&self) -> ParamType {
+        match self {
+            SchemaType::Address => ParamType::Address,
+            SchemaType::Bool => ParamType::Bool,
+            SchemaType::String => ParamType::String,
+            SchemaType::Bytes => ParamType::Bytes,
+            SchemaType::FixedBytes(size) => ParamType::FixedBytes(*size),
+            SchemaType::Uint(bits) => ParamType::Uint(*bits),
+            SchemaType::Int(bits) => ParamType::Int(*bits),
+            SchemaType::Array(inner) => ParamType::Array(Box::new(inner.to_param_type())),
+            SchemaType::FixedArray(inner, size) => {
+                ParamType::FixedArray(Box::new(inner.to_param_type()), *size)
+            }
+            SchemaType::Tuple(fields) => ParamType::Tuple(
+                fields
+                    .iter()
+                    .map(|field| field.kind.to_param_type())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaField {
+    pub name: String,
+    pub kind: SchemaType,
+}
+
+// Splits on commas outside of parentheses, None if the parentheses don't
+// balance.
+fn split_top_level(s: &str) -> Option<Vec<&str>> {
+    let mut parts = vec![];
+    let mut depth: usize = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    parts.push(&s[start..]);
+    Some(parts)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn parse_bits(bits: &str, default: usize) -> Option<usize> {
+    if bits.is_empty() {
+        return Some(default);
+    }
+    if bits.starts_with('0') {
+        return None;
+    }
+    bits.parse().ok()
+}
+
+fn parse_elementary_type(type_name: &str) -> Option<SchemaType> {
+    match type_name {
+        "address" => return Some(SchemaType::Address),
+        "bool" => return Some(SchemaType::Bool),
+        "string" => return Some(SchemaType::String),
+        "bytes" => return Some(SchemaType::Bytes),
+        // Alias used by the EAS schema builder
+        "ipfsHash" => return Some(SchemaType::FixedBytes(32)),
+        _ => {}
+    }
+    if let Some(size) = type_name.strip_prefix("bytes") {
+        let size = parse_bits(size, 0)?;
+        return (1..=32)
+            .contains(&size)
+            .then_some(SchemaType::FixedBytes(size));
+    }
+    let (bits, signed) = match type_name.strip_prefix("uint") {
+        Some(bits) => (bits, false),
+        None => (type_name.strip_prefix("int")?, true),
+    };
+    let bits = parse_bits(bits, 256)?;
+    if bits == 0 || bits > 256 || bits % 8 != 0 {
+        return None;
+    }
+    Some(if signed {
+        SchemaType::Int(bits)
+    } else {
+        SchemaType::Uint(bits)
+    })
+}
+
+fn parse_type_at(type_name: &str, position: usize) -> Result<SchemaType, SchemaError> {
+    let unsupported = || SchemaError::UnsupportedType {
+        position,
+        type_name: type_name.to_string(),
+    };
+
+    if let Some(rest) = type_name.strip_suffix(']') {
+        let open = rest.rfind('[').ok_or_else(unsupported)?;
+        let inner = parse_type_at(&rest[..open], position)?;
+        let size = &rest[open + 1..];
+        if size.is_empty() {
+            return Ok(SchemaType::Array(Box::new(inner)));
+        }
+        return match parse_bits(size, 0) {
+            Some(size) if size > 0 => Ok(SchemaType::FixedArray(Box::new(inner), size)),
+            _ => Err(unsupported()),
+        };
+    }
+
+    if let Some(inner) = type_name
+        .strip_prefix('(')
+        .and_then(|inner| inner.strip_suffix(')'))
+    {
+        let components = split_top_level(inner).ok_or_else(unsupported)?;
+        if inner.trim().is_empty() {
+            return Err(unsupported());
+        }
+        let fields = components
+            .into_iter()
+            .map(|component| parse_field(component, position, false))
+            .collect::<Result<Vec<SchemaField>, SchemaError>>()?;
+        return Ok(SchemaType::Tuple(fields));
+    }
+
+    parse_elementary_type(type_name).ok_or_else(unsupported)
+}
+
+// Tuple components may leave out their name
+fn parse_field(
+    field: &str,
+    position: usize,
+    name_required: bool,
+) -> Result<SchemaField, SchemaError> {
+    let field = field.trim();
+    let invalid_field = || SchemaError::InvalidField {
+        position,
+        field: field.to_string(),
+    };
+
+    // A tuple type contains spaces, the type ends after its closing
+    // parenthesis and array suffixes.
+    let type_end = if field.starts_with('(') {
+        let mut depth = 0;
+        let close = field
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })
+            .map(|(i, _)| i)
+            .ok_or_else(invalid_field)?;
+        field[close..]
+            .find(char::is_whitespace)
+            .map_or(field.len(), |i| close + i)
+    } else {
+        field.find(char::is_whitespace).unwrap_or(field.len())
+    };
+
+    let (type_name, name) = field.split_at(type_end);
+    let name = name.trim();
+    if type_name.is_empty() || (name_required && name.is_empty()) {
+        return Err(invalid_field());
+    }
+    if !name.is_empty() && !is_identifier(name) {
+        return Err(SchemaError::InvalidName {
+            position,
+            name: name.to_string(),
+        });
+    }
+
+    Ok(SchemaField {
+        name: name.to_string(),
+        kind: parse_type_at(type_name, position)?,
+    })
+}
+
+/// Parses an EAS schema string, e.g. `"uint256 score,bool verified"`.
+/// Positions in errors count fields from 1.
+pub fn parse(schema: &str) -> Result<Vec<SchemaField>, SchemaError> {
+    if schema.trim().is_empty() {
+        return Err(SchemaError::Empty);
+    }
+    let parts = split_top_level(schema).ok_or_else(|| SchemaError::InvalidField {
+        position: 1,
+        field: schema.to_string(),
+    })?;
+
+    let mut fields: Vec<SchemaField> = vec![];
+    for (i, part) in parts.into_iter().enumerate() {
+        let field = parse_field(part, i + 1, true)?;
+        if fields.iter().any(|f| f.name == field.name) {
+            return Err(SchemaError::DuplicateName(field.name));
+        }
+        fields.push(field);
+    }
+    Ok(fields)
+}
+
+/// Parses a single type, e.g. the type of a schema item in processor output.
+pub fn parse_type(type_name: &str) -> Result<SchemaType, SchemaError> {
+    parse_type_at(type_name.trim(), 1)
+}
+
+/// Checks that the processor output lists the fields of the schema, in
+/// order, with matching names and types.
+pub fn validate_output(schema: &str, output: &str) -> Result<(), SchemaError> {
+    let fields = parse(schema)?;
+    let items: Vec<SchemaItem> =
+        serde_json::from_str(output).map_err(|e| SchemaError::InvalidOutput(e.to_string()))?;
+
+    if items.len() != fields.len() {
+        return Err(SchemaError::FieldCount {
+            expected: fields.len(),
+            actual: items.len(),
+        });
+    }
+
+    for (i, (field, item)) in fields.iter().zip(items.iter()).enumerate() {
+        if item.name != field.name {
+            return Err(SchemaError::FieldName {
+                position: i + 1,
+                expected: field.name.clone(),
+                actual: item.name.clone(),
+            });
+        }
+        let type_matches = parse_type(&item.type_field)
+            .is_ok_and(|kind| kind.to_string() == field.kind.to_string());
+        if !type_matches {
+            return Err(SchemaError::FieldType {
+                name: field.name.clone(),
+                expected: field.kind.to_string(),
+                actual: item.type_field.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_schema(schema: &str) -> Result<(), ValidationError> {
+    parse(schema)
+        .map(|_| ())
+        .map_err(|e| ValidationError::new("schema").with_message(e.to_string().into()))
+}
@@ -1,11 +1,12 @@
 use crate::{
     chain_config::ChainConfig,
     declarations::evm_rpc::{
-        evm_rpc, BlockTag, GetLogsArgs, GetLogsResult, GetTransactionCountArgs,
-        GetTransactionCountResult, GetTransactionReceiptResult, LogEntry, MultiGetLogsResult,
+        evm_rpc, BlockTag, CallArgs, GetBlockByNumberResult, GetLogsArgs, GetLogsResult,
+        GetTransactionCountArgs, GetTransactionCountResult, GetTransactionReceiptResult, LogEntry,
+        MultiCallResult, MultiGetBlockByNumberResult, MultiGetLogsResult,
         MultiGetTransactionCountResult, MultiGetTransactionReceiptResult,
         MultiSendRawTransactionResult, RpcConfig, RpcError, SendRawTransactionResult,
-        SendRawTransactionStatus, TransactionReceipt,
+        SendRawTransactionStatus, Topic, TransactionReceipt, TransactionRequest,
     },
     evm::util::{ecdsa_key_id, nat_to_u256, nat_to_u64},
     logger, ETH_DEFAULT_CALL_CYCLES,
@@ -74,6 +75,9 @@ pub enum EthTransactionError {
 
     #[error("Inconsistent response")]
     InconsistentResponse,
+
+    #[error("Unable to decode output")]
+    OutputDecoding,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -134,6 +138,75 @@ pub async fn eth_transaction(
         other => Err(EthTransactionError::MultiSendRawTransaction(other)),
     }
 }
+
+/// Calls a view function of a contract at the latest block and returns the
+/// decoded output.
+pub async fn eth_call(
+    contract_address: String,
+    abi_contract: &Contract,
+    function_name: &str,
+    args: &[Token],
+    chain_config: &ChainConfig,
+) -> Result<Vec<Token>, EthTransactionError> {
+    let abi_function = get_abi_function_by_name(abi_contract, function_name);
+    let data = abi_function
+        .encode_input(args)
+        .map_err(|_| EthTransactionError::ArgsEncoding)?;
+
+    logger::debug(&format!(
+        "eth_call: contract_address: {}, function_name: {}, args: {:?}",
+        contract_address, function_name, args
+    ));
+
+    let call_args = CallArgs {
+        transaction: TransactionRequest {
+            to: Some(contract_address),
+            gas: None,
+            maxFeePerGas: None,
+            gasPrice: None,
+            value: None,
+            maxFeePerBlobGas: None,
+            from: None,
+            r#type: None,
+            accessList: None,
+            nonce: None,
+            maxPriorityFeePerGas: None,
+            blobs: None,
+            input: Some(format!("0x{}", hex::encode(data))),
+            chainId: None,
+            blobVersionedHashes: None,
+        },
+        block: Some(BlockTag::Latest),
+    };
+
+    let (res,): (MultiCallResult,) = call_with_payment128(
+        evm_rpc.0,
+        "eth_call",
+        (
+            chain_config.rpc_services.clone(),
+            None::<RpcConfig>,
+            call_args,
+        ),
+        ETH_DEFAULT_CALL_CYCLES,
+    )
+    .await
+    .map_err(EthTransactionError::CallError)?;
+
+    match res {
+        MultiCallResult::Consistent(crate::declarations::evm_rpc::CallResult::Ok(output)) => {
+            let output = hex::decode(output.trim_start_matches("0x"))
+                .map_err(|_| EthTransactionError::OutputDecoding)?;
+            abi_function
+                .decode_output(&output)
+                .map_err(|_| EthTransactionError::OutputDecoding)
+        }
+        MultiCallResult::Consistent(crate::declarations::evm_rpc::CallResult::Err(err)) => {
+            Err(EthTransactionError::RpcError(err))
+        }
+        MultiCallResult::Inconsistent(_) => Err(EthTransactionError::InconsistentResponse),
+    }
+}
+
 pub async fn eth_get_transaction_receipt(
     hash: &str,
     chain_config: &ChainConfig,
@@ -196,6 +269,75 @@ pub async fn get_run_payment_logs(
     }
 }
 
+/// The number of the latest block of the chain.
+pub async fn eth_block_number(chain_config: &ChainConfig) -> Result<u64, EthTransactionError> {
+    logger::debug("eth_block_number");
+
+    let (res,): (MultiGetBlockByNumberResult,) = call_with_payment128(
+        evm_rpc.0,
+        "eth_getBlockByNumber",
+        (
+            chain_config.rpc_services.clone(),
+            None::<RpcConfig>,
+            BlockTag::Latest,
+        ),
+        ETH_DEFAULT_CALL_CYCLES,
+    )
+    .await
+    .map_err(EthTransactionError::CallError)?;
+
+    match res {
+        MultiGetBlockByNumberResult::Consistent(block_result) => match block_result {
+            GetBlockByNumberResult::Ok(block) => Ok(nat_to_u64(&block.number).as_u64()),
+            GetBlockByNumberResult::Err(err) => Err(EthTransactionError::RpcError(err)),
+        },
+        MultiGetBlockByNumberResult::Inconsistent(_) => {
+            Err(EthTransactionError::InconsistentResponse)
+        }
+    }
+}
+
+/// The logs of a contract matching the topics, between two blocks inclusive.
+/// Oldest logs first.
+pub async fn eth_get_logs(
+    contract_address: String,
+    topics: Vec<Topic>,
+    from_block: u64,
+    to_block: u64,
+    chain_config: &ChainConfig,
+) -> Result<Vec<LogEntry>, EthTransactionError> {
+    logger::debug(&format!(
+        "eth_get_logs: contract: {}, blocks: {}-{}",
+        contract_address, from_block, to_block
+    ));
+
+    let (res,): (MultiGetLogsResult,) = call_with_payment128(
+        evm_rpc.0,
+        "eth_getLogs",
+        (
+            chain_config.rpc_services.clone(),
+            None::<RpcConfig>,
+            GetLogsArgs {
+                addresses: vec![contract_address],
+                fromBlock: Some(BlockTag::Number(from_block.into())),
+                toBlock: Some(BlockTag::Number(to_block.into())),
+                topics: Some(topics),
+            },
+        ),
+        ETH_DEFAULT_CALL_CYCLES,
+    )
+    .await
+    .map_err(EthTransactionError::CallError)?;
+
+    match res {
+        MultiGetLogsResult::Consistent(log_result) => match log_result {
+            GetLogsResult::Ok(entries) => Ok(entries),
+            GetLogsResult::Err(err) => Err(EthTransactionError::RpcError(err)),
+        },
+        MultiGetLogsResult::Inconsistent(_) => Err(EthTransactionError::InconsistentResponse),
+    }
+}
+
 /// Computes a signature for an [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) transaction.
 async fn sign_transaction(req: SignRequest) -> String {
     use ethers_core::types::transaction::eip1559::Eip1559TransactionRequest;
@@ -241,6 +383,18 @@ async fn sign_transaction(req: SignRequest) -> String {
     format!("0x{}", hex::encode(&signed_tx_bytes))
 }
 
+/// Signs a 32 byte hash with the canister key. The recovery id `v` is 27 or 28,
+/// as expected by `ecrecover`.
+pub async fn sign_hash(hash: &[u8; 32]) -> ethers_core::types::Signature {
+    let (pubkey, signature) = pubkey_and_signature(hash.to_vec()).await;
+
+    ethers_core::types::Signature {
+        v: 27 + y_parity(hash, &signature, &pubkey),
+        r: U256::from_big_endian(&signature[0..32]),
+        s: U256::from_big_endian(&signature[32..64]),
+    }
+}
+
 /// Computes the parity bit allowing to recover the public key from the signature.
 fn y_parity(prehash: &[u8], sig: &[u8], pubkey: &[u8]) -> u64 {
     use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
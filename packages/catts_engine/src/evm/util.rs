@@ -12,6 +12,15 @@ pub fn nat_to_u256(n: &Nat) -> U256 {
     U256::from_big_endian(&be_bytes)
 }
 
+/// Converts without truncating, Nat has no conversion from 256 bit integers so
+/// it is built from the two 128 bit halves.
+pub fn u256_to_nat(n: &U256) -> Nat {
+    let half = Nat::from(u64::MAX) + Nat::from(1u8);
+    let high = Nat::from((*n >> 128).low_u128());
+    let low = Nat::from(n.low_u128());
+    high * half.clone() * half + low
+}
+
 pub fn nat_to_u64(n: &Nat) -> U64 {
     let be_bytes = n.0.to_bytes_be();
     U64::from_big_endian(&be_bytes)
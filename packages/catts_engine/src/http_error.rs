@@ -11,6 +11,7 @@ pub enum HttpStatusCode {
     NotFound = 404,
     MethodNotAllowed = 405,
     Conflict = 409,
+    TooManyRequests = 429,
     InternalServerError = 500,
     NotImplemented = 501,
     BadGateway = 502,
@@ -81,6 +82,14 @@ impl HttpError {
         )
     }
 
+    pub fn too_many_requests<M: Display>(message: M) -> Self {
+        Self::new(
+            HttpStatusCode::TooManyRequests as u16,
+            "Too many requests".to_string(),
+            Some(message.to_string()),
+        )
+    }
+
     pub fn internal_server_error<M: Display>(message: M) -> Self {
         Self::new(
             HttpStatusCode::InternalServerError as u16,
@@ -1,5 +1,7 @@
 use crate::{
-    certified_data::{render_recipe_assets, render_run_assets, render_user_assets},
+    certified_data::{
+        render_recipe_assets, render_run_assets, render_run_attestation_asset, render_user_assets,
+    },
     http_request::http::{default_headers, http_error, HttpRequest, HttpResponse},
 };
 
@@ -10,6 +12,7 @@ async fn http_request_update(req: HttpRequest) -> HttpResponse {
         ["user", user_id] => render_user_assets(user_id.to_string()),
         ["recipe", recipe_name] => render_recipe_assets(recipe_name.to_string()),
         ["run", run_id] => render_run_assets(run_id.to_string()),
+        ["run", run_id, "attestation.json"] => render_run_attestation_asset(run_id.to_string()),
         _ => {
             return http_error(404, "Not found.");
         }
@@ -1,3 +1,4 @@
+mod batch;
 mod certified_data;
 mod chain_config;
 mod change_log;
@@ -16,6 +17,8 @@ mod http_error;
 mod http_request;
 mod json;
 mod logger;
+mod processor;
+mod rate_limit;
 mod recipe;
 mod run;
 mod siwe;
@@ -24,10 +27,12 @@ mod time;
 mod user;
 
 use asset_util::CertifiedAssets;
+use batch::{Batch, BatchId};
 use candid::{CandidType, Nat};
 use certified_data::init_assets;
 use chain_config::{init_chain_configs, ChainConfig};
 use change_log::{ChangeLogItem, ChangeLogResponse};
+use eas::attestation::AttestationVerification;
 use eth_address::EthAddressBytes;
 use ethers_core::abi::Contract;
 use http_error::HttpError;
@@ -40,12 +45,16 @@ use ic_cdk::{
 use ic_stable_structures::{
     memory_manager::{MemoryId, MemoryManager, VirtualMemory},
     storable::Blob,
-    DefaultMemoryImpl, Log, StableBTreeMap,
+    Cell, DefaultMemoryImpl, Log, StableBTreeMap,
 };
 use lazy_static::lazy_static;
 use logger::LogItem;
-use recipe::{Recipe, RecipeDetailsInput, RecipeId};
-use run::{Run, RunId};
+use processor::{ProcessorFailure, ProcessorLimits, ProcessorOutput};
+use recipe::{
+    Recipe, RecipeDetailsInput, RecipeId, RecipeListOptions, RecipeListResponse,
+    RecipeSimulateInput, RecipeSimulateResult, RecipeStats, RecipeTestResult, RecipeTrendingItem,
+};
+use run::{Run, RunCreateOptions, RunId, RunListOptions, RunListResponse};
 use serde::{Deserialize, Serialize};
 use serde_bytes::ByteBuf;
 use std::{cell::RefCell, collections::HashMap, sync::Arc, time::Duration};
@@ -72,6 +81,27 @@ const RUNS_MEMORY_ID: MemoryId = MemoryId::new(5);
 const TASKS_MEMORY_ID: MemoryId = MemoryId::new(6);
 const CHANGE_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(8);
 const CHANGE_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(9);
+const RECIPE_CREATED_INDEX_MEMORY_ID: MemoryId = MemoryId::new(10);
+const RECIPE_CREATOR_INDEX_MEMORY_ID: MemoryId = MemoryId::new(11);
+const RECIPE_KEYWORD_INDEX_MEMORY_ID: MemoryId = MemoryId::new(12);
+const RECIPE_STATS_MEMORY_ID: MemoryId = MemoryId::new(13);
+const RECIPE_RUN_COUNT_INDEX_MEMORY_ID: MemoryId = MemoryId::new(14);
+const RECIPE_SEARCH_INDEX_MEMORY_ID: MemoryId = MemoryId::new(15);
+const RECIPE_SEARCH_TERMS_MEMORY_ID: MemoryId = MemoryId::new(16);
+const RECIPE_USERS_MEMORY_ID: MemoryId = MemoryId::new(17);
+const RECIPE_DAILY_RUNS_MEMORY_ID: MemoryId = MemoryId::new(18);
+const RUN_CREATED_INDEX_MEMORY_ID: MemoryId = MemoryId::new(19);
+const RUN_CREATOR_INDEX_MEMORY_ID: MemoryId = MemoryId::new(20);
+const RUN_RECIPE_INDEX_MEMORY_ID: MemoryId = MemoryId::new(21);
+const RUN_STATUS_INDEX_MEMORY_ID: MemoryId = MemoryId::new(22);
+const BATCHES_MEMORY_ID: MemoryId = MemoryId::new(23);
+const INDEXES_VERSION_MEMORY_ID: MemoryId = MemoryId::new(24);
+const ATTESTATION_INDEX_MEMORY_ID: MemoryId = MemoryId::new(25);
+
+// Version of the secondary indexes and recipe stats derived from the stored
+// recipes and runs. Bump it when their layout changes, upgrades only rebuild
+// them when the stored version differs.
+const INDEXES_VERSION: u32 = 2;
 
 #[derive(Serialize, Deserialize, CandidType)]
 struct CanisterSettingsInput {
@@ -89,6 +119,8 @@ pub struct CanisterSettings {
 
 lazy_static! {
     static ref ETH_EAS_CONTRACT: Arc<Contract> = Arc::new(include_abi!("../../eas/eas.abi.json"));
+    static ref ETH_SCHEMA_REGISTRY_CONTRACT: Arc<Contract> =
+        Arc::new(include_abi!("../../eas/schema_registry.abi.json"));
 }
 
 thread_local! {
@@ -125,6 +157,60 @@ thread_local! {
         )
     );
 
+    static RECIPE_CREATED_INDEX: RefCell<StableBTreeMap<String, recipe::RecipeId, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_CREATED_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static RECIPE_CREATOR_INDEX: RefCell<StableBTreeMap<String, recipe::RecipeId, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_CREATOR_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static RECIPE_KEYWORD_INDEX: RefCell<StableBTreeMap<String, recipe::RecipeId, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_KEYWORD_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static RECIPE_STATS: RefCell<StableBTreeMap<RecipeId, recipe::RecipeStats, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_STATS_MEMORY_ID)),
+        )
+    );
+
+    static RECIPE_USERS: RefCell<StableBTreeMap<String, u32, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_USERS_MEMORY_ID)),
+        )
+    );
+
+    static RECIPE_DAILY_RUNS: RefCell<StableBTreeMap<String, u32, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_DAILY_RUNS_MEMORY_ID)),
+        )
+    );
+
+    static RECIPE_RUN_COUNT_INDEX: RefCell<StableBTreeMap<String, recipe::RecipeId, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_RUN_COUNT_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static RECIPE_SEARCH_INDEX: RefCell<StableBTreeMap<String, u32, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_SEARCH_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static RECIPE_SEARCH_TERMS: RefCell<StableBTreeMap<RecipeId, recipe::search::RecipeSearchTerms, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_SEARCH_TERMS_MEMORY_ID)),
+        )
+    );
+
     // RUNS
     static RUNS: RefCell<StableBTreeMap<RunId, run::Run, Memory>> = RefCell::new(
         StableBTreeMap::init(
@@ -132,6 +218,51 @@ thread_local! {
         )
     );
 
+    static RUN_CREATED_INDEX: RefCell<StableBTreeMap<String, RunId, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RUN_CREATED_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static RUN_CREATOR_INDEX: RefCell<StableBTreeMap<String, RunId, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RUN_CREATOR_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static RUN_RECIPE_INDEX: RefCell<StableBTreeMap<String, RunId, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RUN_RECIPE_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static RUN_STATUS_INDEX: RefCell<StableBTreeMap<String, RunId, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RUN_STATUS_INDEX_MEMORY_ID)),
+        )
+    );
+
+    // BATCHES
+    static BATCHES: RefCell<StableBTreeMap<BatchId, batch::Batch, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(BATCHES_MEMORY_ID)),
+        )
+    );
+
+    // ATTESTATIONS
+    static ATTESTATION_INDEX: RefCell<StableBTreeMap<String, eas::index::IndexedAttestation, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(ATTESTATION_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static STORED_INDEXES_VERSION: RefCell<Cell<u32, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(INDEXES_VERSION_MEMORY_ID)),
+            0,
+        ).expect("Failed to initialize indexes version.")
+    );
+
     // TASKS
     static TASKS: RefCell<StableBTreeMap<Timestamp, tasks::Task, Memory>> = RefCell::new(
         StableBTreeMap::init(
@@ -191,14 +322,40 @@ fn init_and_upgrade(settings: CanisterSettingsInput) {
     init_assets();
 }
 
+fn save_indexes_version() {
+    STORED_INDEXES_VERSION.with_borrow_mut(|version| {
+        version
+            .set(INDEXES_VERSION)
+            .expect("Failed to save indexes version.");
+    });
+}
+
+/// Rebuilds the indexes and recipe stats if they were built by a version of
+/// the canister with a different layout. Rebuilding visits every recipe and
+/// run, it is skipped on upgrades that don't need it.
+fn rebuild_outdated_indexes() {
+    let stored_version = STORED_INDEXES_VERSION.with_borrow(|version| *version.get());
+    if stored_version == INDEXES_VERSION {
+        return;
+    }
+    recipe::index::rebuild_indexes();
+    recipe::search::rebuild_index();
+    run::index::rebuild_indexes();
+    eas::index::rebuild_index();
+    save_indexes_version();
+}
+
 #[init]
 fn init(settings: CanisterSettingsInput) {
     init_and_upgrade(settings);
+    save_indexes_version();
 }
 
 #[post_upgrade]
 fn post_upgrade(settings: CanisterSettingsInput) {
     init_and_upgrade(settings);
+    rebuild_outdated_indexes();
+    recipe::tasks::verify_schema::add_unchecked_verify_schema_tasks();
 }
 
 export_candid!();
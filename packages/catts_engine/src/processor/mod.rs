@@ -0,0 +1,212 @@
+pub mod rpc;
+pub mod stdlib;
+pub mod types;
+
+pub use types::*;
+
+use ic_cdk::api::{call::call, id, performance_counter};
+use rquickjs::{CatchResultExt, Context, Runtime};
+use serde::Deserialize;
+use std::{cell::Cell, rc::Rc};
+
+use crate::logger;
+
+// Console output beyond this is dropped
+const MAX_LOG_ENTRIES: usize = 100;
+const MAX_LOG_ENTRY_LENGTH: usize = 1_000;
+
+// The processor runs inside a function wrapped in an envelope that captures
+// console output and reports the outcome as JSON, keeping exceptions apart
+// from the processor output.
+const PROCESSOR_ENVELOPE: &str = r#"
+    (function () {
+        const logs = [];
+        const log = (...args) => {
+            if (logs.length >= MAX_LOG_ENTRIES) return;
+            const entry = args
+                .map((arg) => (typeof arg === "string" ? arg : JSON.stringify(arg)))
+                .join(" ");
+            logs.push(entry.substring(0, MAX_LOG_ENTRY_LENGTH));
+        };
+        globalThis.console = { log, info: log, warn: log, error: log, debug: log };
+
+        let result;
+        try {
+            const queryResult = JSON.parse(queryResultRaw);
+            const output = (function process() {
+                PROCESSOR
+            })();
+            result = typeof output === "string"
+                ? { Ok: output }
+                : { NotAString: output === null ? "null" : typeof output };
+        } catch (e) {
+            result = { Exception: e && e.stack ? `${e}\n${e.stack}` : `${e}` };
+        }
+        return JSON.stringify({ result, logs });
+    })();
+"#;
+
+#[derive(Deserialize)]
+enum EnvelopeResult {
+    Ok(String),
+    NotAString(String),
+    Exception(String),
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    result: EnvelopeResult,
+    logs: Vec<String>,
+}
+
+fn heap_size() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        const WASM_PAGE_SIZE: u64 = 65_536;
+        core::arch::wasm32::memory_size(0) as u64 * WASM_PAGE_SIZE
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0
+    }
+}
+
+fn evaluate(
+    processor: &str,
+    query_result: &str,
+    limits: &ProcessorLimits,
+    stdlib_version: Option<u32>,
+) -> Result<ProcessorOutput, ProcessorFailure> {
+    let js_process_function = PROCESSOR_ENVELOPE
+        .replace("MAX_LOG_ENTRIES", &MAX_LOG_ENTRIES.to_string())
+        .replace("MAX_LOG_ENTRY_LENGTH", &MAX_LOG_ENTRY_LENGTH.to_string())
+        .replace("PROCESSOR", processor);
+
+    let failed = |e: rquickjs::Error| ProcessorFailure::from(ProcessorError::Failed(e.to_string()));
+
+    let runtime = Runtime::new().map_err(failed)?;
+
+    // The runtime calls the handler periodically while JavaScript runs, the
+    // processor is interrupted as soon as it has used up its instructions
+    let instructions_start = performance_counter(0);
+    let max_instructions = limits.max_instructions;
+    let interrupted = Rc::new(Cell::new(false));
+    let handler_interrupted = Rc::clone(&interrupted);
+    runtime.set_interrupt_handler(Some(Box::new(move || {
+        if performance_counter(0) - instructions_start > max_instructions {
+            handler_interrupted.set(true);
+        }
+        handler_interrupted.get()
+    })));
+    runtime.set_memory_limit(limits.max_memory_bytes as usize);
+
+    let context = Context::full(&runtime).map_err(failed)?;
+
+    let envelope = context.with(|ctx| -> Result<String, ProcessorFailure> {
+        ctx.globals()
+            .set("queryResultRaw", query_result)
+            .map_err(failed)?;
+
+        // Processors of recipes without a stdlib version run without the
+        // `catts` global
+        if let Some(version) = stdlib_version {
+            let stdlib = stdlib::source(version).ok_or_else(|| {
+                ProcessorError::Failed(format!("Unsupported stdlib version {}", version))
+            })?;
+            ctx.eval::<(), _>(stdlib)
+                .catch(&ctx)
+                .map_err(|e| ProcessorError::Failed(e.to_string()))?;
+        }
+
+        // Syntax errors in the processor surface here, before the envelope runs
+        ctx.eval::<String, _>(js_process_function)
+            .catch(&ctx)
+            .map_err(|e| ProcessorError::Exception(e.to_string()).into())
+    });
+
+    if interrupted.get() {
+        return Err(ProcessorError::Timeout(limits.max_instructions).into());
+    }
+
+    let Envelope { result, logs } =
+        serde_json::from_str(&envelope?).map_err(|e| ProcessorError::Failed(e.to_string()))?;
+
+    let error = match result {
+        EnvelopeResult::Ok(output) => match serde_json::from_str::<serde_json::Value>(&output) {
+            Ok(_) => return Ok(ProcessorOutput { output, logs }),
+            Err(e) => ProcessorError::InvalidJson(e.to_string()),
+        },
+        EnvelopeResult::NotAString(type_name) => ProcessorError::NotAString(type_name),
+        EnvelopeResult::Exception(message) => ProcessorError::Exception(message),
+    };
+
+    Err(ProcessorFailure { error, logs })
+}
+
+/// Runs the processor in the current message. The processor is interrupted
+/// once it exceeds its instruction limit, the JavaScript heap is capped at its
+/// memory limit.
+pub fn execute(
+    processor: &str,
+    query_result: &str,
+    limits: &ProcessorLimits,
+    stdlib_version: Option<u32>,
+) -> Result<ProcessorOutput, ProcessorFailure> {
+    logger::debug("processor::execute");
+
+    let heap_size_before = heap_size();
+
+    let result = evaluate(processor, query_result, limits, stdlib_version);
+
+    if heap_size() - heap_size_before > limits.max_memory_bytes {
+        let logs = match result {
+            Ok(ProcessorOutput { logs, .. }) | Err(ProcessorFailure { logs, .. }) => logs,
+        };
+        return Err(ProcessorFailure {
+            error: ProcessorError::MemoryLimitExceeded(limits.max_memory_bytes),
+            logs,
+        });
+    }
+
+    result
+}
+
+/// Runs the processor in a separate message by calling the canister itself.
+/// Anything the limits don't catch, a trap of the runtime, fails only that
+/// call.
+pub async fn execute_isolated(
+    processor: &str,
+    query_result: &str,
+    limits: &ProcessorLimits,
+    stdlib_version: Option<u32>,
+) -> Result<ProcessorOutput, ProcessorFailure> {
+    let result: Result<(Result<ProcessorOutput, ProcessorFailure>,), _> = call(
+        id(),
+        "processor_execute",
+        (processor, query_result, *limits, stdlib_version),
+    )
+    .await;
+
+    match result {
+        Ok((result,)) => result,
+        Err((code, message)) => Err(ProcessorError::Failed(format!(
+            "Processor call rejected ({:?}): {}",
+            code, message
+        ))
+        .into()),
+    }
+}
+
+/// Joins the console output into one string, truncated to at most `max_length`
+/// bytes.
+pub fn logs_to_string(logs: &[String], max_length: usize) -> String {
+    let mut logs = logs.join("\n");
+    if logs.len() > max_length {
+        let mut end = max_length;
+        while !logs.is_char_boundary(end) {
+            end -= 1;
+        }
+        logs.truncate(end);
+    }
+    logs
+}
@@ -0,0 +1 @@
+pub mod processor_execute;
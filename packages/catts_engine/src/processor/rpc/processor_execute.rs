@@ -0,0 +1,20 @@
+use ic_cdk::{api::id, caller, update};
+
+use crate::processor::{self, ProcessorError, ProcessorFailure, ProcessorLimits, ProcessorOutput};
+
+/// Only callable by the canister itself, see `processor::execute_isolated`.
+#[update]
+fn processor_execute(
+    processor: String,
+    query_result: String,
+    limits: ProcessorLimits,
+    stdlib_version: Option<u32>,
+) -> Result<ProcessorOutput, ProcessorFailure> {
+    if caller() != id() {
+        return Err(
+            ProcessorError::Failed("Only the canister can execute processors".to_string()).into(),
+        );
+    }
+
+    processor::execute(&processor, &query_result, &limits, stdlib_version)
+}
@@ -0,0 +1,20 @@
+use validator::ValidationError;
+
+// Published versions are frozen, recipes keep running against the version
+// they were saved with.
+const STDLIB_V1: &str = include_str!("v1.js");
+
+/// The source of the `catts` global for the given stdlib version.
+pub fn source(version: u32) -> Option<&'static str> {
+    match version {
+        1 => Some(STDLIB_V1),
+        _ => None,
+    }
+}
+
+pub fn validate_stdlib_version(version: u32) -> Result<(), ValidationError> {
+    if source(version).is_none() {
+        return Err(ValidationError::new("Unsupported stdlib version"));
+    }
+    Ok(())
+}
@@ -0,0 +1,245 @@
+// catts processor standard library, version 1.
+//
+// Exposed to processors of recipes declaring stdlib_version 1 as the global
+// `catts`. Published versions must never change behaviour, add a new version
+// instead.
+globalThis.catts = (function () {
+  const MASK_64 = (1n << 64n) - 1n;
+
+  const KECCAK_ROUND_CONSTANTS = [
+    0x0000000000000001n, 0x0000000000008082n, 0x800000000000808an,
+    0x8000000080008000n, 0x000000000000808bn, 0x0000000080000001n,
+    0x8000000080008081n, 0x8000000000008009n, 0x000000000000008an,
+    0x0000000000000088n, 0x0000000080008009n, 0x000000008000000an,
+    0x000000008000808bn, 0x800000000000008bn, 0x8000000000008089n,
+    0x8000000000008003n, 0x8000000000008002n, 0x8000000000000080n,
+    0x000000000000800an, 0x800000008000000an, 0x8000000080008081n,
+    0x8000000000008080n, 0x0000000080000001n, 0x8000000080008008n,
+  ];
+
+  // Rotation offsets, indexed by x + 5 * y
+  const KECCAK_ROTATIONS = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8,
+    18, 2, 61, 56, 14,
+  ];
+
+  const rotl64 = (value, shift) =>
+    shift === 0
+      ? value
+      : ((value << BigInt(shift)) | (value >> BigInt(64 - shift))) & MASK_64;
+
+  const keccakF = (state) => {
+    const c = new Array(5);
+    const b = new Array(25);
+    for (let round = 0; round < 24; round++) {
+      for (let x = 0; x < 5; x++) {
+        c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+      }
+      for (let x = 0; x < 5; x++) {
+        const d = c[(x + 4) % 5] ^ rotl64(c[(x + 1) % 5], 1);
+        for (let y = 0; y < 25; y += 5) {
+          state[x + y] ^= d;
+        }
+      }
+      for (let x = 0; x < 5; x++) {
+        for (let y = 0; y < 5; y++) {
+          b[y + 5 * ((2 * x + 3 * y) % 5)] = rotl64(
+            state[x + 5 * y],
+            KECCAK_ROTATIONS[x + 5 * y],
+          );
+        }
+      }
+      for (let x = 0; x < 5; x++) {
+        for (let y = 0; y < 25; y += 5) {
+          state[x + y] = b[x + y] ^ (~b[((x + 1) % 5) + y] & MASK_64 & b[((x + 2) % 5) + y]);
+        }
+      }
+      state[0] ^= KECCAK_ROUND_CONSTANTS[round];
+    }
+  };
+
+  const keccak256 = (bytes) => {
+    const rate = 136;
+    const padded = new Uint8Array((Math.floor(bytes.length / rate) + 1) * rate);
+    padded.set(bytes);
+    padded[bytes.length] ^= 0x01;
+    padded[padded.length - 1] ^= 0x80;
+
+    const state = new Array(25).fill(0n);
+    for (let offset = 0; offset < padded.length; offset += rate) {
+      for (let i = 0; i < rate / 8; i++) {
+        let lane = 0n;
+        for (let j = 0; j < 8; j++) {
+          lane |= BigInt(padded[offset + i * 8 + j]) << BigInt(8 * j);
+        }
+        state[i] ^= lane;
+      }
+      keccakF(state);
+    }
+
+    const out = new Uint8Array(32);
+    for (let i = 0; i < 32; i++) {
+      out[i] = Number((state[Math.floor(i / 8)] >> BigInt(8 * (i % 8))) & 0xffn);
+    }
+    return out;
+  };
+
+  const hex = {
+    strip0x: (value) => (value.startsWith("0x") ? value.slice(2) : value),
+    add0x: (value) => (value.startsWith("0x") ? value : `0x${value}`),
+    fromBytes: (bytes) =>
+      "0x" + Array.from(bytes, (byte) => byte.toString(16).padStart(2, "0")).join(""),
+    toBytes: (value) => {
+      const digits = hex.strip0x(value);
+      if (digits.length % 2 !== 0 || /[^0-9a-fA-F]/.test(digits)) {
+        throw new Error(`Invalid hex string: ${value}`);
+      }
+      const bytes = new Uint8Array(digits.length / 2);
+      for (let i = 0; i < bytes.length; i++) {
+        bytes[i] = parseInt(digits.substr(i * 2, 2), 16);
+      }
+      return bytes;
+    },
+    fromUtf8: (value) => {
+      const binary = unescape(encodeURIComponent(value));
+      return hex.fromBytes(Array.from(binary, (char) => char.charCodeAt(0)));
+    },
+    toUtf8: (value) =>
+      decodeURIComponent(
+        escape(String.fromCharCode(...hex.toBytes(value))),
+      ),
+  };
+
+  const bigint = {
+    // Accepts bigints, integer numbers, decimal or 0x prefixed hex strings and
+    // ethers style { hex } objects as found in EAS decoded data.
+    from: (value) => {
+      if (typeof value === "bigint") return value;
+      if (typeof value === "number") {
+        if (!Number.isInteger(value)) throw new Error(`Not an integer: ${value}`);
+        return BigInt(value);
+      }
+      if (typeof value === "string") return BigInt(value.trim());
+      if (value && typeof value.hex === "string") return BigInt(value.hex);
+      throw new Error(`Can't convert to bigint: ${JSON.stringify(value)}`);
+    },
+    toHex: (value) => {
+      const n = bigint.from(value);
+      return n < 0n ? `-0x${(-n).toString(16)}` : `0x${n.toString(16)}`;
+    },
+    toDecimal: (value) => bigint.from(value).toString(),
+    // Formats a fixed point integer, e.g. format(12345n, 2) === "123.45"
+    format: (value, decimals) => {
+      const n = bigint.from(value);
+      const negative = n < 0n;
+      const digits = (negative ? -n : n).toString().padStart(decimals + 1, "0");
+      const whole = digits.slice(0, digits.length - decimals);
+      const fraction = digits.slice(digits.length - decimals).replace(/0+$/, "");
+      return `${negative ? "-" : ""}${whole}${fraction ? "." + fraction : ""}`;
+    },
+    // Parses a decimal string into a fixed point integer, e.g.
+    // parse("123.45", 2) === 12345n
+    parse: (value, decimals) => {
+      const match = /^(-?)(\d*)(?:\.(\d*))?$/.exec(String(value).trim());
+      if (!match) throw new Error(`Invalid decimal: ${value}`);
+      const fraction = (match[3] || "").padEnd(decimals, "0");
+      if (fraction.length > decimals) throw new Error(`Too many decimals: ${value}`);
+      const n = BigInt((match[2] || "0") + fraction);
+      return match[1] ? -n : n;
+    },
+    min: (...values) => values.map(bigint.from).reduce((a, b) => (b < a ? b : a)),
+    max: (...values) => values.map(bigint.from).reduce((a, b) => (b > a ? b : a)),
+  };
+
+  const address = {
+    isValid: (value) => typeof value === "string" && /^0x[0-9a-fA-F]{40}$/.test(value),
+    // EIP-55 mixed case checksum encoding
+    toChecksum: (value) => {
+      if (!address.isValid(value)) throw new Error(`Invalid address: ${value}`);
+      const lower = value.slice(2).toLowerCase();
+      const hash = hex.strip0x(
+        hex.fromBytes(keccak256(Array.from(lower, (char) => char.charCodeAt(0)))),
+      );
+      let checksummed = "0x";
+      for (let i = 0; i < lower.length; i++) {
+        checksummed += parseInt(hash[i], 16) >= 8 ? lower[i].toUpperCase() : lower[i];
+      }
+      return checksummed;
+    },
+    equals: (a, b) =>
+      address.isValid(a) && address.isValid(b) && a.toLowerCase() === b.toLowerCase(),
+  };
+
+  const date = {
+    // Unix time in seconds from a unix time in seconds or milliseconds or a
+    // date string
+    toUnix: (value) => {
+      if (typeof value === "number") {
+        return value > 1e12 ? Math.floor(value / 1000) : Math.floor(value);
+      }
+      const millis = Date.parse(value);
+      if (Number.isNaN(millis)) throw new Error(`Invalid date: ${value}`);
+      return Math.floor(millis / 1000);
+    },
+    toIso: (value) => new Date(date.toUnix(value) * 1000).toISOString(),
+  };
+
+  const eas = {
+    // The attestations returned by the query at the given index of the
+    // aggregated query result
+    attestations: (queryResult, index = 0) => {
+      const result = queryResult[index];
+      const attestations = result && (result.attestations || (result.data && result.data.attestations));
+      return Array.isArray(attestations) ? attestations : [];
+    },
+    // Decodes the decodedDataJson of an attestation into an object keyed by
+    // field name, unwrapping ethers style BigNumber values into bigints
+    decodedData: (attestation) => {
+      const items =
+        typeof attestation.decodedDataJson === "string"
+          ? JSON.parse(attestation.decodedDataJson)
+          : attestation.decodedDataJson;
+      const data = {};
+      for (const item of items) {
+        const value = item.value && "value" in item.value ? item.value.value : item.value;
+        data[item.name] =
+          value && typeof value === "object" && value.type === "BigNumber"
+            ? BigInt(value.hex)
+            : value;
+      }
+      return data;
+    },
+  };
+
+  const schema = {
+    // Builds processor output for the schema from an object keyed by field
+    // name, e.g. items("uint256 score,bool verified", { score: 1n, verified: true })
+    items: (schemaString, values) =>
+      schemaString.split(",").map((field) => {
+        const [type, name] = field.trim().split(/\s+/);
+        if (!(name in values)) throw new Error(`Missing value for ${name}`);
+        const value = values[name];
+        return {
+          name,
+          type,
+          value: typeof value === "bigint" ? value.toString() : value,
+        };
+      }),
+    // Serializes processor output, bigints are converted to decimal strings
+    stringify: (items) =>
+      JSON.stringify(items, (_, value) =>
+        typeof value === "bigint" ? value.toString() : value,
+      ),
+  };
+
+  return Object.freeze({
+    version: 1,
+    keccak256: (bytes) => hex.fromBytes(keccak256(typeof bytes === "string" ? hex.toBytes(bytes) : bytes)),
+    hex: Object.freeze(hex),
+    bigint: Object.freeze(bigint),
+    address: Object.freeze(address),
+    date: Object.freeze(date),
+    eas: Object.freeze(eas),
+    schema: Object.freeze(schema),
+  });
+})();
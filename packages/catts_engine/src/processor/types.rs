@@ -0,0 +1,82 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use validator::ValidationError;
+
+pub const PROCESSOR_DEFAULT_MAX_INSTRUCTIONS: u64 = 2_000_000_000;
+
+// A quarter of the 40B instruction limit of an update message, leaving room
+// for the work done around the processor.
+pub const PROCESSOR_MAX_INSTRUCTIONS: u64 = 10_000_000_000;
+
+pub const PROCESSOR_DEFAULT_MAX_MEMORY_BYTES: u64 = 16 * 1024 * 1024; // 16 MiB
+pub const PROCESSOR_MAX_MEMORY_BYTES: u64 = 64 * 1024 * 1024; // 64 MiB
+
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone, Copy, PartialEq)]
+pub struct ProcessorLimits {
+    pub max_instructions: u64,
+    // Max growth of the canister heap while the processor runs
+    pub max_memory_bytes: u64,
+}
+
+impl Default for ProcessorLimits {
+    fn default() -> Self {
+        Self {
+            max_instructions: PROCESSOR_DEFAULT_MAX_INSTRUCTIONS,
+            max_memory_bytes: PROCESSOR_DEFAULT_MAX_MEMORY_BYTES,
+        }
+    }
+}
+
+pub fn validate_processor_limits(limits: &ProcessorLimits) -> Result<(), ValidationError> {
+    if limits.max_instructions == 0 || limits.max_instructions > PROCESSOR_MAX_INSTRUCTIONS {
+        return Err(ValidationError::new(
+            "max_instructions must be between 1 and 10 000 000 000",
+        ));
+    }
+    if limits.max_memory_bytes == 0 || limits.max_memory_bytes > PROCESSOR_MAX_MEMORY_BYTES {
+        return Err(ValidationError::new(
+            "max_memory_bytes must be between 1 and 67 108 864",
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Error, Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum ProcessorError {
+    #[error("Processor threw an exception: {0}")]
+    Exception(String),
+    #[error("Processor must return a string, got {0}")]
+    NotAString(String),
+    #[error("Processor returned invalid JSON: {0}")]
+    InvalidJson(String),
+    #[error("Processor timed out, instruction limit of {0} exceeded")]
+    Timeout(u64),
+    #[error("Processor exceeded the memory limit of {0} bytes")]
+    MemoryLimitExceeded(u64),
+    #[error("Processor failed: {0}")]
+    Failed(String),
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ProcessorOutput {
+    pub output: String,
+    pub logs: Vec<String>,
+}
+
+#[derive(Error, Debug, CandidType, Serialize, Deserialize, Clone)]
+#[error("{error}")]
+pub struct ProcessorFailure {
+    pub error: ProcessorError,
+    // Console output up until the failure
+    pub logs: Vec<String>,
+}
+
+impl From<ProcessorError> for ProcessorFailure {
+    fn from(error: ProcessorError) -> Self {
+        Self {
+            error,
+            logs: vec![],
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::{eth_address::EthAddress, http_error::HttpError, time::time};
+
+const RATE_LIMIT_WINDOW_SECONDS: u32 = 60;
+
+/// Calls that make the canister pay for EVM RPC calls, HTTPS outcalls or
+/// processor runs without the caller paying for them, limited per user.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RateLimitedCall {
+    AttestationVerify,
+    RecipeRunTests,
+    RecipeSimulate,
+}
+
+impl RateLimitedCall {
+    // Calls allowed per user in each window
+    fn max_calls(&self) -> u32 {
+        match self {
+            RateLimitedCall::AttestationVerify => 10,
+            RateLimitedCall::RecipeRunTests => 5,
+            RateLimitedCall::RecipeSimulate => 5,
+        }
+    }
+}
+
+thread_local! {
+    // Start of the current window and the calls made in it, per user and call.
+    // Kept on the heap, the windows start over after an upgrade.
+    static CALLS: RefCell<HashMap<(String, RateLimitedCall), (u32, u32)>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Counts a call of the user, fails once the user has made the maximum number
+/// of calls in the current window.
+pub fn check(address: &EthAddress, call: RateLimitedCall) -> Result<(), HttpError> {
+    let now = time();
+    CALLS.with_borrow_mut(|calls| {
+        calls.retain(|_, (window_start, _)| now - *window_start < RATE_LIMIT_WINDOW_SECONDS);
+
+        let (_, count) = calls
+            .entry((address.as_str().to_string(), call))
+            .or_insert((now, 0));
+        if *count >= call.max_calls() {
+            return Err(HttpError::too_many_requests(format!(
+                "At most {} calls per {} seconds",
+                call.max_calls(),
+                RATE_LIMIT_WINDOW_SECONDS
+            )));
+        }
+        *count += 1;
+        Ok(())
+    })
+}
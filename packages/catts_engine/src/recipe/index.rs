@@ -0,0 +1,188 @@
+use std::{cell::RefCell, ops::Bound, thread::LocalKey};
+
+use ethers_core::utils::hex;
+use ic_stable_structures::StableBTreeMap;
+
+use crate::{
+    Memory, RECIPES, RECIPE_CREATED_INDEX, RECIPE_CREATOR_INDEX, RECIPE_KEYWORD_INDEX,
+    RECIPE_NAME_INDEX, RECIPE_RUN_COUNT_INDEX,
+};
+
+use super::{stats, Recipe, RecipeId, RecipeListFilters, RecipeListSortBy, SortOrder};
+
+type RecipeIndex = StableBTreeMap<String, RecipeId, Memory>;
+
+// Index keys are strings ending with the hex encoded recipe id to make them
+// unique. Numbers are zero padded to sort correctly.
+
+fn created_key(recipe: &Recipe) -> String {
+    format!("{:010}{}", recipe.created, hex::encode(recipe.id))
+}
+
+fn creator_key(recipe: &Recipe) -> String {
+    format!("{}{}", recipe.creator, hex::encode(recipe.id))
+}
+
+// Keywords can't contain colons, the separator keeps one keyword from
+// matching another keyword it is a prefix of.
+fn keyword_key(keyword: &str, recipe_id: &RecipeId) -> String {
+    format!("{}:{}", keyword, hex::encode(recipe_id))
+}
+
+fn run_count_key(run_count: u32, recipe_id: &RecipeId) -> String {
+    format!("{:010}{}", run_count, hex::encode(recipe_id))
+}
+
+fn get_run_count(recipe_id: &RecipeId) -> u32 {
+    stats::get(recipe_id).runs_created
+}
+
+/// Returns the key of the recipe in the index used for the given sort order.
+pub fn sort_key(recipe: &Recipe, sort_by: RecipeListSortBy) -> String {
+    match sort_by {
+        RecipeListSortBy::Created => created_key(recipe),
+        RecipeListSortBy::Name => recipe.name.clone(),
+        RecipeListSortBy::RunCount => run_count_key(get_run_count(&recipe.id), &recipe.id),
+    }
+}
+
+fn sort_index(sort_by: RecipeListSortBy) -> &'static LocalKey<RefCell<RecipeIndex>> {
+    match sort_by {
+        RecipeListSortBy::Created => &RECIPE_CREATED_INDEX,
+        RecipeListSortBy::Name => &RECIPE_NAME_INDEX,
+        RecipeListSortBy::RunCount => &RECIPE_RUN_COUNT_INDEX,
+    }
+}
+
+/// Adds the recipe to the secondary indexes. The name index is maintained
+/// separately as it is also used to enforce unique names.
+pub fn add(recipe: &Recipe) {
+    RECIPE_CREATED_INDEX.with_borrow_mut(|index| {
+        index.insert(created_key(recipe), recipe.id);
+    });
+    RECIPE_CREATOR_INDEX.with_borrow_mut(|index| {
+        index.insert(creator_key(recipe), recipe.id);
+    });
+    if let Some(ref keywords) = recipe.keywords {
+        RECIPE_KEYWORD_INDEX.with_borrow_mut(|index| {
+            for keyword in keywords {
+                index.insert(keyword_key(keyword, &recipe.id), recipe.id);
+            }
+        });
+    }
+    RECIPE_RUN_COUNT_INDEX.with_borrow_mut(|index| {
+        index.insert(
+            run_count_key(get_run_count(&recipe.id), &recipe.id),
+            recipe.id,
+        );
+    });
+}
+
+/// Removes the recipe from the secondary indexes.
+pub fn remove(recipe: &Recipe) {
+    RECIPE_CREATED_INDEX.with_borrow_mut(|index| {
+        index.remove(&created_key(recipe));
+    });
+    RECIPE_CREATOR_INDEX.with_borrow_mut(|index| {
+        index.remove(&creator_key(recipe));
+    });
+    if let Some(ref keywords) = recipe.keywords {
+        RECIPE_KEYWORD_INDEX.with_borrow_mut(|index| {
+            for keyword in keywords {
+                index.remove(&keyword_key(keyword, &recipe.id));
+            }
+        });
+    }
+    RECIPE_RUN_COUNT_INDEX.with_borrow_mut(|index| {
+        index.remove(&run_count_key(get_run_count(&recipe.id), &recipe.id));
+    });
+}
+
+/// Moves the recipe in the run count index, called when the run count in the
+/// recipe stats changes.
+pub fn update_run_count(recipe_id: &RecipeId, old_run_count: u32, run_count: u32) {
+    RECIPE_RUN_COUNT_INDEX.with_borrow_mut(|index| {
+        index.remove(&run_count_key(old_run_count, recipe_id));
+        index.insert(run_count_key(run_count, recipe_id), *recipe_id);
+    });
+}
+
+fn ids_with_prefix(
+    index: &'static LocalKey<RefCell<RecipeIndex>>,
+    prefix: &str,
+    max_items: usize,
+) -> Option<Vec<RecipeId>> {
+    let recipe_ids: Vec<RecipeId> = index.with_borrow(|index| {
+        index
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .take(max_items + 1)
+            .map(|(_, recipe_id)| recipe_id)
+            .collect()
+    });
+    (recipe_ids.len() <= max_items).then_some(recipe_ids)
+}
+
+/// Returns the ids of the recipes matching the creator or keyword filter, or
+/// None if the filters can't be served by an index. Filters matching more
+/// than `max_items` recipes are not served by an index either, the sort index
+/// is walked page by page instead.
+pub fn filter_candidates(filters: &RecipeListFilters, max_items: usize) -> Option<Vec<RecipeId>> {
+    if let Some(ref creator) = filters.creator {
+        return ids_with_prefix(&RECIPE_CREATOR_INDEX, &creator.to_lowercase(), max_items);
+    }
+    if let Some(ref keyword) = filters.keyword {
+        return ids_with_prefix(&RECIPE_KEYWORD_INDEX, &format!("{}:", keyword), max_items);
+    }
+    None
+}
+
+/// Walks the index for the given sort order, starting after the cursor.
+/// Returns the index key and id of each visited recipe, visiting at most
+/// `max_items` recipes.
+pub fn scan(
+    sort_by: RecipeListSortBy,
+    sort_order: SortOrder,
+    cursor: Option<&String>,
+    max_items: usize,
+) -> Vec<(String, RecipeId)> {
+    sort_index(sort_by).with_borrow(|index| match (sort_order, cursor) {
+        (SortOrder::Asc, Some(cursor)) => index
+            .range((Bound::Excluded(cursor.clone()), Bound::Unbounded))
+            .take(max_items)
+            .collect(),
+        (SortOrder::Asc, None) => index.iter().take(max_items).collect(),
+        (SortOrder::Desc, Some(cursor)) => index
+            .range(..cursor.clone())
+            .rev()
+            .take(max_items)
+            .collect(),
+        (SortOrder::Desc, None) => index.iter().rev().take(max_items).collect(),
+    })
+}
+
+fn clear(index: &'static LocalKey<RefCell<RecipeIndex>>) {
+    index.with_borrow_mut(|index| {
+        let keys: Vec<String> = index.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            index.remove(&key);
+        }
+    });
+}
+
+/// Recreates the recipe stats and secondary indexes from the recipes and runs
+/// in stable memory.
+pub fn rebuild_indexes() {
+    clear(&RECIPE_CREATED_INDEX);
+    clear(&RECIPE_CREATOR_INDEX);
+    clear(&RECIPE_KEYWORD_INDEX);
+    clear(&RECIPE_RUN_COUNT_INDEX);
+
+    stats::rebuild();
+
+    RECIPES.with_borrow(|recipes| {
+        for (_, recipe) in recipes.iter() {
+            add(&recipe);
+        }
+    });
+}
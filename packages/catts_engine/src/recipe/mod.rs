@@ -1,5 +1,11 @@
+pub mod index;
+pub mod processor_tests;
 pub mod rpc;
+pub mod search;
+pub mod simulate;
 pub mod state;
+pub mod stats;
+pub mod tasks;
 pub mod types;
 pub mod utils;
 
@@ -0,0 +1,77 @@
+use serde_json::Value;
+
+use crate::{
+    eas::{encode_abi_data, expiration_time, parse_processor_output, schema},
+    processor,
+};
+
+use super::{Recipe, RecipeProcessorTest, RecipeTestResult};
+
+// Outputs are compared as JSON to ignore formatting differences, falling back
+// to comparing the raw strings.
+fn outputs_match(output: &str, expected_output: &str) -> bool {
+    match (
+        serde_json::from_str::<Value>(output),
+        serde_json::from_str::<Value>(expected_output),
+    ) {
+        (Ok(output), Ok(expected_output)) => output == expected_output,
+        _ => output.trim() == expected_output.trim(),
+    }
+}
+
+async fn run_test(recipe: &Recipe, test: &RecipeProcessorTest) -> RecipeTestResult {
+    let output = match processor::execute_isolated(
+        &recipe.processor,
+        &test.query_result,
+        &recipe.processor_limits(),
+        recipe.stdlib_version,
+    )
+    .await
+    {
+        Ok(processor_output) => processor_output.output,
+        Err(err) => {
+            return RecipeTestResult {
+                name: test.name.clone(),
+                passed: false,
+                output: String::new(),
+                error: Some(err.to_string()),
+            }
+        }
+    };
+
+    let error = match parse_processor_output(&output, &test.query_result) {
+        Err(err) => Some(err.to_string()),
+        Ok(attestation_output) => {
+            if let Err(err) = schema::validate_output(&recipe.schema, &attestation_output.data) {
+                Some(format!("Output does not match the schema: {}", err))
+            } else if !outputs_match(&output, &test.expected_output) {
+                Some("Output does not match the expected output".to_string())
+            } else if let Err(err) =
+                encode_abi_data(&attestation_output.data, recipe.integer_strings())
+            {
+                Some(format!("Output can't be encoded: {}", err))
+            } else if let Err(err) = expiration_time(recipe, &attestation_output) {
+                Some(err.to_string())
+            } else {
+                None
+            }
+        }
+    };
+
+    RecipeTestResult {
+        name: test.name.clone(),
+        passed: error.is_none(),
+        output,
+        error,
+    }
+}
+
+/// Runs the processor of the recipe against each of its test fixtures, each
+/// test in a call of its own.
+pub async fn run_tests(recipe: &Recipe) -> Vec<RecipeTestResult> {
+    let mut results = vec![];
+    for test in recipe.tests.iter().flatten() {
+        results.push(run_test(recipe, test).await);
+    }
+    results
+}
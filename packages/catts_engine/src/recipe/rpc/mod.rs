@@ -1,3 +1,5 @@
+pub mod recipe_accept_ownership;
+pub mod recipe_cancel_ownership_transfer;
 pub mod recipe_create;
 pub mod recipe_delete;
 pub mod recipe_get_by_id;
@@ -6,3 +8,12 @@ pub mod recipe_get_readme_by_id;
 pub mod recipe_get_readme_by_name;
 pub mod recipe_list;
 pub mod recipe_publish;
+pub mod recipe_register_schema;
+pub mod recipe_run_tests;
+pub mod recipe_search;
+pub mod recipe_set_maintainers;
+pub mod recipe_simulate;
+pub mod recipe_stats;
+pub mod recipe_transfer_ownership;
+pub mod recipe_trending;
+pub mod recipe_update;
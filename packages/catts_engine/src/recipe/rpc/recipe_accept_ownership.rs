@@ -0,0 +1,18 @@
+use ic_cdk::update;
+
+use crate::{
+    http_error::HttpError,
+    recipe::{self, Recipe, RecipeError, RecipeId},
+    user::auth_guard,
+};
+
+#[update]
+fn recipe_accept_ownership(recipe_id: RecipeId) -> Result<Recipe, HttpError> {
+    let address = auth_guard()?;
+
+    recipe::accept_ownership(&recipe_id, &address).map_err(|err| match err {
+        RecipeError::NotFound => HttpError::not_found(err),
+        RecipeError::NoPendingTransfer => HttpError::forbidden(err),
+        err => HttpError::internal_server_error(err),
+    })
+}
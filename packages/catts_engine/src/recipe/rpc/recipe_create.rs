@@ -2,7 +2,7 @@ use ic_cdk::update;
 
 use crate::{
     http_error::HttpError,
-    recipe::{self, Recipe, RecipeDetailsInput},
+    recipe::{self, Recipe, RecipeDetailsInput, RecipeError},
     user::auth_guard,
 };
 
@@ -10,6 +10,16 @@ use crate::{
 pub fn recipe_create(details: RecipeDetailsInput, readme: String) -> Result<Recipe, HttpError> {
     let address = auth_guard()?;
     let recipe = Recipe::new(&details, &address).map_err(HttpError::bad_request)?;
+
+    // Recreating a draft is only allowed for the creator it was created by. The
+    // name may belong to a recipe of another creator, or to a recipe whose
+    // ownership was transferred since it was first created.
+    if let Ok(saved_recipe) = recipe::get_by_name(&recipe.name) {
+        if saved_recipe.id != recipe.id || !saved_recipe.can_edit(&address) {
+            return Err(HttpError::conflict(RecipeError::NameInUse));
+        }
+    }
+
     let recipe = recipe::save(recipe).map_err(HttpError::conflict)?;
     recipe::write_readme(&recipe.name, &readme)
         .map_err(|_| HttpError::internal_server_error("Couldn't save README file."))?;
@@ -11,7 +11,7 @@ fn recipe_delete(recipe_id: RecipeId) -> Result<Recipe, HttpError> {
     let address = auth_guard()?;
     let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
 
-    if address.to_string() != recipe.creator {
+    if !recipe.is_creator(&address) {
         return Err(HttpError::unauthorized(
             "You are not the author of this recipe.",
         ));
@@ -1,8 +1,22 @@
 use ic_cdk::query;
 
-use crate::recipe::{self, Recipe};
+use crate::{
+    eth_address::EthAddress,
+    http_error::HttpError,
+    recipe::{self, RecipeListOptions, RecipeListResponse},
+};
 
 #[query]
-fn recipe_list() -> Result<Vec<Recipe>, String> {
-    Ok(recipe::list())
+fn recipe_list(options: Option<RecipeListOptions>) -> Result<RecipeListResponse, HttpError> {
+    let options = options.unwrap_or_default();
+
+    if let Some(creator) = options
+        .filters
+        .as_ref()
+        .and_then(|filters| filters.creator.as_ref())
+    {
+        EthAddress::new(creator).map_err(HttpError::bad_request)?;
+    }
+
+    Ok(recipe::list(&options))
 }
@@ -1,21 +1,53 @@
 use ic_cdk::update;
 
 use crate::{
+    chain_config::{self},
     http_error::HttpError,
-    recipe::{self, Recipe, RecipeId},
+    recipe::{
+        self, processor_tests,
+        tasks::verify_schema::{add_verify_schema_task, verify_chain_schema},
+        Recipe, RecipeError, RecipeId,
+    },
     user::auth_guard,
 };
 
 #[update]
-fn recipe_publish(recipe_id: RecipeId) -> Result<Recipe, HttpError> {
+async fn recipe_publish(recipe_id: RecipeId) -> Result<Recipe, HttpError> {
     let address = auth_guard()?;
     let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
 
-    if address.to_string() != recipe.creator {
+    if !recipe.can_edit(&address) {
         return Err(HttpError::unauthorized(
-            "You are not the author of this recipe.",
+            "You are not the author or a maintainer of this recipe.",
         ));
     }
 
-    recipe::publish(&recipe_id).map_err(HttpError::bad_request)
+    let test_results = processor_tests::run_tests(&recipe).await;
+
+    // Chains the recipe declares are checked for the schema before publishing,
+    // failed tests are reported first
+    let tests_passed = test_results.iter().all(|result| result.passed);
+    if let (true, Some(ref chain_ids)) = (tests_passed, &recipe.chain_ids) {
+        for chain_id in chain_ids {
+            let chain_config =
+                chain_config::get(*chain_id).map_err(HttpError::internal_server_error)?;
+            verify_chain_schema(&recipe, &chain_config)
+                .await
+                .map_err(HttpError::internal_server_error)?;
+        }
+    }
+
+    let recipe = recipe::publish(&recipe, &test_results).map_err(|err| match err {
+        RecipeError::ChangedDuringTests => HttpError::conflict(err),
+        err => HttpError::bad_request(err),
+    })?;
+
+    // Recipes without declared chains support every configured chain, check their
+    // SchemaRegistry in the background. Runs are rejected on chains where the
+    // schema is found to be missing.
+    if recipe.chain_ids.is_none() {
+        add_verify_schema_task(&recipe_id, 0);
+    }
+
+    Ok(recipe)
 }
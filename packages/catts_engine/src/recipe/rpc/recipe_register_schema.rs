@@ -0,0 +1,151 @@
+use std::{cell::RefCell, collections::HashSet};
+
+use candid::Nat;
+use ic_cdk::{
+    api::{caller, is_controller},
+    update,
+};
+
+use crate::{
+    chain_config::{self},
+    eas::{eip712::to_hex, get_schema_uid, registry::register_schema},
+    evm::rpc::eth_get_transaction_receipt,
+    http_error::HttpError,
+    logger::{self},
+    recipe::{
+        self, tasks::verify_schema::add_verify_schema_task, Recipe, RecipeId,
+        RecipeSchemaRegistration,
+    },
+};
+
+// Time to wait for the registration transaction before checking the registry
+const VERIFY_REGISTRATION_DELAY: u64 = 30_000_000_000; // 30 seconds
+
+// Upper bound on gas * (base fee + priority fee) the canister pays for one
+// registration, in wei
+const SCHEMA_REGISTRATION_MAX_FEE: u128 = 10_000_000_000_000_000; // 0.01 ETH
+
+thread_local! {
+    // Registrations being sent, a second call for the same recipe and chain is
+    // rejected until the first one has saved its transaction hash
+    static REGISTERING: RefCell<HashSet<(RecipeId, u32)>> = RefCell::new(HashSet::new());
+}
+
+// Releases the registration lock also when the call fails after an await
+struct RegistrationLock {
+    key: (RecipeId, u32),
+}
+
+impl RegistrationLock {
+    fn acquire(recipe_id: RecipeId, chain_id: u32) -> Result<Self, HttpError> {
+        let key = (recipe_id, chain_id);
+        if !REGISTERING.with_borrow_mut(|registering| registering.insert(key)) {
+            return Err(HttpError::conflict("Schema registration is pending"));
+        }
+        Ok(Self { key })
+    }
+}
+
+impl Drop for RegistrationLock {
+    fn drop(&mut self) {
+        REGISTERING.with_borrow_mut(|registering| registering.remove(&self.key));
+    }
+}
+
+/// Registers the schema of the recipe in the SchemaRegistry of a chain, from
+/// the canister address. The canister pays for the transaction, only
+/// controllers can register and only schemas found to be missing.
+#[update]
+async fn recipe_register_schema(
+    recipe_id: RecipeId,
+    chain_id: u32,
+    base_fee_per_gas: Nat,
+    max_priority_fee_per_gas: Nat,
+    gas: Nat,
+) -> Result<Recipe, HttpError> {
+    if !is_controller(&caller()) {
+        return Err(HttpError::forbidden(
+            "Only controllers can register schemas",
+        ));
+    }
+    logger::debug("recipe_register_schema");
+
+    let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+
+    let chain_config = chain_config::get(chain_id)
+        .map_err(|_| HttpError::bad_request(format!("Chain {} is not supported", chain_id)))?;
+
+    if !recipe.supports_chain(chain_id) {
+        return Err(HttpError::bad_request(format!(
+            "Recipe does not support chain {}",
+            chain_id
+        )));
+    }
+
+    let fee = gas.clone() * (base_fee_per_gas.clone() + max_priority_fee_per_gas.clone());
+    if fee > SCHEMA_REGISTRATION_MAX_FEE {
+        return Err(HttpError::bad_request(format!(
+            "Registration fee {} exceeds the maximum of {} wei",
+            fee, SCHEMA_REGISTRATION_MAX_FEE
+        )));
+    }
+
+    let previous_transaction_hash = match recipe.schema_registration(chain_id) {
+        Some(registration) if registration.registered => {
+            return Err(HttpError::conflict("Schema is already registered"));
+        }
+        Some(registration) => registration.transaction_hash.clone(),
+        None => {
+            return Err(HttpError::bad_request(
+                "Schema registration has not been checked yet",
+            ));
+        }
+    };
+
+    let _lock = RegistrationLock::acquire(recipe_id, chain_id)?;
+
+    // A previous registration can only be replaced once its transaction is
+    // known to have failed
+    if let Some(transaction_hash) = previous_transaction_hash {
+        let receipt = eth_get_transaction_receipt(&transaction_hash, &chain_config)
+            .await
+            .map_err(|_| HttpError::conflict("Schema registration is pending"))?;
+        if receipt.status != Some(Nat::from(0u8)) {
+            add_verify_schema_task(&recipe_id, 0);
+            return Err(HttpError::conflict(
+                "Schema registration is waiting to be verified",
+            ));
+        }
+    }
+
+    let uid = get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)
+        .map_err(HttpError::internal_server_error)?;
+
+    let transaction_hash = register_schema(
+        &recipe,
+        gas,
+        base_fee_per_gas,
+        max_priority_fee_per_gas,
+        &chain_config,
+    )
+    .await
+    .map_err(|err| {
+        HttpError::internal_server_error(format!("Error registering schema: {}", err))
+    })?;
+
+    let recipe = recipe::set_schema_registration(
+        &recipe_id,
+        RecipeSchemaRegistration {
+            chain_id,
+            uid: to_hex(&uid),
+            registered: false,
+            transaction_hash: Some(transaction_hash),
+            checked: None,
+        },
+    )
+    .map_err(HttpError::internal_server_error)?;
+
+    add_verify_schema_task(&recipe_id, ic_cdk::api::time() + VERIFY_REGISTRATION_DELAY);
+
+    Ok(recipe)
+}
@@ -0,0 +1,28 @@
+use ic_cdk::update;
+
+use crate::{
+    http_error::HttpError,
+    rate_limit::{self, RateLimitedCall},
+    recipe::{self, processor_tests, RecipeId, RecipePublishState, RecipeTestResult},
+    user::auth_guard,
+};
+
+/// Runs the processor tests of the recipe. Each test runs in a call of its own
+/// so a processor stuck in a loop fails only its test. The canister pays for
+/// the processor runs, test runs are rate limited per user.
+#[update]
+async fn recipe_run_tests(recipe_id: RecipeId) -> Result<Vec<RecipeTestResult>, HttpError> {
+    let address = auth_guard()?;
+    rate_limit::check(&address, RateLimitedCall::RecipeRunTests)?;
+
+    let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+
+    // Only the creator and maintainers run the tests of a draft
+    if recipe.publish_state != RecipePublishState::Published && !recipe.can_edit(&address) {
+        return Err(HttpError::unauthorized(
+            "You are not the author or a maintainer of this recipe.",
+        ));
+    }
+
+    Ok(processor_tests::run_tests(&recipe).await)
+}
@@ -0,0 +1,24 @@
+use ic_cdk::query;
+
+use crate::{
+    http_error::HttpError,
+    recipe::{
+        search::{self, MAX_SEARCH_QUERY_LENGTH},
+        RecipeListResponse,
+    },
+};
+
+#[query]
+fn recipe_search(query: String, cursor: Option<String>) -> Result<RecipeListResponse, HttpError> {
+    if query.trim().is_empty() {
+        return Err(HttpError::bad_request("Search query is empty."));
+    }
+    if query.len() > MAX_SEARCH_QUERY_LENGTH {
+        return Err(HttpError::bad_request(format!(
+            "Search query can be at most {} characters long.",
+            MAX_SEARCH_QUERY_LENGTH
+        )));
+    }
+
+    Ok(search::search(&query, cursor.as_ref()))
+}
@@ -0,0 +1,38 @@
+use ic_cdk::update;
+
+use crate::{
+    eth_address::EthAddress,
+    http_error::HttpError,
+    recipe::{self, Recipe, RecipeId, MAX_RECIPE_MAINTAINERS},
+    user::auth_guard,
+};
+
+#[update]
+fn recipe_set_maintainers(
+    recipe_id: RecipeId,
+    maintainers: Vec<String>,
+) -> Result<Recipe, HttpError> {
+    let address = auth_guard()?;
+    let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+
+    if !recipe.is_creator(&address) {
+        return Err(HttpError::unauthorized(
+            "You are not the author of this recipe.",
+        ));
+    }
+
+    if maintainers.len() > MAX_RECIPE_MAINTAINERS {
+        return Err(HttpError::bad_request(format!(
+            "A recipe can have at most {} maintainers.",
+            MAX_RECIPE_MAINTAINERS
+        )));
+    }
+
+    let maintainers = maintainers
+        .iter()
+        .map(|maintainer| EthAddress::new(maintainer))
+        .collect::<Result<Vec<EthAddress>, _>>()
+        .map_err(HttpError::bad_request)?;
+
+    recipe::set_maintainers(&recipe_id, &maintainers).map_err(HttpError::bad_request)
+}
@@ -0,0 +1,34 @@
+use ic_cdk::update;
+
+use crate::{
+    eth_address::EthAddress,
+    http_error::HttpError,
+    rate_limit::{self, RateLimitedCall},
+    recipe::{self, simulate::simulate, Recipe, RecipeSimulateInput, RecipeSimulateResult},
+    user::auth_guard,
+};
+
+/// Dry-runs a recipe for an address. The queries are paid for by the canister,
+/// simulations are rate limited per user.
+#[update]
+async fn recipe_simulate(
+    recipe: RecipeSimulateInput,
+    address: String,
+) -> Result<RecipeSimulateResult, HttpError> {
+    let caller = auth_guard()?;
+    rate_limit::check(&caller, RateLimitedCall::RecipeSimulate)?;
+
+    let address = EthAddress::new(&address).map_err(HttpError::bad_request)?;
+
+    let recipe = match recipe {
+        RecipeSimulateInput::RecipeId(recipe_id) => {
+            recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?
+        }
+        // Unsaved details are simulated as a draft by the caller
+        RecipeSimulateInput::Details(details) => {
+            Recipe::new(&details, &caller).map_err(HttpError::bad_request)?
+        }
+    };
+
+    Ok(simulate(&recipe, &address).await)
+}
@@ -0,0 +1,12 @@
+use ic_cdk::query;
+
+use crate::{
+    http_error::HttpError,
+    recipe::{self, stats, RecipeId, RecipeStats},
+};
+
+#[query]
+fn recipe_stats(recipe_id: RecipeId) -> Result<RecipeStats, HttpError> {
+    recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+    Ok(stats::get(&recipe_id))
+}
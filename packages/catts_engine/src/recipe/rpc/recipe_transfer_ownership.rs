@@ -0,0 +1,33 @@
+use ic_cdk::update;
+
+use crate::{
+    eth_address::EthAddress,
+    http_error::HttpError,
+    recipe::{self, Recipe, RecipeId},
+    user::auth_guard,
+};
+
+/// Starts an ownership transfer. The transfer is completed when the new owner
+/// calls `recipe_accept_ownership`.
+#[update]
+fn recipe_transfer_ownership(
+    recipe_id: RecipeId,
+    new_creator: String,
+) -> Result<Recipe, HttpError> {
+    let address = auth_guard()?;
+    let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+
+    if !recipe.is_creator(&address) {
+        return Err(HttpError::unauthorized(
+            "You are not the author of this recipe.",
+        ));
+    }
+
+    let new_creator = EthAddress::new(&new_creator).map_err(HttpError::bad_request)?;
+
+    if recipe.is_creator(&new_creator) {
+        return Err(HttpError::bad_request("You already own this recipe."));
+    }
+
+    recipe::transfer_ownership(&recipe_id, &new_creator).map_err(HttpError::bad_request)
+}
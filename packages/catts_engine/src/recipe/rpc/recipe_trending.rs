@@ -0,0 +1,31 @@
+use ic_cdk::query;
+
+use crate::{
+    http_error::HttpError,
+    recipe::{
+        stats::{
+            self, TRENDING_DEFAULT_LIMIT, TRENDING_DEFAULT_WINDOW_DAYS, TRENDING_MAX_LIMIT,
+            TRENDING_MAX_WINDOW_DAYS,
+        },
+        RecipeTrendingItem,
+    },
+};
+
+#[query]
+fn recipe_trending(
+    window_days: Option<u32>,
+    limit: Option<u32>,
+) -> Result<Vec<RecipeTrendingItem>, HttpError> {
+    let window_days = window_days.unwrap_or(TRENDING_DEFAULT_WINDOW_DAYS);
+    if window_days == 0 || window_days > TRENDING_MAX_WINDOW_DAYS {
+        return Err(HttpError::bad_request(format!(
+            "Window must be between 1 and {} days.",
+            TRENDING_MAX_WINDOW_DAYS
+        )));
+    }
+    let limit = limit
+        .unwrap_or(TRENDING_DEFAULT_LIMIT)
+        .clamp(1, TRENDING_MAX_LIMIT);
+
+    Ok(stats::trending(window_days, limit as usize))
+}
@@ -0,0 +1,35 @@
+use ic_cdk::update;
+
+use crate::{
+    http_error::HttpError,
+    recipe::{self, Recipe, RecipeDetailsInput, RecipeId},
+    user::auth_guard,
+};
+
+#[update]
+fn recipe_update(
+    recipe_id: RecipeId,
+    details: RecipeDetailsInput,
+    readme: String,
+) -> Result<Recipe, HttpError> {
+    let address = auth_guard()?;
+    let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+
+    if !recipe.can_edit(&address) {
+        return Err(HttpError::unauthorized(
+            "You are not the author or a maintainer of this recipe.",
+        ));
+    }
+
+    if details.name != recipe.name {
+        return Err(HttpError::bad_request("Recipe name can't be changed."));
+    }
+
+    let recipe = recipe
+        .with_details(&details)
+        .map_err(HttpError::bad_request)?;
+    let recipe = recipe::save(recipe).map_err(HttpError::conflict)?;
+    recipe::write_readme(&recipe.name, &readme)
+        .map_err(|_| HttpError::internal_server_error("Couldn't save README file."))?;
+    Ok(recipe)
+}
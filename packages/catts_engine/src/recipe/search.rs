@@ -0,0 +1,234 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use candid::{CandidType, Decode, Encode};
+use ethers_core::utils::hex;
+use ic_stable_structures::{storable::Bound as StorableBound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::{RECIPES, RECIPE_SEARCH_INDEX, RECIPE_SEARCH_TERMS};
+
+use super::{read_readme, Recipe, RecipeId, RecipeListResponse};
+
+// Relevance weight of a term depending on where in the recipe it was found
+const WEIGHT_NAME: u32 = 8;
+const WEIGHT_KEYWORD: u32 = 4;
+const WEIGHT_DESCRIPTION: u32 = 2;
+const WEIGHT_README: u32 = 1;
+
+const MIN_TERM_LENGTH: usize = 2;
+const MAX_TERM_LENGTH: usize = 32;
+
+// Bounds the size of the index for recipes with long READMEs
+const MAX_TERMS_PER_RECIPE: usize = 500;
+
+pub const MAX_SEARCH_QUERY_LENGTH: usize = 200;
+const MAX_SEARCH_QUERY_TERMS: usize = 10;
+const SEARCH_PAGE_SIZE: usize = 20;
+
+/// The terms a recipe is indexed under, kept to be able to remove the recipe
+/// from the inverted index.
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, Default)]
+pub struct RecipeSearchTerms {
+    pub terms: Vec<String>,
+}
+
+impl Storable for RecipeSearchTerms {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: StorableBound = StorableBound::Unbounded;
+}
+
+// Terms are alphanumeric, the colon separates the term from the recipe id.
+fn index_key(term: &str, recipe_id: &RecipeId) -> String {
+    format!("{}:{}", term, hex::encode(recipe_id))
+}
+
+fn parse_index_key(key: &str) -> Option<(&str, RecipeId)> {
+    let (term, recipe_id) = key.rsplit_once(':')?;
+    let recipe_id: RecipeId = hex::decode(recipe_id).ok()?.try_into().ok()?;
+    Some((term, recipe_id))
+}
+
+/// Splits text into lowercase alphanumeric terms.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() >= MIN_TERM_LENGTH)
+        .map(|term| term.to_lowercase().chars().take(MAX_TERM_LENGTH).collect())
+        .collect()
+}
+
+fn recipe_terms(recipe: &Recipe, readme: Option<&str>) -> HashMap<String, u32> {
+    let mut terms: HashMap<String, u32> = HashMap::new();
+    let mut add_terms = |text: &str, weight: u32| {
+        for term in tokenize(text) {
+            if terms.len() >= MAX_TERMS_PER_RECIPE && !terms.contains_key(&term) {
+                continue;
+            }
+            let score = terms.entry(term).or_insert(0);
+            // A term scores once per field it appears in, the weights are
+            // powers of two so each field sets its own bit.
+            if *score & weight == 0 {
+                *score += weight;
+            }
+        }
+    };
+
+    add_terms(&recipe.name, WEIGHT_NAME);
+    if let Some(ref keywords) = recipe.keywords {
+        for keyword in keywords {
+            add_terms(keyword, WEIGHT_KEYWORD);
+        }
+    }
+    if let Some(ref description) = recipe.description {
+        add_terms(description, WEIGHT_DESCRIPTION);
+    }
+    if let Some(readme) = readme {
+        add_terms(readme, WEIGHT_README);
+    }
+
+    terms
+}
+
+/// Adds the recipe to the search index, replacing any previous entries. The
+/// README is read from the file system if it exists.
+pub fn index_recipe(recipe: &Recipe) {
+    remove_recipe(&recipe.id);
+
+    let readme = read_readme(&recipe.name).ok();
+    let terms = recipe_terms(recipe, readme.as_deref());
+
+    RECIPE_SEARCH_INDEX.with_borrow_mut(|index| {
+        for (term, score) in terms.iter() {
+            index.insert(index_key(term, &recipe.id), *score);
+        }
+    });
+    RECIPE_SEARCH_TERMS.with_borrow_mut(|search_terms| {
+        search_terms.insert(
+            recipe.id,
+            RecipeSearchTerms {
+                terms: terms.into_keys().collect(),
+            },
+        );
+    });
+}
+
+/// Removes the recipe from the search index.
+pub fn remove_recipe(recipe_id: &RecipeId) {
+    let Some(search_terms) =
+        RECIPE_SEARCH_TERMS.with_borrow_mut(|search_terms| search_terms.remove(recipe_id))
+    else {
+        return;
+    };
+    RECIPE_SEARCH_INDEX.with_borrow_mut(|index| {
+        for term in search_terms.terms {
+            index.remove(&index_key(&term, recipe_id));
+        }
+    });
+}
+
+// Returns the score of each recipe with a term starting with the query term.
+// Exact term matches score double.
+fn match_term(query_term: &str) -> HashMap<RecipeId, u32> {
+    let mut matches: HashMap<RecipeId, u32> = HashMap::new();
+    RECIPE_SEARCH_INDEX.with_borrow(|index| {
+        for (key, score) in index
+            .range(query_term.to_string()..)
+            .take_while(|(key, _)| key.starts_with(query_term))
+        {
+            if let Some((term, recipe_id)) = parse_index_key(&key) {
+                let score = if term == query_term { score * 2 } else { score };
+                let best = matches.entry(recipe_id).or_insert(0);
+                *best = (*best).max(score);
+            }
+        }
+    });
+    matches
+}
+
+// Results are sorted by descending score, the key inverts the score to sort
+// ascending.
+fn result_key(score: u32, recipe_id: &RecipeId) -> String {
+    format!("{:010}{}", u32::MAX - score, hex::encode(recipe_id))
+}
+
+/// Searches recipes matching all terms of the query. Query terms match indexed
+/// terms by prefix. Results are ordered by relevance and paginated using the
+/// cursor returned with the previous page.
+pub fn search(query: &str, cursor: Option<&String>) -> RecipeListResponse {
+    let mut query_terms = tokenize(query);
+    query_terms.sort();
+    query_terms.dedup();
+    query_terms.truncate(MAX_SEARCH_QUERY_TERMS);
+
+    let mut scores: Option<HashMap<RecipeId, u32>> = None;
+    for query_term in query_terms {
+        let matches = match_term(&query_term);
+        scores = Some(match scores {
+            None => matches,
+            Some(scores) => scores
+                .into_iter()
+                .filter_map(|(recipe_id, score)| {
+                    matches.get(&recipe_id).map(|s| (recipe_id, score + s))
+                })
+                .collect(),
+        });
+    }
+
+    // Results are ordered from the index data, only the recipes of the page
+    // are read from stable memory
+    let mut results: Vec<(String, RecipeId)> = scores
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(recipe_id, score)| (result_key(score, &recipe_id), recipe_id))
+        .collect();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let start = match cursor {
+        Some(cursor) => results.partition_point(|(key, _)| key <= cursor),
+        None => 0,
+    };
+    let end = (start + SEARCH_PAGE_SIZE).min(results.len());
+    let next_cursor = if end < results.len() {
+        Some(results[end - 1].0.clone())
+    } else {
+        None
+    };
+
+    RecipeListResponse {
+        recipes: RECIPES.with_borrow(|recipes| {
+            results[start..end]
+                .iter()
+                .filter_map(|(_, recipe_id)| recipes.get(recipe_id))
+                .collect()
+        }),
+        next_cursor,
+    }
+}
+
+/// Recreates the search index from the recipes in stable memory.
+pub fn rebuild_index() {
+    RECIPE_SEARCH_INDEX.with_borrow_mut(|index| {
+        let keys: Vec<String> = index.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            index.remove(&key);
+        }
+    });
+    RECIPE_SEARCH_TERMS.with_borrow_mut(|search_terms| {
+        let recipe_ids: Vec<RecipeId> = search_terms.iter().map(|(id, _)| id).collect();
+        for recipe_id in recipe_ids {
+            search_terms.remove(&recipe_id);
+        }
+    });
+
+    let recipes: Vec<Recipe> =
+        RECIPES.with_borrow(|recipes| recipes.iter().map(|(_, recipe)| recipe).collect());
+    for recipe in recipes {
+        index_recipe(&recipe);
+    }
+}
@@ -0,0 +1,101 @@
+use ethers_core::utils::hex;
+
+use crate::{
+    eas::{
+        create_attest_request, decode_abi_data, encode_abi_data, expiration_time,
+        parse_processor_output, run_query, schema,
+    },
+    eth_address::EthAddress,
+    processor,
+};
+
+use super::{Recipe, RecipeSimulateResult};
+
+/// Runs the queries and the processor of the recipe for the address and
+/// encodes the result the same way a run would, without creating an
+/// attestation. Stops at the first error.
+pub async fn simulate(recipe: &Recipe, address: &EthAddress) -> RecipeSimulateResult {
+    let mut result = RecipeSimulateResult::default();
+
+    if recipe.queries.is_empty() {
+        result.error = Some("Recipe contains no queries".to_string());
+        return result;
+    }
+
+    for recipe_query in recipe.queries.iter() {
+        match run_query(address, recipe_query).await {
+            Ok(query_result) => result.query_results.push(query_result),
+            Err(err) => {
+                result.error = Some(format!("Error running EAS query: {}", err));
+                return result;
+            }
+        }
+    }
+    let aggregated_response = format!("[{}]", result.query_results.join(","));
+
+    let processor_output = match processor::execute_isolated(
+        &recipe.processor,
+        &aggregated_response,
+        &recipe.processor_limits(),
+        recipe.stdlib_version,
+    )
+    .await
+    {
+        Ok(processor_output) => {
+            result.processor_logs = processor_output.logs;
+            processor_output.output
+        }
+        Err(failure) => {
+            result.error = Some(failure.error.to_string());
+            result.processor_logs = failure.logs;
+            return result;
+        }
+    };
+    result.processor_output = Some(processor_output.clone());
+
+    let attestation_output = match parse_processor_output(&processor_output, &aggregated_response) {
+        Ok(attestation_output) => attestation_output,
+        Err(err) => {
+            result.error = Some(err.to_string());
+            return result;
+        }
+    };
+
+    if let Err(err) = schema::validate_output(&recipe.schema, &attestation_output.data) {
+        result.error = Some(format!(
+            "Processor output does not match the schema: {}",
+            err
+        ));
+        return result;
+    }
+
+    let attestation_data = match encode_abi_data(&attestation_output.data, recipe.integer_strings())
+    {
+        Ok(attestation_data) => attestation_data,
+        Err(err) => {
+            result.error = Some(format!("Error encoding processor output: {}", err));
+            return result;
+        }
+    };
+    result.attestation_data = Some(format!("0x{}", hex::encode(&attestation_data)));
+
+    match expiration_time(recipe, &attestation_output) {
+        Ok(expiration_time) => result.expiration_time = Some(expiration_time),
+        Err(err) => {
+            result.error = Some(err.to_string());
+            return result;
+        }
+    }
+
+    if let Err(err) = create_attest_request(recipe, &attestation_output, address, None) {
+        result.error = Some(format!("Error creating attest request: {}", err));
+        return result;
+    }
+
+    match decode_abi_data(&attestation_output.data, &attestation_data) {
+        Ok(schema_values) => result.schema_values = Some(schema_values),
+        Err(err) => result.error = Some(format!("Error decoding attestation data: {}", err)),
+    }
+
+    result
+}
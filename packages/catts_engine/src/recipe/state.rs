@@ -1,11 +1,25 @@
 use std::{fs, path::Path};
 
+use ic_stable_structures::Storable;
+
 use crate::{
+    chain_config,
     change_log::{self, ChangeLogTypeName},
+    eth_address::EthAddress,
     RECIPES, RECIPE_NAME_INDEX,
 };
 
-use super::{Recipe, RecipeError, RecipeId, RecipePublishState};
+use super::{
+    index, search, stats, Recipe, RecipeError, RecipeId, RecipeListOptions, RecipeListResponse,
+    RecipeListSortBy, RecipePublishState, RecipeSchemaRegistration, RecipeTestResult, SortOrder,
+};
+
+const RECIPE_LIST_DEFAULT_LIMIT: u32 = 20;
+const RECIPE_LIST_MAX_LIMIT: u32 = 100;
+
+// Upper bound on the number of recipes visited by one list call, keeps
+// filtered listings within the query instruction limit.
+const RECIPE_LIST_MAX_SCAN: usize = 1_000;
 
 pub fn get_by_id(recipe_id: &RecipeId) -> Result<Recipe, RecipeError> {
     RECIPES
@@ -20,11 +34,117 @@ pub fn get_by_name(name: &String) -> Result<Recipe, RecipeError> {
     get_by_id(&recipe_id)
 }
 
-pub fn list() -> Vec<Recipe> {
-    RECIPES.with_borrow(|recipes| recipes.iter().map(|(_, recipe)| recipe.clone()).collect())
+/// Published recipes whose schema registration was never checked, recipes
+/// published before registrations were checked.
+pub fn list_published_unchecked_schemas() -> Vec<RecipeId> {
+    RECIPES.with_borrow(|recipes| {
+        recipes
+            .iter()
+            .filter(|(_, recipe)| {
+                recipe.publish_state == RecipePublishState::Published
+                    && recipe.schema_registrations.is_none()
+            })
+            .map(|(recipe_id, _)| recipe_id)
+            .collect()
+    })
+}
+
+pub fn list(options: &RecipeListOptions) -> RecipeListResponse {
+    let limit = options
+        .limit
+        .unwrap_or(RECIPE_LIST_DEFAULT_LIMIT)
+        .clamp(1, RECIPE_LIST_MAX_LIMIT) as usize;
+    let sort_by = options.sort_by.unwrap_or(RecipeListSortBy::Created);
+    let sort_order = options.sort_order.unwrap_or(match sort_by {
+        RecipeListSortBy::Name => SortOrder::Asc,
+        _ => SortOrder::Desc,
+    });
+    let filters = options.filters.clone().unwrap_or_default();
+
+    // Recipes can only be run on configured chains
+    if let Some(chain_id) = filters.chain_id {
+        if chain_config::get(chain_id).is_err() {
+            return RecipeListResponse {
+                recipes: vec![],
+                next_cursor: None,
+            };
+        }
+    }
+
+    let mut recipes = Vec::new();
+    let mut next_cursor = None;
+
+    match index::filter_candidates(&filters, RECIPE_LIST_MAX_SCAN) {
+        // The creator and keyword indexes narrow the result down to a bounded
+        // set of recipes that can be sorted in memory.
+        Some(recipe_ids) => {
+            let mut candidates: Vec<(String, Recipe)> = recipe_ids
+                .iter()
+                .filter_map(|recipe_id| get_by_id(recipe_id).ok())
+                .filter(|recipe| filters.matches(recipe))
+                .map(|recipe| (index::sort_key(&recipe, sort_by), recipe))
+                .collect();
+            candidates.sort_by(|a, b| a.0.cmp(&b.0));
+            if sort_order == SortOrder::Desc {
+                candidates.reverse();
+            }
+
+            for (key, recipe) in candidates {
+                if let Some(ref cursor) = options.cursor {
+                    let after_cursor = match sort_order {
+                        SortOrder::Asc => key > *cursor,
+                        SortOrder::Desc => key < *cursor,
+                    };
+                    if !after_cursor {
+                        continue;
+                    }
+                }
+                recipes.push(recipe);
+                if recipes.len() == limit {
+                    next_cursor = Some(key);
+                    break;
+                }
+            }
+        }
+        // Without a narrowing filter, walk the sort index from the cursor
+        None => {
+            let entries = index::scan(
+                sort_by,
+                sort_order,
+                options.cursor.as_ref(),
+                RECIPE_LIST_MAX_SCAN,
+            );
+            let scanned_all = entries.len() < RECIPE_LIST_MAX_SCAN;
+            let mut last_key = None;
+
+            for (key, recipe_id) in entries {
+                last_key = Some(key.clone());
+                if let Ok(recipe) = get_by_id(&recipe_id) {
+                    if filters.matches(&recipe) {
+                        recipes.push(recipe);
+                        if recipes.len() == limit {
+                            next_cursor = Some(key);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            // Scan limit reached before the page was filled, let the caller
+            // continue from the last visited recipe.
+            if next_cursor.is_none() && !scanned_all {
+                next_cursor = last_key;
+            }
+        }
+    }
+
+    RecipeListResponse {
+        recipes,
+        next_cursor,
+    }
 }
 
-pub fn save(recipe: Recipe) -> Result<Recipe, RecipeError> {
+pub fn save(mut recipe: Recipe) -> Result<Recipe, RecipeError> {
     let saved_recipe_result = get_by_id(&recipe.id);
 
     let maybe_saved_recipe = match saved_recipe_result {
@@ -43,10 +163,24 @@ pub fn save(recipe: Recipe) -> Result<Recipe, RecipeError> {
         Err(_) => Err(RecipeError::InternalError),
     }?;
 
+    // Ownership is managed through the dedicated functions below, saving
+    // recipe details never changes it.
+    if let Some(saved_recipe) = maybe_saved_recipe {
+        recipe.creator = saved_recipe.creator.clone();
+        recipe.maintainers = saved_recipe.maintainers.clone();
+        recipe.pending_creator = saved_recipe.pending_creator.clone();
+    }
+
     RECIPES.with_borrow_mut(|recipes| {
         recipes.insert(recipe.id, recipe.clone());
     });
 
+    if let Some(saved_recipe) = maybe_saved_recipe {
+        index::remove(saved_recipe);
+    }
+    index::add(&recipe);
+    search::index_recipe(&recipe);
+
     match maybe_saved_recipe {
         Some(saved_recipe) => {
             change_log::update(ChangeLogTypeName::Recipe, recipe.id, saved_recipe, &recipe)
@@ -64,12 +198,142 @@ pub fn save(recipe: Recipe) -> Result<Recipe, RecipeError> {
     Ok(recipe)
 }
 
-pub fn publish(recipe_id: &RecipeId) -> Result<Recipe, RecipeError> {
-    let mut recipe = get_by_id(recipe_id)?;
+/// Publishes the recipe the processor tests ran against. The tests run in
+/// separate calls, the recipe must not have been saved in the meantime.
+pub fn publish(
+    tested_recipe: &Recipe,
+    test_results: &[RecipeTestResult],
+) -> Result<Recipe, RecipeError> {
+    let mut recipe = get_by_id(&tested_recipe.id)?;
+
+    // Schema registrations are updated by the verify task while tests run
+    let without_registrations = |recipe: &Recipe| Recipe {
+        schema_registrations: None,
+        ..recipe.clone()
+    };
+    if without_registrations(&recipe).to_bytes() != without_registrations(tested_recipe).to_bytes()
+    {
+        return Err(RecipeError::ChangedDuringTests);
+    }
+
+    let failed_tests: Vec<String> = test_results
+        .iter()
+        .filter(|result| !result.passed)
+        .map(|result| result.name.clone())
+        .collect();
+    if !failed_tests.is_empty() {
+        return Err(RecipeError::TestsFailed(failed_tests.join(", ")));
+    }
+
+    // Chains the recipe declares must have been checked to have the schema
+    if let Some(ref chain_ids) = recipe.chain_ids {
+        let unregistered: Vec<String> = chain_ids
+            .iter()
+            .filter(|chain_id| !recipe.is_schema_registered(**chain_id))
+            .map(|chain_id| chain_id.to_string())
+            .collect();
+        if !unregistered.is_empty() {
+            return Err(RecipeError::SchemaNotRegistered(unregistered.join(", ")));
+        }
+    }
+
     recipe.publish_state = RecipePublishState::Published;
     save(recipe)
 }
 
+/// Updates a recipe regardless of publish state. Used for ownership changes
+/// that are allowed also after a recipe has been published.
+fn update(recipe: Recipe) -> Result<Recipe, RecipeError> {
+    let saved_recipe = get_by_id(&recipe.id)?;
+
+    RECIPES.with_borrow_mut(|recipes| {
+        recipes.insert(recipe.id, recipe.clone());
+    });
+
+    index::remove(&saved_recipe);
+    index::add(&recipe);
+
+    change_log::update(ChangeLogTypeName::Recipe, recipe.id, &saved_recipe, &recipe).unwrap();
+
+    Ok(recipe)
+}
+
+/// Saves the schema registration of a chain, replacing a previous one.
+pub fn set_schema_registration(
+    recipe_id: &RecipeId,
+    registration: RecipeSchemaRegistration,
+) -> Result<Recipe, RecipeError> {
+    let mut recipe = get_by_id(recipe_id)?;
+    let mut registrations = recipe.schema_registrations.take().unwrap_or_default();
+    registrations.retain(|r| r.chain_id != registration.chain_id);
+    registrations.push(registration);
+    registrations.sort_by_key(|r| r.chain_id);
+    recipe.schema_registrations = Some(registrations);
+    update(recipe)
+}
+
+pub fn transfer_ownership(
+    recipe_id: &RecipeId,
+    new_creator: &EthAddress,
+) -> Result<Recipe, RecipeError> {
+    let mut recipe = get_by_id(recipe_id)?;
+    recipe.pending_creator = Some(new_creator.to_string());
+    update(recipe)
+}
+
+pub fn cancel_ownership_transfer(recipe_id: &RecipeId) -> Result<Recipe, RecipeError> {
+    let mut recipe = get_by_id(recipe_id)?;
+    if recipe.pending_creator.is_none() {
+        return Err(RecipeError::NoPendingTransfer);
+    }
+    recipe.pending_creator = None;
+    update(recipe)
+}
+
+pub fn accept_ownership(
+    recipe_id: &RecipeId,
+    new_creator: &EthAddress,
+) -> Result<Recipe, RecipeError> {
+    let mut recipe = get_by_id(recipe_id)?;
+
+    if recipe.pending_creator.as_deref() != Some(new_creator.as_str()) {
+        return Err(RecipeError::NoPendingTransfer);
+    }
+
+    recipe.creator = new_creator.to_string();
+    recipe.pending_creator = None;
+
+    // The new creator no longer needs to be listed as a maintainer
+    if let Some(ref mut maintainers) = recipe.maintainers {
+        maintainers.retain(|maintainer| maintainer != new_creator.as_str());
+    }
+
+    update(recipe)
+}
+
+pub fn set_maintainers(
+    recipe_id: &RecipeId,
+    maintainers: &[EthAddress],
+) -> Result<Recipe, RecipeError> {
+    let mut recipe = get_by_id(recipe_id)?;
+
+    let mut addresses: Vec<String> = Vec::new();
+    for maintainer in maintainers {
+        let address = maintainer.to_string();
+        if address != recipe.creator && !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+
+    recipe.maintainers = if addresses.is_empty() {
+        None
+    } else {
+        Some(addresses)
+    };
+
+    update(recipe)
+}
+
 pub fn delete(recipe_id: &RecipeId) -> Result<Recipe, RecipeError> {
     let recipe = get_by_id(recipe_id)?;
     RECIPES.with_borrow_mut(|recipes| {
@@ -78,6 +342,9 @@ pub fn delete(recipe_id: &RecipeId) -> Result<Recipe, RecipeError> {
     RECIPE_NAME_INDEX.with_borrow_mut(|index| {
         index.remove(&recipe.name);
     });
+    index::remove(&recipe);
+    stats::remove(&recipe.id);
+    search::remove_recipe(&recipe.id);
     change_log::delete(ChangeLogTypeName::Recipe, recipe.id).unwrap();
     Ok(recipe)
 }
@@ -86,6 +353,10 @@ pub fn write_readme(recipe_name: &str, contents: &str) -> Result<(), RecipeError
     fs::create_dir_all("recipes").map_err(|_| RecipeError::InternalError)?;
     fs::write(format!("recipes/{}/README.md", recipe_name), contents)
         .map_err(|_| RecipeError::InternalError)?;
+    // The README is part of the search index
+    if let Ok(recipe) = get_by_name(&recipe_name.to_string()) {
+        search::index_recipe(&recipe);
+    }
     Ok(())
 }
 
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use ethers_core::utils::hex;
+
+use crate::{
+    run::{Run, RunStatus},
+    time::time,
+    RECIPE_DAILY_RUNS, RECIPE_STATS, RECIPE_USERS, RUNS,
+};
+
+use super::{get_by_id, index, RecipeId, RecipeStats, RecipeTrendingItem};
+
+const SECONDS_PER_DAY: u32 = 86_400;
+
+/// Daily run counts older than this are pruned, trending windows can't be
+/// longer.
+pub const TRENDING_MAX_WINDOW_DAYS: u32 = 30;
+pub const TRENDING_DEFAULT_WINDOW_DAYS: u32 = 7;
+pub const TRENDING_DEFAULT_LIMIT: u32 = 10;
+pub const TRENDING_MAX_LIMIT: u32 = 100;
+
+fn day(timestamp: u32) -> u32 {
+    timestamp / SECONDS_PER_DAY
+}
+
+// Daily run counts are keyed by day first to be able to sum a time window
+// with a range scan.
+fn daily_runs_key(day: u32, recipe_id: &RecipeId) -> String {
+    format!("{:06}{}", day, hex::encode(recipe_id))
+}
+
+fn user_key(recipe_id: &RecipeId, user: &str) -> String {
+    format!("{}{}", hex::encode(recipe_id), user)
+}
+
+pub fn get(recipe_id: &RecipeId) -> RecipeStats {
+    RECIPE_STATS.with_borrow(|stats| stats.get(recipe_id).unwrap_or_default())
+}
+
+fn save(recipe_id: &RecipeId, stats: RecipeStats) {
+    RECIPE_STATS.with_borrow_mut(|recipe_stats| {
+        recipe_stats.insert(*recipe_id, stats);
+    });
+}
+
+fn is_paid(run: &Run) -> bool {
+    run.status() >= RunStatus::PaymentVerified
+}
+
+fn is_attested(run: &Run) -> bool {
+    run.status() >= RunStatus::AttestationUidConfirmed
+}
+
+fn is_failed(run: &Run) -> bool {
+    run.error.is_some()
+}
+
+// Counts the state transitions between the old and the new version of a run.
+// A new run has no old version.
+fn apply_transition(stats: &mut RecipeStats, old_run: Option<&Run>, run: &Run) {
+    if is_paid(run) && !old_run.is_some_and(is_paid) {
+        stats.runs_paid += 1;
+    }
+    if is_attested(run) && !old_run.is_some_and(is_attested) {
+        stats.runs_attested += 1;
+    }
+    if is_failed(run) && !old_run.is_some_and(is_failed) {
+        stats.runs_failed += 1;
+    }
+    // A retried run is no longer failed, rebuilt stats don't count it either
+    if !is_failed(run) && old_run.is_some_and(is_failed) {
+        stats.runs_failed = stats.runs_failed.saturating_sub(1);
+    }
+}
+
+fn count_run(stats: &mut RecipeStats, run: &Run) {
+    stats.runs_created += 1;
+    stats.last_run = stats.last_run.max(Some(run.created));
+
+    let is_new_user = RECIPE_USERS.with_borrow_mut(|users| {
+        users
+            .insert(user_key(&run.recipe_id, &run.creator), run.created)
+            .is_none()
+    });
+    if is_new_user {
+        stats.unique_users += 1;
+    }
+
+    RECIPE_DAILY_RUNS.with_borrow_mut(|daily_runs| {
+        let key = daily_runs_key(day(run.created), &run.recipe_id);
+        let runs = daily_runs.get(&key).unwrap_or(0);
+        daily_runs.insert(key, runs + 1);
+    });
+}
+
+/// Updates the stats of the recipe when a run is created.
+pub fn run_created(run: &Run) {
+    let mut stats = get(&run.recipe_id);
+    let run_count = stats.runs_created;
+    count_run(&mut stats, run);
+    apply_transition(&mut stats, None, run);
+    save(&run.recipe_id, stats);
+
+    index::update_run_count(&run.recipe_id, run_count, run_count + 1);
+    prune_daily_runs();
+}
+
+/// Updates the stats of the recipe when a run changes state.
+pub fn run_updated(old_run: &Run, run: &Run) {
+    let mut stats = get(&run.recipe_id);
+    apply_transition(&mut stats, Some(old_run), run);
+    save(&run.recipe_id, stats);
+}
+
+/// Removes the stats of a deleted recipe. Daily run counts are left to be
+/// pruned.
+pub fn remove(recipe_id: &RecipeId) {
+    RECIPE_STATS.with_borrow_mut(|stats| {
+        stats.remove(recipe_id);
+    });
+    RECIPE_USERS.with_borrow_mut(|users| {
+        let prefix = hex::encode(recipe_id);
+        let keys: Vec<String> = users
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, _)| key)
+            .collect();
+        for key in keys {
+            users.remove(&key);
+        }
+    });
+}
+
+fn prune_daily_runs() {
+    let first_day = day(time()).saturating_sub(TRENDING_MAX_WINDOW_DAYS);
+    RECIPE_DAILY_RUNS.with_borrow_mut(|daily_runs| {
+        let keys: Vec<String> = daily_runs
+            .range(..format!("{:06}", first_day))
+            .map(|(key, _)| key)
+            .collect();
+        for key in keys {
+            daily_runs.remove(&key);
+        }
+    });
+}
+
+/// Returns the recipes with the most runs created during the last
+/// `window_days` days, most runs first.
+pub fn trending(window_days: u32, limit: usize) -> Vec<RecipeTrendingItem> {
+    let first_day = day(time()).saturating_sub(window_days.saturating_sub(1));
+
+    let mut runs_per_recipe: HashMap<RecipeId, u32> = HashMap::new();
+    RECIPE_DAILY_RUNS.with_borrow(|daily_runs| {
+        for (key, runs) in daily_runs.range(format!("{:06}", first_day)..) {
+            let recipe_id: Option<RecipeId> = hex::decode(&key[6..])
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok());
+            if let Some(recipe_id) = recipe_id {
+                *runs_per_recipe.entry(recipe_id).or_insert(0) += runs;
+            }
+        }
+    });
+
+    let mut items: Vec<RecipeTrendingItem> = runs_per_recipe
+        .into_iter()
+        .filter_map(|(recipe_id, runs)| {
+            get_by_id(&recipe_id)
+                .ok()
+                .map(|recipe| RecipeTrendingItem { recipe, runs })
+        })
+        .collect();
+    items.sort_by(|a, b| {
+        b.runs
+            .cmp(&a.runs)
+            .then_with(|| a.recipe.name.cmp(&b.recipe.name))
+    });
+    items.truncate(limit);
+    items
+}
+
+/// Recreates the stats of all recipes from the runs in stable memory.
+pub fn rebuild() {
+    RECIPE_STATS.with_borrow_mut(|stats| {
+        let recipe_ids: Vec<RecipeId> = stats.iter().map(|(recipe_id, _)| recipe_id).collect();
+        for recipe_id in recipe_ids {
+            stats.remove(&recipe_id);
+        }
+    });
+    RECIPE_USERS.with_borrow_mut(|users| {
+        let keys: Vec<String> = users.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            users.remove(&key);
+        }
+    });
+    RECIPE_DAILY_RUNS.with_borrow_mut(|daily_runs| {
+        let keys: Vec<String> = daily_runs.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            daily_runs.remove(&key);
+        }
+    });
+
+    RUNS.with_borrow(|runs| {
+        for (_, run) in runs.iter() {
+            let mut stats = get(&run.recipe_id);
+            count_run(&mut stats, &run);
+            apply_transition(&mut stats, None, &run);
+            save(&run.recipe_id, stats);
+        }
+    });
+    prune_daily_runs();
+}
@@ -0,0 +1 @@
+pub mod verify_schema;
@@ -0,0 +1,138 @@
+use crate::{
+    chain_config::{self, ChainConfig},
+    eas::{eip712::to_hex, get_schema_uid, registry::is_schema_registered},
+    logger::{self},
+    recipe::{self, Recipe, RecipeId, RecipeSchemaRegistration},
+    run::vec_to_run_id,
+    tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
+    time::time,
+};
+use futures::Future;
+use std::pin::Pin;
+
+const VERIFY_SCHEMA_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const VERIFY_SCHEMA_MAX_RETRIES: u32 = 5;
+
+/// Schedules a check of the SchemaRegistry of every chain the recipe supports
+/// for the schema of the recipe.
+pub fn add_verify_schema_task(recipe_id: &RecipeId, run_time: u64) {
+    add_task(
+        run_time,
+        Task {
+            task_type: TaskType::VerifyRecipeSchema,
+            args: recipe_id.to_vec(),
+            max_retries: VERIFY_SCHEMA_MAX_RETRIES,
+            execute_count: 0,
+            retry_interval: VERIFY_SCHEMA_RETRY_INTERVAL,
+        },
+    );
+}
+
+/// Schedules schema checks for the published recipes that were never checked.
+/// Tasks are keyed by run time, each check gets its own.
+pub fn add_unchecked_verify_schema_tasks() {
+    let run_time = ic_cdk::api::time() + VERIFY_SCHEMA_RETRY_INTERVAL;
+    for (i, recipe_id) in recipe::list_published_unchecked_schemas()
+        .iter()
+        .enumerate()
+    {
+        add_verify_schema_task(recipe_id, run_time + i as u64);
+    }
+}
+
+/// Saves the result of a SchemaRegistry check. The recipe may have been
+/// updated while the registry was called, results for a changed schema are
+/// discarded.
+fn save_schema_registration(
+    checked_recipe: &Recipe,
+    chain_id: u32,
+    registered: bool,
+) -> Result<Recipe, String> {
+    let recipe = recipe::get_by_id(&checked_recipe.id).map_err(|e| e.to_string())?;
+    if recipe.schema != checked_recipe.schema
+        || recipe.resolver != checked_recipe.resolver
+        || recipe.revokable != checked_recipe.revokable
+    {
+        return Err("Recipe schema has changed".to_string());
+    }
+
+    let uid = get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)
+        .map_err(|e| e.to_string())?;
+    let transaction_hash = recipe
+        .schema_registration(chain_id)
+        .and_then(|r| r.transaction_hash.clone());
+
+    recipe::set_schema_registration(
+        &recipe.id,
+        RecipeSchemaRegistration {
+            chain_id,
+            uid: to_hex(&uid),
+            registered,
+            transaction_hash,
+            checked: Some(time()),
+        },
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Checks the SchemaRegistry of one chain for the schema of the recipe and
+/// saves the result.
+pub async fn verify_chain_schema(
+    recipe: &Recipe,
+    chain_config: &ChainConfig,
+) -> Result<Recipe, String> {
+    let registered = is_schema_registered(recipe, chain_config)
+        .await
+        .map_err(|err| {
+            format!(
+                "Schema could not be checked on chain {}: {}",
+                chain_config.chain_id, err
+            )
+        })?;
+    save_schema_registration(recipe, chain_config.chain_id, registered)
+}
+
+pub struct VerifyRecipeSchemaExecutor {}
+
+impl TaskExecutor for VerifyRecipeSchemaExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let recipe_id = vec_to_run_id(task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            let recipe =
+                recipe::get_by_id(&recipe_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+            // Check every chain, chains that could not be checked are retried
+            let mut failed_chains = Vec::new();
+            for chain_config in chain_config::list()
+                .into_iter()
+                .filter(|config| recipe.supports_chain(config.chain_id))
+            {
+                let registered = match is_schema_registered(&recipe, &chain_config).await {
+                    Ok(registered) => registered,
+                    Err(err) => {
+                        logger::debug(&format!(
+                            "Schema check failed on chain {}: {}",
+                            chain_config.chain_id, err
+                        ));
+                        failed_chains.push(chain_config.chain_id);
+                        continue;
+                    }
+                };
+
+                save_schema_registration(&recipe, chain_config.chain_id, registered)
+                    .map_err(TaskError::Cancel)?;
+            }
+
+            if !failed_chains.is_empty() {
+                return Err(TaskError::Retry(format!(
+                    "Schema could not be checked on chains {:?}",
+                    failed_chains
+                )));
+            }
+
+            Ok(())
+        })
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
+    chain_config::{self},
+    eas::{parse_uid, schema::validate_schema, DecodedSchemaItem},
     eth_address::EthAddress,
     json::{bytes_to_hex_string_value, ToJsonValue},
+    processor::{stdlib::validate_stdlib_version, validate_processor_limits, ProcessorLimits},
     time::time,
 };
 use candid::{CandidType, Decode, Encode};
@@ -18,6 +21,8 @@ use super::generate_recipe_id;
 
 pub type RecipeId = [u8; 12];
 
+pub const MAX_RECIPE_MAINTAINERS: usize = 10;
+
 #[derive(Error, Debug)]
 pub enum RecipeError {
     #[error("Only drafts can be updated")]
@@ -26,11 +31,19 @@ pub enum RecipeError {
     NameInUse,
     #[error("Recipe not found")]
     NotFound,
+    #[error("No ownership transfer pending for this address")]
+    NoPendingTransfer,
+    #[error("Processor tests failed: {0}")]
+    TestsFailed(String),
+    #[error("Schema is not registered on chains: {0}")]
+    SchemaNotRegistered(String),
+    #[error("Recipe was changed while its tests ran")]
+    ChangedDuringTests,
     #[error("Internal error")]
     InternalError,
 }
 
-#[derive(Serialize, Deserialize, CandidType, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, CandidType, Clone, PartialEq, Debug)]
 pub enum RecipePublishState {
     Draft,
     Published,
@@ -48,6 +61,28 @@ impl fmt::Display for RecipePublishState {
     }
 }
 
+/// How processor output strings are read as integers. Recipes saved before
+/// decimal strings were supported read unprefixed strings as hex.
+#[derive(Serialize, Deserialize, CandidType, Clone, Copy, PartialEq, Debug, Default)]
+pub enum RecipeIntegerStrings {
+    #[default]
+    Hex,
+    Decimal,
+}
+
+/// Who, besides the paying user, runs of a recipe can attest for. The subject
+/// is the address the queries look up, the recipient receives the attestation.
+#[derive(Serialize, Deserialize, CandidType, Clone, Copy, PartialEq, Debug, Default)]
+pub enum RecipeRecipientRule {
+    // Subject and recipient are the paying user
+    #[default]
+    PayerOnly,
+    // Any recipient, attesting to data about the recipient
+    SubjectIsRecipient,
+    // Any recipient, attesting to data about any subject
+    Any,
+}
+
 #[derive(Serialize, Deserialize, Debug, CandidType, Clone, Validate)]
 pub struct RecipeQueryBody {
     #[validate(length(min = 1, max = 1024))]
@@ -57,9 +92,37 @@ pub struct RecipeQueryBody {
     pub variables: String,
 }
 
+/// Reads EAS attestations directly from a chain instead of through the query
+/// proxy. Attestations are read by uid, or by schema and recipient from the
+/// Attested logs of the EAS contract.
 #[derive(Serialize, Deserialize, Debug, CandidType, Clone, Validate)]
+#[validate(schema(function = "validate_recipe_onchain_query"))]
+pub struct RecipeOnchainQuery {
+    pub chain_id: u32,
+
+    pub uid: Option<String>,
+
+    pub schema_uid: Option<String>,
+
+    // Supports the same dynamic variables as query variables, the address the
+    // run looks up if not set
+    #[validate(length(min = 1, max = 100))]
+    pub recipient: Option<String>,
+
+    // Logs are read back from the latest block, a limited number of blocks
+    // and never below this block if set
+    pub from_block: Option<u64>,
+
+    // Most recent attestations first
+    #[validate(range(min = 1, max = 10))]
+    pub take: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone, Validate)]
+#[validate(schema(function = "validate_recipe_query"))]
 pub struct RecipeQuery {
-    #[validate(length(min = 1, max = 512))]
+    // Empty for on-chain queries
+    #[validate(length(max = 512))]
     pub url: String,
 
     #[validate(length(min = 1, max = 1024))]
@@ -69,6 +132,36 @@ pub struct RecipeQuery {
     pub filter: Option<String>,
 
     pub body: Option<RecipeQueryBody>,
+
+    #[validate(nested)]
+    pub onchain: Option<RecipeOnchainQuery>,
+}
+
+/// Sample aggregated query results and the output the processor is expected
+/// to produce for them.
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone, Validate)]
+pub struct RecipeProcessorTest {
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+
+    #[validate(length(min = 2, max = 4096))]
+    pub query_result: String,
+
+    #[validate(length(min = 1, max = 4096))]
+    pub expected_output: String,
+}
+
+/// Registration of the recipe schema in the SchemaRegistry of a chain.
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone)]
+pub struct RecipeSchemaRegistration {
+    pub chain_id: u32,
+    pub uid: String,
+    // The schema was found in the SchemaRegistry when last checked
+    pub registered: bool,
+    // Registration transaction sent from the canister address
+    pub transaction_hash: Option<String>,
+    // Time the SchemaRegistry was last checked
+    pub checked: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, CandidType, Clone, Validate)]
@@ -82,6 +175,14 @@ pub struct Recipe {
     #[validate(length(equal = 42))]
     pub creator: String,
 
+    // Addresses allowed to edit drafts and publish, in addition to the creator
+    #[validate(length(max = 10), custom(function = "validate_addresses"))]
+    pub maintainers: Option<Vec<String>>,
+
+    // Set by the creator when transferring ownership, cleared once accepted
+    #[validate(length(equal = 42))]
+    pub pending_creator: Option<String>,
+
     pub created: u32,
 
     #[validate(length(min = 3, max = 160))]
@@ -97,27 +198,56 @@ pub struct Recipe {
     #[validate(length(min = 1, max = 1024))]
     pub processor: String,
 
-    #[validate(length(min = 1, max = 512))]
+    // Recorded when the recipe is saved, recipes saved before limits were
+    // introduced run with the defaults.
+    #[validate(custom(function = "validate_processor_limits"))]
+    pub processor_limits: Option<ProcessorLimits>,
+
+    // Version of the `catts` helper library available to the processor, none
+    // for processors written without it
+    #[validate(custom(function = "validate_stdlib_version"))]
+    pub stdlib_version: Option<u32>,
+
+    // Set when the recipe is created, existing recipes keep reading unprefixed
+    // integer strings as hex
+    pub integer_strings: Option<RecipeIntegerStrings>,
+
+    // Must all pass before the recipe can be published
+    #[validate(length(max = 10), nested)]
+    pub tests: Option<Vec<RecipeProcessorTest>>,
+
+    #[validate(length(min = 1, max = 512), custom(function = "validate_schema"))]
     pub schema: String,
 
     #[validate(length(equal = 42))]
     pub resolver: String,
 
-    // Have to be false at the moment, revokable attestations not yet supported
-    #[validate(custom(function = "validate_revokable_false_only"))]
+    // Attestations expire this many seconds after they are created, unless
+    // the processor returns an expiration time
+    #[validate(range(min = 1))]
+    pub validity_seconds: Option<u64>,
+
+    // Recipes saved before the rule was introduced only attest for the payer
+    pub recipient_rule: Option<RecipeRecipientRule>,
+
+    // Attestations are created with EAS attestByDelegation with this address
+    // as attester, the attester signs each attestation and the engine relays it
+    #[validate(custom(function = "validate_address"))]
+    pub delegated_attester: Option<String>,
+
+    // Chains the recipe can be run on, all configured chains if not set
+    #[validate(custom(function = "validate_chain_ids"))]
+    pub chain_ids: Option<Vec<u32>>,
+
+    // Schema registration per chain, checked when the recipe is published
+    pub schema_registrations: Option<Vec<RecipeSchemaRegistration>>,
+
+    // Attestations of revokable recipes can be revoked by the recipe creator
+    // or the recipient
     pub revokable: bool,
     pub publish_state: RecipePublishState,
 }
 
-fn validate_revokable_false_only(value: &bool) -> Result<(), ValidationError> {
-    if *value {
-        return Err(ValidationError::new(
-            "Revokable attestations are not yet supported",
-        ));
-    }
-    Ok(())
-}
-
 /// Only lowercase letters (a-z), digits (0-9), and hyphens (-) are allowed.
 /// The name must not start or end with a hyphen.
 /// The name must not start with a digit.
@@ -139,6 +269,91 @@ fn validate_recipe_name(name: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+fn validate_address(address: &str) -> Result<(), ValidationError> {
+    if EthAddress::new(address).is_err() {
+        return Err(ValidationError::new("Invalid Ethereum address"));
+    }
+    Ok(())
+}
+
+fn validate_addresses(addresses: &[String]) -> Result<(), ValidationError> {
+    for address in addresses {
+        if EthAddress::new(address).is_err() {
+            return Err(ValidationError::new("Invalid Ethereum address"));
+        }
+    }
+    Ok(())
+}
+
+fn validate_chain_ids(chain_ids: &[u32]) -> Result<(), ValidationError> {
+    if chain_ids.is_empty() {
+        return Err(ValidationError::new("Chain ids must not be empty"));
+    }
+
+    for (i, chain_id) in chain_ids.iter().enumerate() {
+        if chain_config::get(*chain_id).is_err() {
+            return Err(ValidationError::new("Chain is not supported"));
+        }
+        if chain_ids[..i].contains(chain_id) {
+            return Err(ValidationError::new("Duplicate chain id"));
+        }
+    }
+    Ok(())
+}
+
+/// A query either reads attestations on chain or is sent to the query proxy.
+fn validate_recipe_query(query: &RecipeQuery) -> Result<(), ValidationError> {
+    match query.onchain {
+        Some(_) => {
+            if !query.url.is_empty()
+                || query.headers.is_some()
+                || query.filter.is_some()
+                || query.body.is_some()
+            {
+                return Err(ValidationError::new(
+                    "On-chain queries must not have a url, headers, filter or body",
+                ));
+            }
+        }
+        None => {
+            if query.url.is_empty() {
+                return Err(ValidationError::new("Query url must not be empty"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_recipe_onchain_query(query: &RecipeOnchainQuery) -> Result<(), ValidationError> {
+    if chain_config::get(query.chain_id).is_err() {
+        return Err(ValidationError::new("Chain is not supported"));
+    }
+
+    match (&query.uid, &query.schema_uid) {
+        (Some(uid), None) => {
+            if parse_uid(uid).is_err() {
+                return Err(ValidationError::new("Invalid attestation uid"));
+            }
+            if query.recipient.is_some() || query.from_block.is_some() || query.take.is_some() {
+                return Err(ValidationError::new(
+                    "Queries by uid must not have a recipient, from_block or take",
+                ));
+            }
+        }
+        (None, Some(schema_uid)) => {
+            if parse_uid(schema_uid).is_err() {
+                return Err(ValidationError::new("Invalid schema uid"));
+            }
+        }
+        _ => {
+            return Err(ValidationError::new(
+                "On-chain queries must have either a uid or a schema uid",
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn validate_keywords(keywords: &[String]) -> Result<(), ValidationError> {
     if keywords.is_empty() {
         return Err(ValidationError::new("Keywords must not be empty"));
@@ -180,6 +395,12 @@ impl ToJsonValue for Recipe {
         obj.insert("id".to_string(), bytes_to_hex_string_value(&self.id));
         obj.insert("name".to_string(), json!(self.name));
         obj.insert("creator".to_string(), json!(self.creator));
+        if let Some(ref maintainers) = self.maintainers {
+            obj.insert("maintainers".to_string(), json!(maintainers));
+        }
+        if let Some(ref pending_creator) = self.pending_creator {
+            obj.insert("pending_creator".to_string(), json!(pending_creator));
+        }
         obj.insert("created".to_string(), json!(self.created));
         if let Some(ref description) = self.description {
             obj.insert("description".to_string(), json!(description));
@@ -189,8 +410,38 @@ impl ToJsonValue for Recipe {
         }
         obj.insert("queries".to_string(), json!(self.queries));
         obj.insert("processor".to_string(), json!(self.processor));
+        if let Some(ref processor_limits) = self.processor_limits {
+            obj.insert("processor_limits".to_string(), json!(processor_limits));
+        }
+        if let Some(stdlib_version) = self.stdlib_version {
+            obj.insert("stdlib_version".to_string(), json!(stdlib_version));
+        }
+        if let Some(integer_strings) = self.integer_strings {
+            obj.insert("integer_strings".to_string(), json!(integer_strings));
+        }
+        if let Some(ref tests) = self.tests {
+            obj.insert("tests".to_string(), json!(tests));
+        }
         obj.insert("schema".to_string(), json!(self.schema));
         obj.insert("resolver".to_string(), json!(self.resolver));
+        if let Some(validity_seconds) = self.validity_seconds {
+            obj.insert("validity_seconds".to_string(), json!(validity_seconds));
+        }
+        if let Some(recipient_rule) = self.recipient_rule {
+            obj.insert("recipient_rule".to_string(), json!(recipient_rule));
+        }
+        if let Some(ref delegated_attester) = self.delegated_attester {
+            obj.insert("delegated_attester".to_string(), json!(delegated_attester));
+        }
+        if let Some(ref chain_ids) = self.chain_ids {
+            obj.insert("chain_ids".to_string(), json!(chain_ids));
+        }
+        if let Some(ref schema_registrations) = self.schema_registrations {
+            obj.insert(
+                "schema_registrations".to_string(),
+                json!(schema_registrations),
+            );
+        }
         obj.insert("revokable".to_string(), json!(self.revokable));
         obj.insert(
             "publish_state".to_string(),
@@ -216,13 +467,24 @@ impl Recipe {
             id: generate_recipe_id(creator, details.name.as_str()),
             name: details.name.clone(),
             creator: creator.to_string(),
+            maintainers: None,
+            pending_creator: None,
             created: time(),
             description: details.description.clone(),
             keywords: details.keywords.clone(),
             queries: details.queries.clone(),
             processor: details.processor.clone(),
+            processor_limits: Some(details.processor_limits.unwrap_or_default()),
+            stdlib_version: details.stdlib_version,
+            integer_strings: Some(RecipeIntegerStrings::Decimal),
+            tests: details.tests.clone(),
             schema: details.schema.clone(),
             resolver: details.resolver.clone(),
+            validity_seconds: details.validity_seconds,
+            recipient_rule: details.recipient_rule,
+            delegated_attester: details.delegated_attester.clone(),
+            chain_ids: details.chain_ids.clone(),
+            schema_registrations: None,
             revokable: details.revokable,
             publish_state: RecipePublishState::Draft,
         };
@@ -231,6 +493,97 @@ impl Recipe {
 
         Ok(recipe)
     }
+
+    /// Returns a copy of the recipe with the editable details replaced. Name,
+    /// ownership and publish state are kept as is.
+    pub fn with_details(
+        &self,
+        details: &RecipeDetailsInput,
+    ) -> Result<Self, validator::ValidationErrors> {
+        let recipe = Self {
+            description: details.description.clone(),
+            keywords: details.keywords.clone(),
+            queries: details.queries.clone(),
+            processor: details.processor.clone(),
+            processor_limits: Some(details.processor_limits.unwrap_or_default()),
+            stdlib_version: details.stdlib_version,
+            tests: details.tests.clone(),
+            schema: details.schema.clone(),
+            resolver: details.resolver.clone(),
+            validity_seconds: details.validity_seconds,
+            recipient_rule: details.recipient_rule,
+            delegated_attester: details.delegated_attester.clone(),
+            chain_ids: details.chain_ids.clone(),
+            // Registrations are for the schema uid, keep them only if it is unchanged
+            schema_registrations: if self.schema == details.schema
+                && self.resolver == details.resolver
+                && self.revokable == details.revokable
+            {
+                self.schema_registrations.clone()
+            } else {
+                None
+            },
+            revokable: details.revokable,
+            ..self.clone()
+        };
+
+        recipe.validate()?;
+
+        Ok(recipe)
+    }
+
+    pub fn processor_limits(&self) -> ProcessorLimits {
+        self.processor_limits.unwrap_or_default()
+    }
+
+    pub fn recipient_rule(&self) -> RecipeRecipientRule {
+        self.recipient_rule.unwrap_or_default()
+    }
+
+    pub fn integer_strings(&self) -> RecipeIntegerStrings {
+        self.integer_strings.unwrap_or_default()
+    }
+
+    pub fn supports_chain(&self, chain_id: u32) -> bool {
+        match self.chain_ids {
+            Some(ref chain_ids) => chain_ids.contains(&chain_id),
+            None => true,
+        }
+    }
+
+    pub fn schema_registration(&self, chain_id: u32) -> Option<&RecipeSchemaRegistration> {
+        self.schema_registrations
+            .as_ref()
+            .and_then(|registrations| registrations.iter().find(|r| r.chain_id == chain_id))
+    }
+
+    /// The schema is known to be missing from the SchemaRegistry of the chain.
+    /// Chains that have not been checked yet are not considered unregistered.
+    pub fn is_schema_unregistered(&self, chain_id: u32) -> bool {
+        self.schema_registration(chain_id)
+            .is_some_and(|registration| !registration.registered)
+    }
+
+    /// The schema was found in the SchemaRegistry of the chain.
+    pub fn is_schema_registered(&self, chain_id: u32) -> bool {
+        self.schema_registration(chain_id)
+            .is_some_and(|registration| registration.registered)
+    }
+
+    pub fn is_creator(&self, address: &EthAddress) -> bool {
+        self.creator == address.as_str()
+    }
+
+    pub fn is_maintainer(&self, address: &EthAddress) -> bool {
+        self.maintainers
+            .as_ref()
+            .is_some_and(|maintainers| maintainers.iter().any(|m| m == address.as_str()))
+    }
+
+    /// Creators and maintainers can edit drafts and publish.
+    pub fn can_edit(&self, address: &EthAddress) -> bool {
+        self.is_creator(address) || self.is_maintainer(address)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, CandidType)]
@@ -240,7 +593,138 @@ pub struct RecipeDetailsInput {
     pub keywords: Option<Vec<String>>,
     pub queries: Vec<RecipeQuery>,
     pub processor: String,
+    pub processor_limits: Option<ProcessorLimits>,
+    pub stdlib_version: Option<u32>,
+    pub tests: Option<Vec<RecipeProcessorTest>>,
     pub schema: String,
     pub resolver: String,
+    pub validity_seconds: Option<u64>,
+    pub recipient_rule: Option<RecipeRecipientRule>,
+    pub delegated_attester: Option<String>,
+    pub chain_ids: Option<Vec<u32>>,
     pub revokable: bool,
 }
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Copy, PartialEq, Debug)]
+pub enum RecipeListSortBy {
+    Created,
+    Name,
+    RunCount,
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Copy, PartialEq, Debug)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, Default)]
+pub struct RecipeListFilters {
+    pub publish_state: Option<RecipePublishState>,
+    pub creator: Option<String>,
+    pub keyword: Option<String>,
+    pub chain_id: Option<u32>,
+}
+
+impl RecipeListFilters {
+    pub fn matches(&self, recipe: &Recipe) -> bool {
+        if let Some(ref publish_state) = self.publish_state {
+            if recipe.publish_state != *publish_state {
+                return false;
+            }
+        }
+        if let Some(ref creator) = self.creator {
+            if recipe.creator != creator.to_lowercase() {
+                return false;
+            }
+        }
+        if let Some(chain_id) = self.chain_id {
+            if !recipe.supports_chain(chain_id) {
+                return false;
+            }
+        }
+        if let Some(ref keyword) = self.keyword {
+            if !recipe
+                .keywords
+                .as_ref()
+                .is_some_and(|keywords| keywords.contains(keyword))
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, Default)]
+pub struct RecipeListOptions {
+    // Opaque cursor, pass the next_cursor of the previous response to get the next page
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub filters: Option<RecipeListFilters>,
+    pub sort_by: Option<RecipeListSortBy>,
+    pub sort_order: Option<SortOrder>,
+}
+
+#[derive(Serialize, Deserialize, CandidType)]
+pub struct RecipeListResponse {
+    pub recipes: Vec<Recipe>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, Default)]
+pub struct RecipeStats {
+    pub runs_created: u32,
+    pub runs_paid: u32,
+    pub runs_attested: u32,
+    pub runs_failed: u32,
+    pub unique_users: u32,
+    pub last_run: Option<u32>,
+}
+
+impl Storable for RecipeStats {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Serialize, Deserialize, CandidType)]
+pub struct RecipeTrendingItem {
+    pub recipe: Recipe,
+    // Runs created within the trending window
+    pub runs: u32,
+}
+
+#[derive(Serialize, Deserialize, CandidType)]
+pub enum RecipeSimulateInput {
+    RecipeId(RecipeId),
+    Details(Box<RecipeDetailsInput>),
+}
+
+#[derive(Serialize, Deserialize, CandidType, Default)]
+pub struct RecipeSimulateResult {
+    pub query_results: Vec<String>,
+    pub processor_output: Option<String>,
+    // Console output of the processor
+    pub processor_logs: Vec<String>,
+    pub schema_values: Option<Vec<DecodedSchemaItem>>,
+    // Hex encoded attestation data, as it would be sent to the EAS contract
+    pub attestation_data: Option<String>,
+    // Unix time in seconds, 0 if the attestation doesn't expire
+    pub expiration_time: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug)]
+pub struct RecipeTestResult {
+    pub name: String,
+    pub passed: bool,
+    pub output: String,
+    pub error: Option<String>,
+}
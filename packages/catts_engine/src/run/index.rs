@@ -0,0 +1,155 @@
+use std::{cell::RefCell, ops::Bound, thread::LocalKey};
+
+use ethers_core::utils::hex;
+use ic_stable_structures::StableBTreeMap;
+
+use crate::{
+    recipe::RecipeId, Memory, RUNS, RUN_CREATED_INDEX, RUN_CREATOR_INDEX, RUN_RECIPE_INDEX,
+    RUN_STATUS_INDEX,
+};
+
+use super::{Run, RunId, RunStatus};
+
+type RunIndex = StableBTreeMap<String, RunId, Memory>;
+
+// Index keys start with the value runs are grouped by, followed by the zero
+// padded creation time and the hex encoded run id. Walking a group in reverse
+// lists the most recent runs first.
+
+fn created_suffix(run: &Run) -> String {
+    format!("{:010}{}", run.created, hex::encode(run.id))
+}
+
+fn creator_prefix(creator: &str) -> String {
+    creator.to_string()
+}
+
+fn recipe_prefix(recipe_id: &RecipeId) -> String {
+    hex::encode(recipe_id)
+}
+
+fn status_prefix(status: RunStatus) -> String {
+    format!("{:02}", status as u8)
+}
+
+fn created_key(run: &Run) -> String {
+    created_suffix(run)
+}
+
+fn creator_key(run: &Run) -> String {
+    format!("{}{}", creator_prefix(&run.creator), created_suffix(run))
+}
+
+fn recipe_key(run: &Run) -> String {
+    format!("{}{}", recipe_prefix(&run.recipe_id), created_suffix(run))
+}
+
+fn status_key(run: &Run) -> String {
+    format!("{}{}", status_prefix(run.status()), created_suffix(run))
+}
+
+/// The index and key prefix to walk when listing runs.
+pub enum RunListScope {
+    All,
+    Creator(String),
+    Recipe(RecipeId),
+    Status(RunStatus),
+}
+
+impl RunListScope {
+    fn index(&self) -> &'static LocalKey<RefCell<RunIndex>> {
+        match self {
+            RunListScope::All => &RUN_CREATED_INDEX,
+            RunListScope::Creator(_) => &RUN_CREATOR_INDEX,
+            RunListScope::Recipe(_) => &RUN_RECIPE_INDEX,
+            RunListScope::Status(_) => &RUN_STATUS_INDEX,
+        }
+    }
+
+    fn prefix(&self) -> String {
+        match self {
+            RunListScope::All => String::new(),
+            RunListScope::Creator(creator) => creator_prefix(creator),
+            RunListScope::Recipe(recipe_id) => recipe_prefix(recipe_id),
+            RunListScope::Status(status) => status_prefix(*status),
+        }
+    }
+}
+
+pub fn add(run: &Run) {
+    RUN_CREATED_INDEX.with_borrow_mut(|index| {
+        index.insert(created_key(run), run.id);
+    });
+    RUN_CREATOR_INDEX.with_borrow_mut(|index| {
+        index.insert(creator_key(run), run.id);
+    });
+    RUN_RECIPE_INDEX.with_borrow_mut(|index| {
+        index.insert(recipe_key(run), run.id);
+    });
+    RUN_STATUS_INDEX.with_borrow_mut(|index| {
+        index.insert(status_key(run), run.id);
+    });
+}
+
+/// Moves the run in the status index if the status changed. The other index
+/// keys don't change during the lifetime of a run.
+pub fn update(old_run: &Run, run: &Run) {
+    if old_run.status() == run.status() {
+        return;
+    }
+    RUN_STATUS_INDEX.with_borrow_mut(|index| {
+        index.remove(&status_key(old_run));
+        index.insert(status_key(run), run.id);
+    });
+}
+
+/// Walks the index of the scope from the most recent run backwards, starting
+/// before the cursor. Returns the index key and id of each visited run,
+/// visiting at most `max_items` runs.
+pub fn scan(
+    scope: &RunListScope,
+    cursor: Option<&String>,
+    max_items: usize,
+) -> Vec<(String, RunId)> {
+    let prefix = scope.prefix();
+    if cursor.is_some_and(|cursor| !cursor.starts_with(&prefix)) {
+        return vec![];
+    }
+    scope.index().with_borrow(|index| {
+        // Keys of the scope are all smaller than the prefix followed by a
+        // character sorting after the alphanumeric key characters.
+        let end = match cursor {
+            Some(cursor) => Bound::Excluded(cursor.clone()),
+            None => Bound::Excluded(format!("{}~", prefix)),
+        };
+        index
+            .range((Bound::Included(prefix.clone()), end))
+            .rev()
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(max_items)
+            .collect()
+    })
+}
+
+fn clear(index: &'static LocalKey<RefCell<RunIndex>>) {
+    index.with_borrow_mut(|index| {
+        let keys: Vec<String> = index.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            index.remove(&key);
+        }
+    });
+}
+
+/// Recreates the run indexes from the runs in stable memory.
+pub fn rebuild_indexes() {
+    clear(&RUN_CREATED_INDEX);
+    clear(&RUN_CREATOR_INDEX);
+    clear(&RUN_RECIPE_INDEX);
+    clear(&RUN_STATUS_INDEX);
+
+    RUNS.with_borrow(|runs| {
+        for (_, run) in runs.iter() {
+            add(&run);
+        }
+    });
+}
@@ -1,3 +1,4 @@
+pub mod index;
 pub mod rpc;
 pub mod state;
 pub mod tasks;
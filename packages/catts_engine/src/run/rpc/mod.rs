@@ -1,4 +1,10 @@
 pub mod run_cancel;
 pub mod run_create;
 pub mod run_get;
+pub mod run_list_by_recipe;
+pub mod run_list_by_user;
+pub mod run_list_recent;
 pub mod run_register_payment;
+pub mod run_retry_chains;
+pub mod run_revoke;
+pub mod run_submit_delegation_signature;
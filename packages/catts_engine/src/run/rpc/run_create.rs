@@ -1,14 +1,37 @@
 use crate::{
     chain_config::{self},
+    eth_address::EthAddress,
     http_error::HttpError,
     logger::{self},
-    recipe::{self, RecipeId, RecipePublishState},
-    run::{self, get_cyclesfee_for_chain, get_min_gasfee_for_chain, Run},
+    recipe::{self, Recipe, RecipeId, RecipePublishState},
+    run::{
+        self, get_cyclesfee_for_chain, get_min_gasfee_for_chain, get_onchain_query_fee, Run,
+        RunChainInput, RunCreateOptions,
+    },
     user::auth_guard,
 };
 use candid::Nat;
 use ic_cdk::{api::canister_balance, update};
 
+fn check_recipe_chain(recipe: &Recipe, chain_id: u32) -> Result<(), HttpError> {
+    if !recipe.supports_chain(chain_id) {
+        return Err(HttpError::bad_request(format!(
+            "Recipe does not support chain {}",
+            chain_id
+        )));
+    }
+
+    // Attestations would revert on chains without the schema
+    if recipe.is_schema_unregistered(chain_id) {
+        return Err(HttpError::bad_request(format!(
+            "Recipe schema is not registered on chain {}",
+            chain_id
+        )));
+    }
+
+    Ok(())
+}
+
 #[update]
 async fn run_create(
     recipe_id: RecipeId,
@@ -16,6 +39,7 @@ async fn run_create(
     base_fee_per_gas: Nat,
     max_priority_fee_per_gas: Nat,
     gas: Nat,
+    options: Option<RunCreateOptions>,
 ) -> Result<Run, HttpError> {
     let address = auth_guard()?;
 
@@ -32,31 +56,120 @@ async fn run_create(
         HttpError::internal_server_error(format!("Chain {} is not supported", chain_id).as_str())
     })?;
 
-    let mut run = Run::new(&recipe_id, chain_id, &address).map_err(HttpError::bad_request)?;
-
-    // Add 50% to the base fee per gas to account for gas fee volatility
-    let base_fee_per_gas = base_fee_per_gas * Nat::from(3_u8) / Nat::from(2_u8);
+    check_recipe_chain(&recipe, chain_id)?;
 
-    let gas_fee = gas.clone() * (base_fee_per_gas.clone() + max_priority_fee_per_gas.clone());
-    let min_gas_fee = get_min_gasfee_for_chain(chain_id).unwrap();
-    let gas_fee = gas_fee.max(min_gas_fee);
+    let mut run = Run::new(&recipe_id, chain_id, &address).map_err(HttpError::bad_request)?;
 
-    let cycles_fee = get_cyclesfee_for_chain(chain_id).unwrap();
+    let options = options.unwrap_or_default();
+    let recipient = options
+        .recipient
+        .as_deref()
+        .map(EthAddress::new)
+        .transpose()
+        .map_err(HttpError::bad_request)?;
+    let subject = options
+        .subject
+        .as_deref()
+        .map(EthAddress::new)
+        .transpose()
+        .map_err(HttpError::bad_request)?;
+    run.set_recipient(
+        recipe.recipient_rule(),
+        recipient.as_ref(),
+        subject.as_ref(),
+    )
+    .map_err(HttpError::bad_request)?;
+
+    if options.offchain == Some(true) {
+        // Delegated attestations are signed by the attester of the recipe
+        if recipe.delegated_attester.is_some() {
+            return Err(HttpError::bad_request(
+                "Recipe attests by delegation, off-chain runs are not supported",
+            ));
+        }
+        run.offchain = Some(true);
+    }
 
-    let user_fee = gas_fee + cycles_fee;
+    if let Some(ref additional_chains) = options.additional_chains {
+        if run.is_offchain() || recipe.delegated_attester.is_some() {
+            return Err(HttpError::bad_request(
+                "Only on-chain runs attested by the canister can target several chains",
+            ));
+        }
+        for additional_chain in additional_chains {
+            chain_config::get(additional_chain.chain_id).map_err(|_| {
+                HttpError::bad_request(format!(
+                    "Chain {} is not supported",
+                    additional_chain.chain_id
+                ))
+            })?;
+            check_recipe_chain(&recipe, additional_chain.chain_id)?;
+        }
+
+        // Same margin on the base fee per gas as the run chain
+        let additional_chains: Vec<RunChainInput> = additional_chains
+            .iter()
+            .map(|chain| RunChainInput {
+                base_fee_per_gas: chain.base_fee_per_gas.clone() * Nat::from(3_u8)
+                    / Nat::from(2_u8),
+                ..chain.clone()
+            })
+            .collect();
+        run.set_additional_chains(&additional_chains)
+            .map_err(HttpError::bad_request)?;
+    }
 
-    logger::debug(
-        format!(
-            "base_fee_per_gas: {}, max_priority_fee_per_gas: {}, gas: {}",
-            base_fee_per_gas, max_priority_fee_per_gas, gas
-        )
-        .as_str(),
-    );
+    if let Some(refresh_run_id) = options.refresh_run_id {
+        let refreshed_run = run::get(&refresh_run_id).map_err(HttpError::not_found)?;
+        run.refresh(&refreshed_run)
+            .map_err(HttpError::bad_request)?;
+    }
 
-    run.gas = Some(gas);
-    run.base_fee_per_gas = Some(base_fee_per_gas);
-    run.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
-    run.user_fee = Some(user_fee);
+    // The queries run once, whatever the number of chains attested on
+    let cycles_fee = get_cyclesfee_for_chain(chain_id).unwrap()
+        + get_onchain_query_fee(&recipe, chain_id).unwrap();
+
+    if run.is_offchain() {
+        // Off-chain attestations are signed by the canister, no gas is paid
+        run.user_fee = Some(cycles_fee);
+    } else {
+        // Add 50% to the base fee per gas to account for gas fee volatility
+        let base_fee_per_gas = base_fee_per_gas * Nat::from(3_u8) / Nat::from(2_u8);
+
+        let gas_fee = gas.clone() * (base_fee_per_gas.clone() + max_priority_fee_per_gas.clone());
+        let min_gas_fee = get_min_gasfee_for_chain(chain_id).unwrap();
+        let mut user_fee = gas_fee.clone().max(min_gas_fee.clone()) + cycles_fee;
+
+        // Revoking the attestation sends a second transaction with the same
+        // gas settings, paid for upfront
+        if recipe.revokable {
+            user_fee += gas_fee.clone().max(min_gas_fee);
+        }
+
+        // One payment covers every chain, each additional chain is charged
+        // for the gas settings quoted for it
+        for chain in run.additional_chains.iter().flatten() {
+            let chain_gas_fee = chain.gas.clone().unwrap_or_default()
+                * (chain.base_fee_per_gas.clone().unwrap_or_default()
+                    + chain.max_priority_fee_per_gas.clone().unwrap_or_default());
+            let min_gas_fee = get_min_gasfee_for_chain(chain.chain_id).unwrap();
+            user_fee +=
+                chain_gas_fee.max(min_gas_fee) + get_cyclesfee_for_chain(chain.chain_id).unwrap();
+        }
+
+        logger::debug(
+            format!(
+                "base_fee_per_gas: {}, max_priority_fee_per_gas: {}, gas: {}",
+                base_fee_per_gas, max_priority_fee_per_gas, gas
+            )
+            .as_str(),
+        );
+
+        run.gas = Some(gas);
+        run.base_fee_per_gas = Some(base_fee_per_gas);
+        run.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+        run.user_fee = Some(user_fee);
+    }
 
     let run = run::create(run);
 
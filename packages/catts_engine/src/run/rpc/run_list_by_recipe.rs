@@ -0,0 +1,19 @@
+use ic_cdk::query;
+
+use crate::{
+    http_error::HttpError,
+    recipe::{self, RecipeId},
+    run::{self, index::RunListScope, RunListOptions, RunListResponse},
+};
+
+#[query]
+fn run_list_by_recipe(
+    recipe_id: RecipeId,
+    options: Option<RunListOptions>,
+) -> Result<RunListResponse, HttpError> {
+    recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+    Ok(run::list(
+        RunListScope::Recipe(recipe_id),
+        &options.unwrap_or_default(),
+    ))
+}
@@ -0,0 +1,19 @@
+use ic_cdk::query;
+
+use crate::{
+    eth_address::EthAddress,
+    http_error::HttpError,
+    run::{self, index::RunListScope, RunListOptions, RunListResponse},
+};
+
+#[query]
+fn run_list_by_user(
+    address: String,
+    options: Option<RunListOptions>,
+) -> Result<RunListResponse, HttpError> {
+    let address = EthAddress::new(&address).map_err(HttpError::bad_request)?;
+    Ok(run::list(
+        RunListScope::Creator(address.to_string()),
+        &options.unwrap_or_default(),
+    ))
+}
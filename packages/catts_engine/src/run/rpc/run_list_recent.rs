@@ -0,0 +1,11 @@
+use ic_cdk::query;
+
+use crate::{
+    http_error::HttpError,
+    run::{self, index::RunListScope, RunListOptions, RunListResponse},
+};
+
+#[query]
+fn run_list_recent(options: Option<RunListOptions>) -> Result<RunListResponse, HttpError> {
+    Ok(run::list(RunListScope::All, &options.unwrap_or_default()))
+}
@@ -0,0 +1,58 @@
+use crate::{
+    http_error::HttpError,
+    logger::{self},
+    run::{self, tasks::chain_attestation::add_create_chain_attestation_task, Run, RunId},
+    user::auth_guard,
+};
+use ic_cdk::update;
+
+/// Retries the attestations of a multi-chain run on the chains where they
+/// failed, the run chain included. Chains that were attested are left as is.
+#[update]
+fn run_retry_chains(run_id: RunId) -> Result<Run, HttpError> {
+    let address = auth_guard()?;
+    logger::debug("run_retry_chains");
+
+    let mut run = run::get(&run_id).map_err(HttpError::not_found)?;
+
+    if run.creator != address.as_str() {
+        return Err(HttpError::forbidden(
+            "Only the creator of the run can retry it",
+        ));
+    }
+
+    if run.attestation_output.is_none() {
+        return Err(HttpError::bad_request(
+            "Run has no processor output to attest with",
+        ));
+    }
+
+    let mut failed_chain_ids: Vec<u32> = run
+        .additional_chains
+        .iter()
+        .flatten()
+        .filter(|chain| chain.is_failed())
+        .map(|chain| chain.chain_id)
+        .collect();
+    if run.error.is_some() && run.attestation_transaction_hash.is_none() {
+        failed_chain_ids.insert(0, run.chain_id);
+        run.error = None;
+    }
+
+    if failed_chain_ids.is_empty() {
+        return Err(HttpError::bad_request("Run has no failed chains"));
+    }
+
+    for chain_id in failed_chain_ids.iter() {
+        if let Some(chain) = run.additional_chain_mut(*chain_id) {
+            chain.error = None;
+        }
+    }
+    let run = run::update(run).map_err(HttpError::internal_server_error)?;
+
+    for chain_id in failed_chain_ids {
+        add_create_chain_attestation_task(&run_id, chain_id);
+    }
+
+    Ok(run)
+}
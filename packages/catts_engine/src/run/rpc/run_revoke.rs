@@ -0,0 +1,71 @@
+use crate::{
+    eas::revoke_attestation,
+    http_error::HttpError,
+    logger::{self},
+    recipe::{self},
+    run::{self, tasks::confirm_revocation::add_confirm_revocation_task, Run, RunError, RunId},
+    user::auth_guard,
+};
+use ic_cdk::update;
+
+/// Revokes the attestation of the run with the gas settings the run was paid
+/// with, runs of revokable recipes include the revocation in their fee. The
+/// run is marked revoked once the transaction has succeeded.
+#[update]
+async fn run_revoke(run_id: RunId) -> Result<Run, HttpError> {
+    let address = auth_guard()?;
+    logger::debug("run_revoke");
+
+    let run = run::get(&run_id).map_err(HttpError::not_found)?;
+    let recipe = recipe::get_by_id(&run.recipe_id).map_err(HttpError::not_found)?;
+
+    // Only the recipe creator or the recipient can revoke the attestation
+    if !recipe.is_creator(&address) && run.recipient() != address.as_str() {
+        return Err(HttpError::forbidden(
+            "Only the recipe creator or the recipient can revoke the attestation",
+        ));
+    }
+
+    if !recipe.revokable {
+        return Err(HttpError::bad_request(
+            "Attestations of this recipe are not revokable",
+        ));
+    }
+
+    if run.is_offchain() {
+        return Err(HttpError::bad_request(RunError::CantBeRevoked(
+            "Off-chain attestations are not revoked on chain".to_string(),
+        )));
+    }
+
+    // Rejects concurrent requests while the revocation is sent. The task also
+    // clears a revocation that never got its transaction hash saved.
+    let run = run::start_revocation(&run_id).map_err(|err| match err {
+        RunError::CantBeRevoked(_) => HttpError::bad_request(err),
+        err => HttpError::internal_server_error(err),
+    })?;
+    add_confirm_revocation_task(&run_id);
+
+    let transaction_hash = match revoke_attestation(&recipe, &run).await {
+        Ok(transaction_hash) => transaction_hash,
+        Err(err) => {
+            // Nothing was sent, the attestation can be revoked again
+            run::reset_revocation(&run_id).map_err(HttpError::internal_server_error)?;
+            return Err(HttpError::internal_server_error(format!(
+                "Error revoking attestation: {}",
+                err
+            )));
+        }
+    };
+
+    let revoked_run = run::set_revocation_transaction_hash(&run_id, &transaction_hash)
+        .map_err(HttpError::internal_server_error)?;
+
+    // The task gave up on a revocation that took too long to send, confirm it
+    // with a new one
+    if revoked_run.revocation_started != run.revocation_started {
+        add_confirm_revocation_task(&run_id);
+    }
+
+    Ok(revoked_run)
+}
@@ -0,0 +1,89 @@
+use crate::{
+    chain_config::{self},
+    eas::delegation::{create_delegated_attestation, verify_delegation_signature},
+    http_error::HttpError,
+    logger::{self},
+    recipe::{self},
+    run::{
+        self, tasks::create_attestation::add_get_attestation_uid_task, Run, RunDelegation,
+        RunError, RunId,
+    },
+    time::time,
+    user::auth_guard,
+};
+use ic_cdk::update;
+
+/// Submits the signature of the delegated attester of the recipe. The run
+/// creator or the attester can submit it, the signature is checked against
+/// the prepared attestation before the canister sends it as relayer.
+#[update]
+async fn run_submit_delegation_signature(
+    run_id: RunId,
+    signature: String,
+) -> Result<Run, HttpError> {
+    let address = auth_guard()?;
+    logger::debug("run_submit_delegation_signature");
+
+    let mut run = run::get(&run_id).map_err(HttpError::not_found)?;
+    let recipe = recipe::get_by_id(&run.recipe_id).map_err(HttpError::not_found)?;
+
+    if !run.is_awaiting_delegation_signature() {
+        return Err(HttpError::bad_request(
+            "Run has no delegated attestation awaiting a signature",
+        ));
+    }
+
+    let delegation = run.delegation.clone().unwrap();
+    if run.creator != address.as_str() && delegation.attester != address.as_str() {
+        return Err(HttpError::forbidden(
+            "Only the run creator or the attester can submit the signature",
+        ));
+    }
+
+    if delegation.expires.is_some_and(|expires| expires < time()) {
+        return Err(HttpError::bad_request(
+            "The delegated attestation has expired",
+        ));
+    }
+
+    let chain_config = chain_config::get(run.chain_id).map_err(|_| {
+        HttpError::internal_server_error(format!("Chain {} is not supported", run.chain_id))
+    })?;
+
+    let signature = verify_delegation_signature(&recipe, &delegation, &signature, &chain_config)
+        .map_err(|err| {
+            HttpError::bad_request(RunError::InvalidDelegationSignature(err.to_string()))
+        })?;
+
+    // Reject further submissions while the attestation is sent
+    run.delegation = Some(RunDelegation {
+        submitted: Some(time()),
+        ..delegation
+    });
+    let run = run::update(run).map_err(HttpError::internal_server_error)?;
+
+    let transaction_hash = match create_delegated_attestation(&recipe, &run, &signature).await {
+        Ok(transaction_hash) => transaction_hash,
+        Err(err) => {
+            // Nothing was sent, the signature can be submitted again
+            let mut run = run::get(&run_id).map_err(HttpError::not_found)?;
+            if let Some(ref mut delegation) = run.delegation {
+                delegation.submitted = None;
+            }
+            run::update(run).map_err(HttpError::internal_server_error)?;
+            return Err(HttpError::internal_server_error(format!(
+                "Error creating attestation: {}",
+                err
+            )));
+        }
+    };
+
+    // The run may have changed while the transaction was sent
+    let mut run = run::get(&run_id).map_err(HttpError::not_found)?;
+    run.attestation_transaction_hash = Some(transaction_hash);
+    let run = run::update(run).map_err(HttpError::internal_server_error)?;
+
+    add_get_attestation_uid_task(&run_id);
+
+    Ok(run)
+}
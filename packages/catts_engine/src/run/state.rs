@@ -1,11 +1,20 @@
-use super::types::{Run, RunError, RunId};
+use super::index::{self, RunListScope};
+use super::types::{Run, RunError, RunId, RunListOptions, RunListResponse, RunStatus};
 use crate::change_log::ChangeLogTypeName;
 use crate::eth_address::EthAddress;
-use crate::{change_log, RUNS};
+use crate::time::time;
+use crate::{change_log, eas, recipe, RUNS};
 use blake2::digest::{Update, VariableOutput};
 use blake2::Blake2bVar;
 use candid::Nat;
 
+const RUN_LIST_DEFAULT_LIMIT: u32 = 20;
+const RUN_LIST_MAX_LIMIT: u32 = 100;
+
+// Upper bound on the number of runs visited by one list call, keeps filtered
+// listings within the query instruction limit.
+const RUN_LIST_MAX_SCAN: usize = 1_000;
+
 pub fn generate_run_id(creator: &EthAddress, created: u32) -> RunId {
     let mut hasher = Blake2bVar::new(12).unwrap();
     hasher.update(&creator.as_byte_array());
@@ -19,6 +28,8 @@ pub fn create(run: Run) -> Run {
     RUNS.with_borrow_mut(|runs| {
         runs.insert(run.id, run.clone());
     });
+    index::add(&run);
+    recipe::stats::run_created(&run);
     change_log::create(ChangeLogTypeName::Run, run.id, &run).unwrap();
     run
 }
@@ -28,6 +39,9 @@ pub fn update(run: Run) -> Result<Run, RunError> {
     RUNS.with_borrow_mut(|runs| {
         runs.insert(run.id, run.clone());
     });
+    index::update(&old_run, &run);
+    eas::index::add_run(&run);
+    recipe::stats::run_updated(&old_run, &run);
     change_log::update(ChangeLogTypeName::Run, run.id, &old_run, &run).unwrap();
     Ok(run)
 }
@@ -45,10 +59,110 @@ pub fn cancel(run_id: &RunId) -> Result<Run, RunError> {
     update(run)
 }
 
+/// Marks the revocation of the attestation of the run as started, before the
+/// revocation transaction is sent.
+pub fn start_revocation(run_id: &RunId) -> Result<Run, RunError> {
+    let mut run = get(run_id)?;
+
+    match run.status() {
+        RunStatus::AttestationUidConfirmed => {}
+        RunStatus::AttestationRevocationPending => {
+            return Err(RunError::CantBeRevoked("Revocation is pending".to_string()))
+        }
+        RunStatus::AttestationRevoked => {
+            return Err(RunError::CantBeRevoked(
+                "Attestation is already revoked".to_string(),
+            ))
+        }
+        _ => {
+            return Err(RunError::CantBeRevoked(
+                "Run has no confirmed attestation".to_string(),
+            ))
+        }
+    }
+
+    run.revocation_started = Some(time());
+
+    update(run)
+}
+
+/// Saves the hash of the sent revocation transaction.
+pub fn set_revocation_transaction_hash(
+    run_id: &RunId,
+    transaction_hash: &str,
+) -> Result<Run, RunError> {
+    let mut run = get(run_id)?;
+    // The revocation may have been cleared while the transaction was signed,
+    // it is pending again now that the transaction has been sent
+    if run.revocation_started.is_none() {
+        run.revocation_started = Some(time());
+    }
+    run.revocation_transaction_hash = Some(transaction_hash.to_string());
+    update(run)
+}
+
+/// Records the revocation once its transaction has succeeded.
+pub fn confirm_revocation(run_id: &RunId) -> Result<Run, RunError> {
+    let mut run = get(run_id)?;
+    run.revoked = Some(time());
+    update(run)
+}
+
+/// Clears a revocation that was not sent or whose transaction failed, the
+/// attestation can be revoked again.
+pub fn reset_revocation(run_id: &RunId) -> Result<Run, RunError> {
+    let mut run = get(run_id)?;
+    run.revocation_started = None;
+    run.revocation_transaction_hash = None;
+    update(run)
+}
+
 pub fn get(run_id: &RunId) -> Result<Run, RunError> {
     RUNS.with_borrow(|runs| runs.get(run_id).ok_or(RunError::NotFound))
 }
 
+/// Lists the runs in the scope, most recent first.
+pub fn list(scope: RunListScope, options: &RunListOptions) -> RunListResponse {
+    let limit = options
+        .limit
+        .unwrap_or(RUN_LIST_DEFAULT_LIMIT)
+        .clamp(1, RUN_LIST_MAX_LIMIT) as usize;
+
+    // The status index serves the status filter when no other scope is given
+    let scope = match (scope, options.status) {
+        (RunListScope::All, Some(status)) => RunListScope::Status(status),
+        (scope, _) => scope,
+    };
+
+    let entries = index::scan(&scope, options.cursor.as_ref(), RUN_LIST_MAX_SCAN);
+    let scanned_all = entries.len() < RUN_LIST_MAX_SCAN;
+    let mut runs = Vec::new();
+    let mut next_cursor = None;
+    let mut last_key = None;
+
+    for (key, run_id) in entries {
+        last_key = Some(key.clone());
+        if let Ok(run) = get(&run_id) {
+            if options.status.is_some_and(|status| run.status() != status) {
+                continue;
+            }
+            runs.push(run);
+            if runs.len() == limit {
+                next_cursor = Some(key);
+                break;
+            }
+        }
+    }
+
+    // Scan limit reached before the page was filled, let the caller continue
+    // from the last visited run.
+    if next_cursor.is_none() && !scanned_all {
+        next_cursor = last_key;
+    }
+
+    RunListResponse { runs, next_cursor }
+}
+
 pub fn register_payment(
     run_id: &RunId,
     transaction_hash: &str,
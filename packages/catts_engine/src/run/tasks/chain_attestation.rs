@@ -0,0 +1,198 @@
+use crate::{
+    chain_config::{self},
+    eas::create_attestation,
+    eth_address::EthAddress,
+    evm::rpc::eth_get_transaction_receipt,
+    logger::{self},
+    recipe::{self},
+    run::{self, RunId},
+    tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
+};
+use futures::Future;
+use std::pin::Pin;
+
+use super::create_attestation::{add_get_attestation_uid_task, add_get_chain_attestation_uid_task};
+
+const CREATE_CHAIN_ATTESTATION_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const CREATE_CHAIN_ATTESTATION_MAX_RETRIES: u32 = 3;
+
+// Chain tasks take the run id followed by the chain id as arguments
+pub fn chain_task_args(run_id: &RunId, chain_id: u32) -> Vec<u8> {
+    let mut args = run_id.to_vec();
+    args.extend_from_slice(&chain_id.to_be_bytes());
+    args
+}
+
+fn parse_chain_task_args(args: &[u8]) -> Result<(RunId, u32), TaskError> {
+    if args.len() != 16 {
+        return Err(TaskError::Cancel("Invalid arguments".to_string()));
+    }
+    let run_id: RunId = args[..12].try_into().unwrap();
+    let chain_id = u32::from_be_bytes(args[12..].try_into().unwrap());
+    Ok((run_id, chain_id))
+}
+
+/// Schedules the attestation of a multi-chain run on one of its chains, the
+/// run chain included.
+pub fn add_create_chain_attestation_task(run_id: &RunId, chain_id: u32) {
+    add_task(
+        0, // Run ASAP
+        Task {
+            task_type: TaskType::CreateChainAttestation,
+            args: chain_task_args(run_id, chain_id),
+            max_retries: CREATE_CHAIN_ATTESTATION_MAX_RETRIES,
+            execute_count: 0,
+            retry_interval: CREATE_CHAIN_ATTESTATION_RETRY_INTERVAL,
+        },
+    );
+}
+
+/// Saves the error on the chain of the run, the other chains of the run are
+/// not affected. The error of the run chain is the error of the run.
+fn save_chain_error_and_cancel(run_id: &RunId, chain_id: u32, error: String) -> TaskError {
+    logger::debug(&format!("save_chain_error_and_cancel: {}", error));
+    let mut run = run::get(run_id).unwrap();
+    if chain_id == run.chain_id {
+        run.error = Some(error.clone());
+    } else if let Some(chain) = run.additional_chain_mut(chain_id) {
+        chain.error = Some(error.clone());
+    }
+    run::update(run).unwrap();
+    TaskError::Cancel(error)
+}
+
+async fn create_chain_attestation(run_id: &RunId, chain_id: u32) -> Result<(), TaskError> {
+    let run = run::get(run_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+    let attestation_transaction_hash = if chain_id == run.chain_id {
+        run.attestation_transaction_hash.as_ref()
+    } else {
+        run.additional_chain(chain_id)
+            .ok_or(TaskError::Cancel(
+                "Chain is not part of the run".to_string(),
+            ))?
+            .attestation_transaction_hash
+            .as_ref()
+    };
+    if attestation_transaction_hash.is_some() {
+        return Err(TaskError::Cancel("Chain already attested".to_string()));
+    }
+
+    let recipe = recipe::get_by_id(&run.recipe_id).map_err(|_| {
+        save_chain_error_and_cancel(run_id, chain_id, "Recipe not found".to_string())
+    })?;
+
+    let attestation_output = run.attestation_output.clone().ok_or_else(|| {
+        save_chain_error_and_cancel(run_id, chain_id, "Run has no processor output".to_string())
+    })?;
+
+    // A refreshed run references its own attestation on the same chain, on
+    // the run chain the refUID is kept on the run
+    let ref_uid = match run.ref_run_id {
+        _ if chain_id == run.chain_id => run.ref_uid.clone(),
+        Some(ref ref_run_id) => run::get(ref_run_id)
+            .ok()
+            .and_then(|ref_run| ref_run.attestation_uid_on_chain(chain_id).map(String::from)),
+        None => None,
+    };
+
+    let attestation_transaction_hash = create_attestation(
+        &recipe,
+        &run,
+        &attestation_output,
+        &EthAddress::from(run.recipient()),
+        ref_uid.as_deref(),
+        chain_id,
+    )
+    .await
+    .map_err(|err| {
+        save_chain_error_and_cancel(
+            run_id,
+            chain_id,
+            format!("Error creating attestation: {}", err),
+        )
+    })?;
+
+    // The other chains may have updated the run while the transaction was sent
+    let mut run = run::get(run_id).unwrap();
+    if chain_id == run.chain_id {
+        run.attestation_transaction_hash = Some(attestation_transaction_hash);
+        run.error = None;
+        run::update(run).unwrap();
+        add_get_attestation_uid_task(run_id);
+        return Ok(());
+    }
+    if let Some(chain) = run.additional_chain_mut(chain_id) {
+        chain.attestation_transaction_hash = Some(attestation_transaction_hash);
+        chain.error = None;
+    }
+    run::update(run).unwrap();
+
+    add_get_chain_attestation_uid_task(run_id, chain_id);
+
+    Ok(())
+}
+
+async fn get_chain_attestation_uid(run_id: &RunId, chain_id: u32) -> Result<(), TaskError> {
+    let run = run::get(run_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+    let chain = run.additional_chain(chain_id).ok_or(TaskError::Cancel(
+        "Chain is not part of the run".to_string(),
+    ))?;
+    if chain.attestation_uid.is_some() {
+        return Err(TaskError::Cancel("Chain already attested".to_string()));
+    }
+    let attestation_transaction_hash =
+        chain.attestation_transaction_hash.clone().ok_or_else(|| {
+            save_chain_error_and_cancel(
+                run_id,
+                chain_id,
+                "No attestation transaction hash".to_string(),
+            )
+        })?;
+
+    let chain_config = chain_config::get(chain_id).map_err(|_| {
+        save_chain_error_and_cancel(run_id, chain_id, "Chain config not found".to_string())
+    })?;
+
+    let receipt = eth_get_transaction_receipt(&attestation_transaction_hash, &chain_config)
+        .await
+        .map_err(TaskError::Retry)?;
+
+    if receipt.logs.is_empty() {
+        return Err(TaskError::Retry(
+            "No logs in transaction receipt".to_string(),
+        ));
+    }
+
+    logger::debug("Chain attestation uid found");
+    let mut run = run::get(run_id).unwrap();
+    if let Some(chain) = run.additional_chain_mut(chain_id) {
+        chain.attestation_uid = Some(receipt.logs[0].data.clone());
+    }
+    run::update(run).unwrap();
+
+    Ok(())
+}
+
+pub struct CreateChainAttestationExecutor {}
+
+impl TaskExecutor for CreateChainAttestationExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let (run_id, chain_id) = parse_chain_task_args(&task.args)?;
+            create_chain_attestation(&run_id, chain_id).await
+        })
+    }
+}
+
+pub struct GetChainAttestationUidExecutor {}
+
+impl TaskExecutor for GetChainAttestationUidExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let (run_id, chain_id) = parse_chain_task_args(&task.args)?;
+            get_chain_attestation_uid(&run_id, chain_id).await
+        })
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{
+    chain_config::{self},
+    evm::rpc::eth_get_transaction_receipt,
+    logger::{self},
+    run::{self, RunId, RunStatus},
+    tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
+    time::time,
+};
+use candid::Nat;
+use futures::Future;
+use std::pin::Pin;
+
+const CONFIRM_REVOCATION_FIRST_TIME_INTERVAL: u64 = 5_000_000_000; // 5 seconds
+const CONFIRM_REVOCATION_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const CONFIRM_REVOCATION_MAX_RETRIES: u32 = 10;
+
+// A revocation without a transaction hash after this time was never sent
+const REVOCATION_SEND_TIMEOUT_SECONDS: u32 = 60;
+
+/// Schedules the task that waits for the revocation transaction of the run
+/// and records the revocation once the transaction has succeeded.
+pub fn add_confirm_revocation_task(run_id: &RunId) {
+    add_task(
+        ic_cdk::api::time() + CONFIRM_REVOCATION_FIRST_TIME_INTERVAL,
+        Task {
+            task_type: TaskType::ConfirmRunRevocation,
+            args: run_id.to_vec(),
+            max_retries: CONFIRM_REVOCATION_MAX_RETRIES,
+            execute_count: 0,
+            retry_interval: CONFIRM_REVOCATION_RETRY_INTERVAL,
+        },
+    );
+}
+
+pub struct ConfirmRunRevocationExecutor {}
+
+impl TaskExecutor for ConfirmRunRevocationExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let run_id = run::vec_to_run_id(task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            let run = run::get(&run_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+            if run.status() != RunStatus::AttestationRevocationPending {
+                return Ok(());
+            }
+
+            // The transaction hash is saved once the signed transaction has been
+            // sent, a revocation that never got one is cleared after a timeout
+            let Some(transaction_hash) = run.revocation_transaction_hash.clone() else {
+                let started = run.revocation_started.unwrap_or_default();
+                if time().saturating_sub(started) > REVOCATION_SEND_TIMEOUT_SECONDS {
+                    logger::debug("Revocation transaction was never sent");
+                    run::reset_revocation(&run_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+                    return Err(TaskError::Cancel(
+                        "No revocation transaction hash".to_string(),
+                    ));
+                }
+                return Err(TaskError::Retry(
+                    "Revocation transaction not sent yet".to_string(),
+                ));
+            };
+
+            let chain_config = chain_config::get(run.chain_id)
+                .map_err(|_| TaskError::Cancel("Chain config not found".to_string()))?;
+
+            let receipt = eth_get_transaction_receipt(&transaction_hash, &chain_config)
+                .await
+                .map_err(TaskError::Retry)?;
+
+            // A failed transaction leaves the attestation in place, the run can
+            // be revoked again
+            if receipt.status == Some(Nat::from(0u8)) {
+                logger::debug("Revocation transaction failed");
+                run::reset_revocation(&run_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+                return Err(TaskError::Cancel(
+                    "Revocation transaction failed".to_string(),
+                ));
+            }
+
+            logger::debug("Revocation confirmed");
+            run::confirm_revocation(&run_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+            Ok(())
+        })
+    }
+}
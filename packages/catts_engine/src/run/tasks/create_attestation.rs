@@ -1,22 +1,63 @@
 use crate::{
-    eas::{create_attestation, process_query_result, run_query},
+    certified_data::render_run_attestation_asset,
+    chain_config::{self},
+    eas::{
+        create_attestation, delegation::prepare_delegated_attestation,
+        offchain::create_offchain_attestation, parse_processor_output, run_query, schema,
+        AttestationOutput,
+    },
     eth_address::EthAddress,
     logger::{self},
+    processor,
     recipe::{self},
-    run::{self, RunStatus},
+    run::{self, RunId, RunStatus},
     tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
 };
+use ethers_core::utils::hex;
 use futures::Future;
 use ic_cdk::api::canister_balance;
 use std::pin::Pin;
 
-use super::util::save_error_and_cancel;
+use super::{
+    chain_attestation::{add_create_chain_attestation_task, chain_task_args},
+    expire_delegation::add_expire_delegation_task,
+    util::{save_error_and_cancel, save_processor_failure_and_cancel},
+};
+
 const GET_ATTESTATION_UID_FIRST_TIME_INTERVAL: u64 = 5_000_000_000; // 5 seconds
 const GET_ATTESTATION_UID_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
 const GET_ATTESTATION_UID_MAX_RETRIES: u32 = 10;
 
 pub struct CreateAttestationExecutor {}
 
+/// Schedules the task that waits for the attestation transaction of the run
+/// and saves the attestation uid.
+pub fn add_get_attestation_uid_task(run_id: &RunId) {
+    schedule_get_attestation_uid(TaskType::GetAttestationUid, run_id.to_vec());
+}
+
+/// Schedules the task that saves the attestation uid of a multi-chain run on
+/// one of its additional chains.
+pub fn add_get_chain_attestation_uid_task(run_id: &RunId, chain_id: u32) {
+    schedule_get_attestation_uid(
+        TaskType::GetChainAttestationUid,
+        chain_task_args(run_id, chain_id),
+    );
+}
+
+fn schedule_get_attestation_uid(task_type: TaskType, args: Vec<u8>) {
+    add_task(
+        ic_cdk::api::time() + GET_ATTESTATION_UID_FIRST_TIME_INTERVAL,
+        Task {
+            task_type,
+            args,
+            max_retries: GET_ATTESTATION_UID_MAX_RETRIES,
+            execute_count: 0,
+            retry_interval: GET_ATTESTATION_UID_RETRY_INTERVAL,
+        },
+    );
+}
+
 impl TaskExecutor for CreateAttestationExecutor {
     fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
         Box::pin(async move {
@@ -48,11 +89,12 @@ impl TaskExecutor for CreateAttestationExecutor {
                 save_error_and_cancel(&run_id, "Recipe contains no queries".to_string());
             }
 
-            let recipient = EthAddress::from(run.creator.as_str());
+            let recipient = EthAddress::from(run.recipient());
+            let subject = EthAddress::from(run.subject());
             let mut query_response = Vec::new();
 
             for i in 0..recipe.queries.len() {
-                let response = run_query(&recipient, &recipe.queries[i]).await;
+                let response = run_query(&subject, &recipe.queries[i]).await;
                 match response {
                     Ok(qr) => {
                         logger::debug(&format!("Query response: {}", qr));
@@ -65,31 +107,131 @@ impl TaskExecutor for CreateAttestationExecutor {
             }
             let aggregated_response = format!("[{}]", query_response.join(","));
 
-            let attestation_data = process_query_result(&recipe.processor, &aggregated_response);
+            let processor_output = processor::execute_isolated(
+                &recipe.processor,
+                &aggregated_response,
+                &recipe.processor_limits(),
+                recipe.stdlib_version,
+            )
+            .await
+            .map_err(|failure| save_processor_failure_and_cancel(&run_id, failure))?
+            .output;
+
+            let attestation_output =
+                parse_processor_output(&processor_output, &aggregated_response)
+                    .map_err(|err| save_error_and_cancel(&run_id, err.to_string()))?;
+
+            schema::validate_output(&recipe.schema, &attestation_output.data).map_err(|err| {
+                save_error_and_cancel(
+                    &run_id,
+                    format!("Processor output does not match the schema: {}", err),
+                )
+            })?;
+
+            // Keep the attestation referenced by the processor on the run
+            if run.ref_uid.is_none() {
+                run.ref_uid = attestation_output.ref_uid.clone();
+            }
+
+            if run.is_offchain() {
+                let chain_config = chain_config::get(run.chain_id).map_err(|_| {
+                    save_error_and_cancel(&run_id, "Chain config not found".to_string())
+                })?;
+                let (uid, offchain_attestation) = create_offchain_attestation(
+                    &recipe,
+                    &attestation_output,
+                    &recipient,
+                    run.ref_uid.as_deref(),
+                    &chain_config,
+                )
+                .await
+                .map_err(|err| {
+                    save_error_and_cancel(
+                        &run_id,
+                        format!("Error creating off-chain attestation: {}", err),
+                    )
+                })?;
+
+                run.attestation_uid = Some(uid);
+                run.offchain_attestation = Some(offchain_attestation);
+                run::update(run).unwrap();
+
+                // Serve the signed attestation right away
+                let _ = render_run_attestation_asset(hex::encode(run_id));
+
+                return Ok(());
+            }
+
+            // The attester of the recipe signs the attestation before it is sent,
+            // see run_submit_delegation_signature
+            if let Some(ref delegated_attester) = recipe.delegated_attester {
+                let chain_config = chain_config::get(run.chain_id).map_err(|_| {
+                    save_error_and_cancel(&run_id, "Chain config not found".to_string())
+                })?;
+                let delegation = prepare_delegated_attestation(
+                    &recipe,
+                    &attestation_output,
+                    &recipient,
+                    run.ref_uid.as_deref(),
+                    &EthAddress::from(delegated_attester.as_str()),
+                    &chain_config,
+                )
+                .await
+                .map_err(|err| {
+                    save_error_and_cancel(
+                        &run_id,
+                        format!("Error preparing delegated attestation: {}", err),
+                    )
+                })?;
+
+                run.delegation = Some(delegation);
+                run::update(run).unwrap();
+
+                add_expire_delegation_task(&run_id);
 
-            let attestation_transaction_hash =
-                create_attestation(&recipe, &run, &attestation_data, &recipient, run.chain_id)
-                    .await
-                    .map_err(|err| {
-                        save_error_and_cancel(
-                            &run_id,
-                            format!("Error creating attestation: {}", err),
-                        )
-                    })?;
+                return Ok(());
+            }
+
+            // Multi-chain runs attest on every chain from the same processor
+            // output, each chain in a task of its own that can be retried. A
+            // refUID from the processor points at an attestation on the run
+            // chain, it is kept on the run and not used on other chains.
+            if let Some(ref additional_chains) = run.additional_chains {
+                let mut chain_ids = vec![run.chain_id];
+                chain_ids.extend(
+                    additional_chains
+                        .iter()
+                        .filter(|chain| chain.attestation_transaction_hash.is_none())
+                        .map(|chain| chain.chain_id),
+                );
+                run.attestation_output = Some(AttestationOutput {
+                    ref_uid: None,
+                    ..attestation_output.clone()
+                });
+                run::update(run).unwrap();
+                for chain_id in chain_ids {
+                    add_create_chain_attestation_task(&run_id, chain_id);
+                }
+                return Ok(());
+            }
+
+            let attestation_transaction_hash = create_attestation(
+                &recipe,
+                &run,
+                &attestation_output,
+                &recipient,
+                run.ref_uid.as_deref(),
+                run.chain_id,
+            )
+            .await
+            .map_err(|err| {
+                save_error_and_cancel(&run_id, format!("Error creating attestation: {}", err))
+            })?;
 
             run.attestation_transaction_hash = Some(attestation_transaction_hash.clone());
             run::update(run).unwrap();
 
-            add_task(
-                ic_cdk::api::time() + GET_ATTESTATION_UID_FIRST_TIME_INTERVAL,
-                Task {
-                    task_type: TaskType::GetAttestationUid,
-                    args: run_id.to_vec(),
-                    max_retries: GET_ATTESTATION_UID_MAX_RETRIES,
-                    execute_count: 0,
-                    retry_interval: GET_ATTESTATION_UID_RETRY_INTERVAL,
-                },
-            );
+            add_get_attestation_uid_task(&run_id);
 
             let cycles_after = canister_balance();
             logger::info(
@@ -0,0 +1,50 @@
+use crate::{
+    eas::delegation::DELEGATION_SIGNATURE_TTL_SECONDS,
+    logger::{self},
+    run::{self, RunId},
+    tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
+};
+use futures::Future;
+use std::pin::Pin;
+
+const EXPIRE_DELEGATION_MAX_RETRIES: u32 = 1;
+const EXPIRE_DELEGATION_RETRY_INTERVAL: u64 = 60_000_000_000; // 1 minute
+
+/// Schedules the task that fails the run if the attester has not signed the
+/// delegated attestation when the signature expires.
+pub fn add_expire_delegation_task(run_id: &RunId) {
+    add_task(
+        ic_cdk::api::time() + DELEGATION_SIGNATURE_TTL_SECONDS as u64 * 1_000_000_000,
+        Task {
+            task_type: TaskType::ExpireRunDelegation,
+            args: run_id.to_vec(),
+            max_retries: EXPIRE_DELEGATION_MAX_RETRIES,
+            execute_count: 0,
+            retry_interval: EXPIRE_DELEGATION_RETRY_INTERVAL,
+        },
+    );
+}
+
+pub struct ExpireRunDelegationExecutor {}
+
+impl TaskExecutor for ExpireRunDelegationExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let run_id = run::vec_to_run_id(task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            let mut run = run::get(&run_id).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+            // Signed in time, or a submitted attestation is being sent
+            if !run.is_awaiting_delegation_signature() {
+                return Ok(());
+            }
+
+            logger::debug("Delegated attestation expired");
+            run.error = Some("Delegated attestation was not signed in time".to_string());
+            run::update(run).map_err(|e| TaskError::Cancel(e.to_string()))?;
+
+            Ok(())
+        })
+    }
+}
@@ -1,4 +1,7 @@
+pub mod chain_attestation;
+pub mod confirm_revocation;
 pub mod create_attestation;
+pub mod expire_delegation;
 pub mod get_attestation_uid;
 pub mod register_payment;
 pub mod util;
@@ -8,7 +8,7 @@ use crate::{
     eth_address::{remove_address_padding, EthAddress},
     ETH_PAYMENT_EVENT_SIGNATURE,
 };
-use anyhow::{anyhow, bail, Result};
+use anyhow::{bail, Result};
 use ethers_core::abi::ParamType;
 use futures::Future;
 use ic_cdk::api::canister_balance;
@@ -90,11 +90,14 @@ impl TaskExecutor for RegisterPaymentExecutor {
     }
 }
 
-fn process_log_entry(
+/// Checks that the log entry is a payment event of the payment contract sent
+/// from the expected address, returns the paid id and amount. Runs and batches
+/// are paid with the same event.
+pub fn decode_payment_log_entry(
     entry: &LogEntry,
-    args: &ProcessRunPaymentArgs,
     chain_config: &ChainConfig,
-) -> Result<Run> {
+    from_address: &EthAddress,
+) -> Result<([u8; 12], u128)> {
     if entry.address.to_lowercase() != chain_config.payment_contract.to_lowercase() {
         bail!("Payment log entry address does not match the expected address");
     }
@@ -120,83 +123,96 @@ fn process_log_entry(
         }
     };
 
-    let from_address = EthAddress::from(args.from_address);
-
     if event_from_address.as_byte_array() != from_address.as_byte_array() {
         bail!("Payment log entry from address does not match the expected address");
     }
 
     // Hex string to raw bytes
-    if let Ok(data) = ethers_core::utils::hex::decode(entry.data.clone()) {
-        // Raw bytes to two parameters, payment amount and run_id
-        if let Ok(decoded_data) =
-            ethers_core::abi::decode(&[ParamType::Uint(256), ParamType::FixedBytes(12)], &data)
-        {
-            if decoded_data.len() < 2 {
-                bail!("Decoded data has less than 2 elements");
-            }
+    let data = match ethers_core::utils::hex::decode(entry.data.clone()) {
+        Ok(data) => data,
+        Err(_) => bail!("Failed to decode log hex data"),
+    };
 
-            let event_run_id = match decoded_data[1].clone().into_fixed_bytes() {
-                Some(bytes) => bytes,
-                None => {
-                    bail!("Payment run_id is the wrong data type");
-                }
-            };
+    // Raw bytes to two parameters, payment amount and run_id
+    let decoded_data =
+        match ethers_core::abi::decode(&[ParamType::Uint(256), ParamType::FixedBytes(12)], &data) {
+            Ok(decoded_data) => decoded_data,
+            Err(_) => bail!("Failed to decode log data"),
+        };
 
-            let event_run_id = match run::vec_to_run_id(event_run_id) {
-                Ok(run_id) => run_id,
-                Err(_) => {
-                    bail!("Payment run_id is not a valid run_id");
-                }
-            };
+    if decoded_data.len() < 2 {
+        bail!("Decoded data has less than 2 elements");
+    }
 
-            if event_run_id != args.run_id {
-                bail!("Payment run_id does not match the expected run_id");
-            }
+    let event_id = match decoded_data[1].clone().into_fixed_bytes() {
+        Some(bytes) => bytes,
+        None => {
+            bail!("Payment run_id is the wrong data type");
+        }
+    };
 
-            let run = match run::get(&event_run_id) {
-                Ok(run) => run,
-                Err(_) => {
-                    bail!("Found payment for non-existent run");
-                }
-            };
+    let event_id = match run::vec_to_run_id(event_id) {
+        Ok(id) => id,
+        Err(_) => {
+            bail!("Payment run_id is not a valid run_id");
+        }
+    };
 
-            if run.status() == RunStatus::PaymentPending {
-                bail!("No payment transaction is registered for this run");
-            }
+    let event_amount = match decoded_data[0].clone().into_uint() {
+        Some(amount) => amount,
+        None => {
+            bail!("Payment amount is the wrong data type");
+        }
+    };
 
-            if run.status() > RunStatus::PaymentRegistered {
-                bail!("Run payment is already verified");
-            }
+    let event_amount: u128 = match event_amount.try_into() {
+        Ok(amount) => amount,
+        Err(_) => {
+            bail!("Payment amount is too large");
+        }
+    };
 
-            let event_amount = match decoded_data[0].clone().into_uint() {
-                Some(amount) => amount,
-                None => {
-                    bail!("Payment amount is the wrong data type");
-                }
-            };
+    Ok((event_id, event_amount))
+}
 
-            let event_amount: u128 = match event_amount.try_into() {
-                Ok(amount) => amount,
-                Err(_) => {
-                    bail!("Payment amount is too large");
-                }
-            };
+fn process_log_entry(
+    entry: &LogEntry,
+    args: &ProcessRunPaymentArgs,
+    chain_config: &ChainConfig,
+) -> Result<Run> {
+    let from_address = EthAddress::from(args.from_address);
+    let (event_run_id, event_amount) =
+        decode_payment_log_entry(entry, chain_config, &from_address)?;
 
-            let user_fee = match run.user_fee.clone() {
-                Some(fee) => fee,
-                None => {
-                    bail!("Run does not have a user fee");
-                }
-            };
+    if event_run_id != args.run_id {
+        bail!("Payment run_id does not match the expected run_id");
+    }
 
-            if event_amount >= user_fee {
-                return Ok(run);
-            } else {
-                bail!("Payment did not cover the cost of the run");
-            }
+    let run = match run::get(&event_run_id) {
+        Ok(run) => run,
+        Err(_) => {
+            bail!("Found payment for non-existent run");
         }
-        bail!("Failed to decode log data");
+    };
+
+    if run.status() == RunStatus::PaymentPending {
+        bail!("No payment transaction is registered for this run");
+    }
+
+    if run.status() > RunStatus::PaymentRegistered {
+        bail!("Run payment is already verified");
+    }
+
+    let user_fee = match run.user_fee.clone() {
+        Some(fee) => fee,
+        None => {
+            bail!("Run does not have a user fee");
+        }
+    };
+
+    if event_amount >= user_fee {
+        Ok(run)
+    } else {
+        bail!("Payment did not cover the cost of the run");
     }
-    Err(anyhow!("Failed to decode log hex data"))
 }
@@ -1,4 +1,11 @@
-use crate::{logger, run, tasks::TaskError};
+use crate::{
+    logger,
+    processor::{self, ProcessorFailure},
+    run,
+    tasks::TaskError,
+};
+
+const MAX_PROCESSOR_LOGS_LENGTH: usize = 2_048;
 
 pub fn save_error_and_cancel(run_id: &[u8; 12], error: String) -> TaskError {
     logger::debug(&format!("save_error_and_cancel: {}", error));
@@ -7,3 +14,23 @@ pub fn save_error_and_cancel(run_id: &[u8; 12], error: String) -> TaskError {
     run::update(run).unwrap();
     TaskError::Cancel(error)
 }
+
+/// Saves the processor error and its console output on the run and cancels
+/// the task.
+pub fn save_processor_failure_and_cancel(
+    run_id: &[u8; 12],
+    failure: ProcessorFailure,
+) -> TaskError {
+    let error = failure.error.to_string();
+    logger::debug(&format!("save_processor_failure_and_cancel: {}", error));
+    let mut run = run::get(run_id).unwrap();
+    run.error = Some(error.clone());
+    if !failure.logs.is_empty() {
+        run.processor_logs = Some(processor::logs_to_string(
+            &failure.logs,
+            MAX_PROCESSOR_LOGS_LENGTH,
+        ));
+    }
+    run::update(run).unwrap();
+    TaskError::Cancel(error)
+}
@@ -1,7 +1,8 @@
 use crate::{
+    eas::AttestationOutput,
     eth_address::EthAddress,
     json::{bytes_to_hex_string_value, nat_to_hex_string_value, ToJsonValue},
-    recipe::{self, RecipeId},
+    recipe::{self, RecipeId, RecipeRecipientRule},
     time::time,
 };
 use candid::{CandidType, Decode, Encode, Nat};
@@ -23,10 +24,23 @@ pub enum RunError {
     CantBeCancelled(String),
     #[error("Already paid")]
     AlreadyPaid,
+    #[error("Can't be revoked: {0}")]
+    CantBeRevoked(String),
+    #[error("Can't be refreshed: {0}")]
+    CantBeRefreshed(String),
+    #[error("Invalid recipient: {0}")]
+    InvalidRecipient(String),
+    #[error("Invalid delegation signature: {0}")]
+    InvalidDelegationSignature(String),
+    #[error("Invalid chains: {0}")]
+    InvalidChains(String),
 }
 
 pub type RunId = [u8; 12];
 
+// Maximum number of chains a run attests on besides the run chain
+pub const MAX_ADDITIONAL_CHAINS: usize = 3;
+
 #[derive(Serialize, Deserialize, Debug, CandidType, Clone)]
 pub struct Run {
     pub id: RunId,
@@ -34,6 +48,13 @@ pub struct Run {
     pub creator: String,
     pub created: u32,
     pub chain_id: u32,
+    // Address receiving the attestation, the creator if not set
+    pub recipient: Option<String>,
+    // Address the recipe queries are run for, the recipient if not set
+    pub subject: Option<String>,
+    // Off-chain runs sign an EAS off-chain attestation instead of sending a
+    // transaction, no gas is paid
+    pub offchain: Option<bool>,
     pub gas: Option<Nat>,
     pub base_fee_per_gas: Option<Nat>,
     pub max_priority_fee_per_gas: Option<Nat>,
@@ -43,17 +64,124 @@ pub struct Run {
     pub payment_log_index: Option<Nat>,
     pub attestation_transaction_hash: Option<String>,
     pub attestation_uid: Option<String>,
+    // Signed off-chain attestation, JSON
+    pub offchain_attestation: Option<String>,
+    // Delegated attestation prepared for the attester of the recipe, the
+    // attestation is sent once the attester has signed it
+    pub delegation: Option<RunDelegation>,
+    // Chains attested on besides the chain the run is paid on
+    pub additional_chains: Option<Vec<RunChainAttestation>>,
+    // Processor output of a multi-chain run, the attestations on the
+    // additional chains are created from it
+    pub attestation_output: Option<AttestationOutput>,
+    // Run whose attestation this run refreshes
+    pub ref_run_id: Option<RunId>,
+    // Attestation referenced by the refUID of the attestation, from the
+    // refreshed run or the processor output
+    pub ref_uid: Option<String>,
+    // Time the revocation was requested, further requests are rejected while
+    // it is sent and confirmed
+    pub revocation_started: Option<u32>,
+    pub revocation_transaction_hash: Option<String>,
+    // Time the revocation transaction was confirmed
+    pub revoked: Option<u32>,
     pub is_cancelled: bool,
     pub error: Option<String>,
+    // Console output of a failed processor, truncated
+    pub processor_logs: Option<String>,
 }
 
-#[derive(PartialEq, PartialOrd)]
+/// The attestation of a multi-chain run on one of its additional chains.
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone)]
+pub struct RunChainAttestation {
+    pub chain_id: u32,
+    pub attestation_transaction_hash: Option<String>,
+    pub attestation_uid: Option<String>,
+    // The attestation failed on this chain, it can be retried without
+    // affecting the other chains
+    pub error: Option<String>,
+    // Gas settings quoted for this chain, runs created before chains were
+    // quoted separately use the settings of the run
+    pub gas: Option<Nat>,
+    pub base_fee_per_gas: Option<Nat>,
+    pub max_priority_fee_per_gas: Option<Nat>,
+}
+
+/// An additional chain of a new run, with gas settings quoted for that chain.
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone)]
+pub struct RunChainInput {
+    pub chain_id: u32,
+    pub base_fee_per_gas: Nat,
+    pub max_priority_fee_per_gas: Nat,
+    pub gas: Nat,
+}
+
+impl RunChainAttestation {
+    pub fn is_failed(&self) -> bool {
+        self.error.is_some() && self.attestation_transaction_hash.is_none()
+    }
+}
+
+/// The request data of a delegated attestation, as signed by the attester.
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone)]
+pub struct RunDelegation {
+    pub attester: String,
+    pub recipient: String,
+    pub expiration_time: u64,
+    pub revocable: bool,
+    pub ref_uid: String,
+    // ABI encoded attestation data, 0x prefixed
+    pub data: String,
+    // EAS nonce of the attester when the attestation was prepared
+    pub nonce: Nat,
+    // EIP-712 typed data for the attester to sign, JSON
+    pub typed_data: String,
+    // The signature is no longer accepted after this time
+    pub expires: Option<u32>,
+    // Time a signature was accepted and the attestation is being sent, further
+    // submissions are rejected
+    pub submitted: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone, Copy, PartialEq, PartialOrd)]
 pub enum RunStatus {
     PaymentPending = 0,
     PaymentRegistered = 1,
     PaymentVerified = 2,
     AttestationCreated = 3,
     AttestationUidConfirmed = 4,
+    AttestationRevocationPending = 5,
+    AttestationRevoked = 6,
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, Default)]
+pub struct RunListOptions {
+    // Opaque cursor, pass the next_cursor of the previous response to get the next page
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub status: Option<RunStatus>,
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, Default)]
+pub struct RunCreateOptions {
+    // A previous run of the same recipe to refresh, the new attestation
+    // references its attestation
+    pub refresh_run_id: Option<RunId>,
+    // Attest for another address than the paying user, if the recipe allows it
+    pub recipient: Option<String>,
+    // Run the queries for another address than the recipient, if the recipe
+    // allows it
+    pub subject: Option<String>,
+    // Create an off-chain attestation
+    pub offchain: Option<bool>,
+    // Also attest on these chains, paid for together with the run chain
+    pub additional_chains: Option<Vec<RunChainInput>>,
+}
+
+#[derive(Serialize, Deserialize, CandidType)]
+pub struct RunListResponse {
+    pub runs: Vec<Run>,
+    pub next_cursor: Option<String>,
 }
 
 impl Storable for Run {
@@ -80,6 +208,18 @@ impl ToJsonValue for Run {
         obj.insert("creator".to_string(), json!(self.creator));
         obj.insert("created".to_string(), json!(self.created));
         obj.insert("chain_id".to_string(), json!(self.chain_id));
+        if let Some(ref recipient) = self.recipient {
+            obj.insert(
+                "recipient".to_string(),
+                Value::String(recipient.to_string()),
+            );
+        }
+        if let Some(ref subject) = self.subject {
+            obj.insert("subject".to_string(), Value::String(subject.to_string()));
+        }
+        if let Some(offchain) = self.offchain {
+            obj.insert("offchain".to_string(), json!(offchain));
+        }
         if let Some(ref gas) = self.gas {
             obj.insert("gas".to_string(), nat_to_hex_string_value(gas));
         }
@@ -128,10 +268,75 @@ impl ToJsonValue for Run {
                 Value::String(attestation_uid.to_string()),
             );
         }
+        if let Some(ref offchain_attestation) = self.offchain_attestation {
+            obj.insert(
+                "offchain_attestation".to_string(),
+                Value::String(offchain_attestation.to_string()),
+            );
+        }
+        if let Some(ref delegation) = self.delegation {
+            obj.insert("delegation".to_string(), delegation.to_json_value());
+        }
+        if let Some(ref additional_chains) = self.additional_chains {
+            obj.insert("additional_chains".to_string(), json!(additional_chains));
+        }
+        if let Some(ref attestation_output) = self.attestation_output {
+            obj.insert("attestation_output".to_string(), json!(attestation_output));
+        }
+        if let Some(ref ref_run_id) = self.ref_run_id {
+            obj.insert(
+                "ref_run_id".to_string(),
+                bytes_to_hex_string_value(ref_run_id),
+            );
+        }
+        if let Some(ref ref_uid) = self.ref_uid {
+            obj.insert("ref_uid".to_string(), Value::String(ref_uid.to_string()));
+        }
+        if let Some(revocation_started) = self.revocation_started {
+            obj.insert("revocation_started".to_string(), json!(revocation_started));
+        }
+        if let Some(ref revocation_transaction_hash) = self.revocation_transaction_hash {
+            obj.insert(
+                "revocation_transaction_hash".to_string(),
+                Value::String(revocation_transaction_hash.to_string()),
+            );
+        }
+        if let Some(revoked) = self.revoked {
+            obj.insert("revoked".to_string(), json!(revoked));
+        }
         obj.insert("is_cancelled".to_string(), json!(self.is_cancelled));
         if let Some(ref error) = self.error {
             obj.insert("error".to_string(), Value::String(error.to_string()));
         }
+        if let Some(ref processor_logs) = self.processor_logs {
+            obj.insert(
+                "processor_logs".to_string(),
+                Value::String(processor_logs.to_string()),
+            );
+        }
+
+        Value::Object(obj)
+    }
+}
+
+impl ToJsonValue for RunDelegation {
+    fn to_json_value(&self) -> Value {
+        let mut obj = serde_json::Map::new();
+
+        obj.insert("attester".to_string(), json!(self.attester));
+        obj.insert("recipient".to_string(), json!(self.recipient));
+        obj.insert("expiration_time".to_string(), json!(self.expiration_time));
+        obj.insert("revocable".to_string(), json!(self.revocable));
+        obj.insert("ref_uid".to_string(), json!(self.ref_uid));
+        obj.insert("data".to_string(), json!(self.data));
+        obj.insert("nonce".to_string(), nat_to_hex_string_value(&self.nonce));
+        obj.insert("typed_data".to_string(), json!(self.typed_data));
+        if let Some(expires) = self.expires {
+            obj.insert("expires".to_string(), json!(expires));
+        }
+        if let Some(submitted) = self.submitted {
+            obj.insert("submitted".to_string(), json!(submitted));
+        }
 
         Value::Object(obj)
     }
@@ -161,6 +366,9 @@ impl Run {
             creator: creator.to_string(),
             created,
             chain_id,
+            recipient: None,
+            subject: None,
+            offchain: None,
             gas: None,
             base_fee_per_gas: None,
             max_priority_fee_per_gas: None,
@@ -170,14 +378,185 @@ impl Run {
             payment_log_index: None,
             attestation_transaction_hash: None,
             attestation_uid: None,
+            offchain_attestation: None,
+            delegation: None,
+            additional_chains: None,
+            attestation_output: None,
+            ref_run_id: None,
+            ref_uid: None,
+            revocation_started: None,
+            revocation_transaction_hash: None,
+            revoked: None,
             is_cancelled: false,
             error: None,
+            processor_logs: None,
         };
 
         Ok(run)
     }
 
+    /// Makes this run refresh the attestation of a previous run by the same
+    /// creator, for the same recipe and chain.
+    pub fn refresh(&mut self, refreshed_run: &Run) -> Result<(), RunError> {
+        if refreshed_run.recipe_id != self.recipe_id {
+            return Err(RunError::CantBeRefreshed(
+                "Run is for a different recipe".to_string(),
+            ));
+        }
+        if refreshed_run.creator != self.creator {
+            return Err(RunError::CantBeRefreshed(
+                "Run was created by another user".to_string(),
+            ));
+        }
+        if refreshed_run.recipient() != self.recipient() {
+            return Err(RunError::CantBeRefreshed(
+                "Run has a different recipient".to_string(),
+            ));
+        }
+        if refreshed_run.chain_id != self.chain_id {
+            return Err(RunError::CantBeRefreshed(
+                "Run is on a different chain".to_string(),
+            ));
+        }
+        if refreshed_run.is_offchain() != self.is_offchain() {
+            return Err(RunError::CantBeRefreshed(
+                "Run attests in a different mode, on-chain or off-chain".to_string(),
+            ));
+        }
+        if refreshed_run.status() != RunStatus::AttestationUidConfirmed {
+            return Err(RunError::CantBeRefreshed(
+                "Run has no confirmed attestation".to_string(),
+            ));
+        }
+
+        self.ref_run_id = Some(refreshed_run.id);
+        self.ref_uid = refreshed_run.attestation_uid.clone();
+        Ok(())
+    }
+
+    /// Makes the run attest also on the given chains, besides the chain it is
+    /// paid on.
+    pub fn set_additional_chains(&mut self, chains: &[RunChainInput]) -> Result<(), RunError> {
+        let chain_ids: Vec<u32> = chains.iter().map(|chain| chain.chain_id).collect();
+        if chain_ids.is_empty() {
+            return Ok(());
+        }
+        if chain_ids.len() > MAX_ADDITIONAL_CHAINS {
+            return Err(RunError::InvalidChains(format!(
+                "A run can attest on at most {} additional chains",
+                MAX_ADDITIONAL_CHAINS
+            )));
+        }
+        for (i, chain_id) in chain_ids.iter().enumerate() {
+            if *chain_id == self.chain_id || chain_ids[..i].contains(chain_id) {
+                return Err(RunError::InvalidChains(format!(
+                    "Chain {} is included more than once",
+                    chain_id
+                )));
+            }
+        }
+
+        self.additional_chains = Some(
+            chains
+                .iter()
+                .map(|chain| RunChainAttestation {
+                    chain_id: chain.chain_id,
+                    attestation_transaction_hash: None,
+                    attestation_uid: None,
+                    error: None,
+                    gas: Some(chain.gas.clone()),
+                    base_fee_per_gas: Some(chain.base_fee_per_gas.clone()),
+                    max_priority_fee_per_gas: Some(chain.max_priority_fee_per_gas.clone()),
+                })
+                .collect(),
+        );
+        Ok(())
+    }
+
+    /// Sets the recipient of the attestation and the subject of the queries,
+    /// as far as the recipient rule of the recipe allows. Addresses equal to
+    /// the creator are always allowed.
+    pub fn set_recipient(
+        &mut self,
+        rule: RecipeRecipientRule,
+        recipient: Option<&EthAddress>,
+        subject: Option<&EthAddress>,
+    ) -> Result<(), RunError> {
+        let recipient = recipient.map(|r| r.as_str()).unwrap_or(&self.creator);
+        let subject = subject.map(|s| s.as_str()).unwrap_or(recipient);
+
+        match rule {
+            RecipeRecipientRule::PayerOnly if recipient != self.creator => {
+                return Err(RunError::InvalidRecipient(
+                    "Recipe only attests for the paying user".to_string(),
+                ));
+            }
+            RecipeRecipientRule::PayerOnly | RecipeRecipientRule::SubjectIsRecipient
+                if subject != recipient =>
+            {
+                return Err(RunError::InvalidRecipient(
+                    "Recipe only attests to data about the recipient".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        self.recipient = (recipient != self.creator).then(|| recipient.to_string());
+        self.subject = (subject != recipient).then(|| subject.to_string());
+        Ok(())
+    }
+
+    pub fn is_offchain(&self) -> bool {
+        self.offchain.unwrap_or(false)
+    }
+
+    /// A delegated attestation is prepared and waits for the signature of the
+    /// attester.
+    pub fn is_awaiting_delegation_signature(&self) -> bool {
+        self.error.is_none()
+            && self.attestation_transaction_hash.is_none()
+            && self
+                .delegation
+                .as_ref()
+                .is_some_and(|delegation| delegation.submitted.is_none())
+    }
+
+    pub fn additional_chain(&self, chain_id: u32) -> Option<&RunChainAttestation> {
+        self.additional_chains
+            .as_ref()
+            .and_then(|chains| chains.iter().find(|chain| chain.chain_id == chain_id))
+    }
+
+    pub fn additional_chain_mut(&mut self, chain_id: u32) -> Option<&mut RunChainAttestation> {
+        self.additional_chains
+            .as_mut()
+            .and_then(|chains| chains.iter_mut().find(|chain| chain.chain_id == chain_id))
+    }
+
+    /// The uid of the attestation of this run on a chain.
+    pub fn attestation_uid_on_chain(&self, chain_id: u32) -> Option<&str> {
+        if chain_id == self.chain_id {
+            return self.attestation_uid.as_deref();
+        }
+        self.additional_chain(chain_id)
+            .and_then(|chain| chain.attestation_uid.as_deref())
+    }
+
+    pub fn recipient(&self) -> &str {
+        self.recipient.as_deref().unwrap_or(&self.creator)
+    }
+
+    pub fn subject(&self) -> &str {
+        self.subject.as_deref().unwrap_or(self.recipient())
+    }
+
     pub fn status(&self) -> RunStatus {
+        if self.revoked.is_some() {
+            return RunStatus::AttestationRevoked;
+        }
+        if self.revocation_started.is_some() {
+            return RunStatus::AttestationRevocationPending;
+        }
         if self.attestation_uid.is_some() {
             return RunStatus::AttestationUidConfirmed;
         }
@@ -1,6 +1,8 @@
 use anyhow::{bail, Result};
 use candid::Nat;
 
+use crate::{eas::onchain_query, recipe::Recipe};
+
 pub fn vec_to_run_id(bytes: Vec<u8>) -> Result<[u8; 12], String> {
     if bytes.len() == 12 {
         let mut array = [0u8; 12];
@@ -32,3 +34,27 @@ pub fn get_cyclesfee_for_chain(chain_id: u32) -> Result<Nat> {
     };
     Ok(Nat::from(fee))
 }
+
+/// Fee for each EVM RPC call made by the on-chain queries of a run.
+pub fn get_rpc_call_fee_for_chain(chain_id: u32) -> Result<Nat> {
+    let fee: u64 = match chain_id {
+        11155111 => 100000000000000, // Sepolia, 0.0001 ETH
+        10 => 10000000000000,        // Optimism, 0.00001 ETH
+        8453 => 10000000000000,      // Base, 0.00001 ETH
+        42161 => 10000000000000,     // Arbitrum One, 0.00001 ETH
+        _ => bail!("Chain not supported"),
+    };
+    Ok(Nat::from(fee))
+}
+
+/// Fee for the EVM RPC calls the on-chain queries of the recipe can make when
+/// run once.
+pub fn get_onchain_query_fee(recipe: &Recipe, chain_id: u32) -> Result<Nat> {
+    let rpc_calls: u32 = recipe
+        .queries
+        .iter()
+        .filter_map(|query| query.onchain.as_ref())
+        .map(onchain_query::max_rpc_calls)
+        .sum();
+    Ok(get_rpc_call_fee_for_chain(chain_id)? * Nat::from(rpc_calls))
+}
@@ -1,8 +1,18 @@
 use crate::{
+    batch::tasks::{
+        create_attestations::CreateBatchAttestationsExecutor,
+        get_attestation_uids::GetBatchAttestationUidsExecutor,
+        register_payment::RegisterBatchPaymentExecutor,
+    },
     logger,
+    recipe::tasks::verify_schema::VerifyRecipeSchemaExecutor,
     run::tasks::{
+        chain_attestation::{CreateChainAttestationExecutor, GetChainAttestationUidExecutor},
+        confirm_revocation::ConfirmRunRevocationExecutor,
         create_attestation::CreateAttestationExecutor,
-        get_attestation_uid::GetAttestationUidExecutor, register_payment::RegisterPaymentExecutor,
+        expire_delegation::ExpireRunDelegationExecutor,
+        get_attestation_uid::GetAttestationUidExecutor,
+        register_payment::RegisterPaymentExecutor,
     },
     TASKS,
 };
@@ -28,6 +38,14 @@ pub enum TaskType {
     ProcessRunPayment,
     CreateAttestation,
     GetAttestationUid,
+    ProcessBatchPayment,
+    CreateBatchAttestations,
+    GetBatchAttestationUids,
+    VerifyRecipeSchema,
+    ExpireRunDelegation,
+    ConfirmRunRevocation,
+    CreateChainAttestation,
+    GetChainAttestationUid,
 }
 
 #[derive(CandidType, Deserialize, Debug, Clone)]
@@ -60,6 +78,14 @@ fn get_executor_for_task(task: &Task) -> Box<dyn TaskExecutor> {
         TaskType::ProcessRunPayment => Box::new(RegisterPaymentExecutor {}),
         TaskType::CreateAttestation => Box::new(CreateAttestationExecutor {}),
         TaskType::GetAttestationUid => Box::new(GetAttestationUidExecutor {}),
+        TaskType::ProcessBatchPayment => Box::new(RegisterBatchPaymentExecutor {}),
+        TaskType::CreateBatchAttestations => Box::new(CreateBatchAttestationsExecutor {}),
+        TaskType::GetBatchAttestationUids => Box::new(GetBatchAttestationUidsExecutor {}),
+        TaskType::VerifyRecipeSchema => Box::new(VerifyRecipeSchemaExecutor {}),
+        TaskType::ExpireRunDelegation => Box::new(ExpireRunDelegationExecutor {}),
+        TaskType::ConfirmRunRevocation => Box::new(ConfirmRunRevocationExecutor {}),
+        TaskType::CreateChainAttestation => Box::new(CreateChainAttestationExecutor {}),
+        TaskType::GetChainAttestationUid => Box::new(GetChainAttestationUidExecutor {}),
     }
 }
 
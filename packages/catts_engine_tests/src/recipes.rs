@@ -1,4 +1,4 @@
-use crate::types::{RecipeDetailsInput, RecipeQuery};
+use crate::types::{RecipeDetailsInput, RecipeQuery, RecipeQueryBody};
 
 pub fn recipe_eu_gtc_passport_clone() -> (RecipeDetailsInput, String) {
     let details = RecipeDetailsInput {
@@ -6,8 +6,11 @@ pub fn recipe_eu_gtc_passport_clone() -> (RecipeDetailsInput, String) {
         description: None,
         keywords: None,
         queries: vec![RecipeQuery {
-            endpoint: "https://optimism.easscan.org/graphql".to_string(),
-            query: r#"
+            url: "https://optimism.easscan.org/graphql".to_string(),
+            headers: None,
+            filter: None,
+            body: Some(RecipeQueryBody {
+                query: r#"
               query PassportQuery($where: AttestationWhereInput, $take: Int)
               {
                 attestations(where: $where, take: $take)
@@ -16,8 +19,8 @@ pub fn recipe_eu_gtc_passport_clone() -> (RecipeDetailsInput, String) {
                 }
               }
             "#
-            .to_string(),
-            variables: r#"
+                .to_string(),
+                variables: r#"
               {
                 where: {
                   schemaId: {
@@ -32,7 +35,9 @@ pub fn recipe_eu_gtc_passport_clone() -> (RecipeDetailsInput, String) {
                 take: 1,
               }
             "#
-            .to_string(),
+                .to_string(),
+            }),
+            onchain: None,
         }],
         processor: r#"
             if (!queryResult[0].attestations[0]) {
@@ -55,7 +60,14 @@ pub fn recipe_eu_gtc_passport_clone() -> (RecipeDetailsInput, String) {
             return JSON.stringify(data);
         "#
         .to_string(),
+        processor_limits: None,
+        stdlib_version: None,
+        tests: None,
         resolver: "0x0000000000000000000000000000000000000000".to_string(),
+        validity_seconds: None,
+        recipient_rule: None,
+        delegated_attester: None,
+        chain_ids: None,
         schema: "uint256 score,uint32 scorer_id,uint8 score_decimals".to_string(),
         revokable: false,
     };
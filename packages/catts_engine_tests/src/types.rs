@@ -43,6 +43,25 @@ pub struct User {
     pub eth_address: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct AttestationVerification {
+    pub uid: String,
+    pub chain_id: u32,
+    pub schema_uid: String,
+    pub recipe_id: Option<RecipeId>,
+    pub attester: String,
+    pub recipient: String,
+    pub time: u64,
+    pub expiration_time: u64,
+    pub revocation_time: u64,
+    pub ref_uid: String,
+    pub revocable: bool,
+    pub data: String,
+    pub decoded_data: Option<String>,
+    pub is_valid: bool,
+    pub errors: Vec<String>,
+}
+
 pub type EthAddressBytes = [u8; 20];
 pub type Uid = String;
 pub type RecipeId = [u8; 12];
@@ -54,29 +73,86 @@ pub enum RecipePublishState {
     Unpublished,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, CandidType)]
+pub enum RecipeRecipientRule {
+    PayerOnly,
+    SubjectIsRecipient,
+    Any,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
-pub struct RecipeQuery {
-    pub endpoint: String,
+pub struct RecipeQueryBody {
     pub query: String,
     pub variables: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Default, CandidType)]
+pub struct RecipeOnchainQuery {
+    pub chain_id: u32,
+    pub uid: Option<String>,
+    pub schema_uid: Option<String>,
+    pub recipient: Option<String>,
+    pub from_block: Option<u64>,
+    pub take: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RecipeQuery {
+    pub url: String,
+    pub headers: Option<String>,
+    pub filter: Option<String>,
+    pub body: Option<RecipeQueryBody>,
+    pub onchain: Option<RecipeOnchainQuery>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, CandidType)]
+pub struct ProcessorLimits {
+    pub max_instructions: u64,
+    pub max_memory_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RecipeProcessorTest {
+    pub name: String,
+    pub query_result: String,
+    pub expected_output: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
 pub struct Recipe {
     pub id: RecipeId,
     pub name: String,
-    pub created: u64,
+    pub creator: String,
+    pub maintainers: Option<Vec<String>>,
+    pub pending_creator: Option<String>,
+    pub created: u32,
     pub description: Option<String>,
     pub keywords: Option<Vec<String>>,
     pub queries: Vec<RecipeQuery>,
     pub processor: String,
+    pub processor_limits: Option<ProcessorLimits>,
+    pub stdlib_version: Option<u32>,
+    pub tests: Option<Vec<RecipeProcessorTest>>,
     pub schema: Uid,
     pub resolver: String,
+    pub validity_seconds: Option<u64>,
+    pub recipient_rule: Option<RecipeRecipientRule>,
+    pub delegated_attester: Option<String>,
+    pub chain_ids: Option<Vec<u32>>,
+    pub schema_registrations: Option<Vec<RecipeSchemaRegistration>>,
     pub revokable: bool,
-    pub gas: Option<Nat>,
     pub publish_state: RecipePublishState,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RecipeSchemaRegistration {
+    pub chain_id: u32,
+    pub uid: String,
+    pub registered: bool,
+    pub transaction_hash: Option<String>,
+    pub checked: Option<u32>,
+}
+
 #[derive(Serialize, Deserialize, Debug, CandidType)]
 pub struct RecipeDetailsInput {
     pub name: String,
@@ -84,7 +160,199 @@ pub struct RecipeDetailsInput {
     pub keywords: Option<Vec<String>>,
     pub queries: Vec<RecipeQuery>,
     pub processor: String,
+    pub processor_limits: Option<ProcessorLimits>,
+    pub stdlib_version: Option<u32>,
+    pub tests: Option<Vec<RecipeProcessorTest>>,
     pub schema: String,
     pub resolver: String,
+    pub validity_seconds: Option<u64>,
+    pub recipient_rule: Option<RecipeRecipientRule>,
+    pub delegated_attester: Option<String>,
+    pub chain_ids: Option<Vec<u32>>,
     pub revokable: bool,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub enum RecipeListSortBy {
+    Created,
+    Name,
+    RunCount,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, CandidType)]
+pub struct RecipeListFilters {
+    pub publish_state: Option<RecipePublishState>,
+    pub creator: Option<String>,
+    pub keyword: Option<String>,
+    pub chain_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, CandidType)]
+pub struct RecipeListOptions {
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub filters: Option<RecipeListFilters>,
+    pub sort_by: Option<RecipeListSortBy>,
+    pub sort_order: Option<SortOrder>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RecipeListResponse {
+    pub recipes: Vec<Recipe>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RecipeStats {
+    pub runs_created: u32,
+    pub runs_paid: u32,
+    pub runs_attested: u32,
+    pub runs_failed: u32,
+    pub unique_users: u32,
+    pub last_run: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RecipeTrendingItem {
+    pub recipe: Recipe,
+    pub runs: u32,
+}
+
+pub type BatchId = [u8; 12];
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct BatchRecipient {
+    pub address: String,
+    pub attestation_transaction_hash: Option<String>,
+    pub attestation_uid: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct Batch {
+    pub id: BatchId,
+    pub recipe_id: RecipeId,
+    pub creator: String,
+    pub created: u32,
+    pub chain_id: u32,
+    pub recipients: Vec<BatchRecipient>,
+    pub gas_per_recipient: Nat,
+    pub user_fee: Nat,
+    pub payment_transaction_hash: Option<String>,
+    pub is_cancelled: bool,
+    pub error: Option<String>,
+}
+
+pub type RunId = [u8; 12];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, CandidType)]
+pub enum RunStatus {
+    PaymentPending,
+    PaymentRegistered,
+    PaymentVerified,
+    AttestationCreated,
+    AttestationUidConfirmed,
+    AttestationRevocationPending,
+    AttestationRevoked,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct Run {
+    pub id: RunId,
+    pub recipe_id: RecipeId,
+    pub creator: String,
+    pub created: u32,
+    pub chain_id: u32,
+    pub recipient: Option<String>,
+    pub subject: Option<String>,
+    pub offchain: Option<bool>,
+    pub gas: Option<Nat>,
+    pub user_fee: Option<Nat>,
+    pub attestation_transaction_hash: Option<String>,
+    pub attestation_uid: Option<String>,
+    pub ref_run_id: Option<RunId>,
+    pub ref_uid: Option<String>,
+    pub revocation_started: Option<u32>,
+    pub revocation_transaction_hash: Option<String>,
+    pub revoked: Option<u32>,
+    pub additional_chains: Option<Vec<RunChainAttestation>>,
+    pub is_cancelled: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RunChainAttestation {
+    pub chain_id: u32,
+    pub attestation_transaction_hash: Option<String>,
+    pub attestation_uid: Option<String>,
+    pub error: Option<String>,
+    pub gas: Option<Nat>,
+    pub base_fee_per_gas: Option<Nat>,
+    pub max_priority_fee_per_gas: Option<Nat>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RunChainInput {
+    pub chain_id: u32,
+    pub base_fee_per_gas: Nat,
+    pub max_priority_fee_per_gas: Nat,
+    pub gas: Nat,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, CandidType)]
+pub struct RunCreateOptions {
+    pub refresh_run_id: Option<RunId>,
+    pub recipient: Option<String>,
+    pub subject: Option<String>,
+    pub offchain: Option<bool>,
+    pub additional_chains: Option<Vec<RunChainInput>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, CandidType)]
+pub struct RunListOptions {
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub status: Option<RunStatus>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RunListResponse {
+    pub runs: Vec<Run>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, CandidType)]
+pub enum RecipeSimulateInput {
+    RecipeId(RecipeId),
+    Details(Box<RecipeDetailsInput>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct DecodedSchemaItem {
+    pub name: String,
+    pub type_name: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RecipeSimulateResult {
+    pub query_results: Vec<String>,
+    pub processor_output: Option<String>,
+    pub schema_values: Option<Vec<DecodedSchemaItem>>,
+    pub attestation_data: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RecipeTestResult {
+    pub name: String,
+    pub passed: bool,
+    pub output: String,
+    pub error: Option<String>,
+}
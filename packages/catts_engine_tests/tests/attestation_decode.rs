@@ -0,0 +1,39 @@
+use candid::{encode_args, Principal};
+use catts_engine_tests::{
+    common::{catts_query, setup},
+    types::RpcResult,
+};
+
+fn decode(schema: &str, data_hex: &str) -> RpcResult<String> {
+    let (ic, _, catts) = setup();
+    catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "attestation_decode",
+        encode_args((schema, data_hex)).unwrap(),
+    )
+}
+
+#[test]
+fn test_attestation_decode() {
+    // uint256 1, bool true, int16 -300
+    let data_hex = format!("0x{:064x}{:064x}{}fed4", 1, 1, "f".repeat(60));
+    let response = decode("uint256 score,bool verified,int16 delta", &data_hex);
+    assert_eq!(
+        response.unwrap_ok(),
+        r#"[{"name":"score","type":"uint256","value":"1"},{"name":"verified","type":"bool","value":true},{"name":"delta","type":"int16","value":"-300"}]"#
+    );
+}
+
+#[test]
+fn test_attestation_decode_invalid_schema() {
+    let response = decode("uint7 score", &format!("0x{:064x}", 1));
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn test_attestation_decode_invalid_data() {
+    let response = decode("uint256 score,bool verified", "0x01");
+    assert_eq!(response.unwrap_err().code, 400);
+}
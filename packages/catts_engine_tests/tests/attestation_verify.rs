@@ -0,0 +1,62 @@
+use candid::{encode_args, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    siwe::full_login,
+    types::{AttestationVerification, RpcResult},
+};
+use ic_agent::Identity;
+
+const CHAIN_ID: u32 = 10;
+
+fn verify(chain_id: u32, uid: &str) -> RpcResult<AttestationVerification> {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "attestation_verify",
+        encode_args((chain_id, uid)).unwrap(),
+    )
+}
+
+#[test]
+fn test_attestation_verify_unauthorized() {
+    let (ic, _, catts) = setup();
+    let response: RpcResult<AttestationVerification> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "attestation_verify",
+        encode_args((CHAIN_ID, format!("0x{:064x}", 1))).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
+
+#[test]
+fn test_attestation_verify_unsupported_chain() {
+    let response = verify(1234, &format!("0x{:064x}", 1));
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn test_attestation_verify_invalid_uid() {
+    let response = verify(CHAIN_ID, "0x01");
+    assert_eq!(response.unwrap_err().code, 400);
+
+    let response = verify(CHAIN_ID, "not a uid");
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn test_attestation_verify_not_created_by_catts() {
+    // Attestations not recorded by a run or batch are rejected without reading
+    // the chain, whatever their schema or attester
+    let response = verify(CHAIN_ID, &format!("0x{:064x}", 1));
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 404);
+    assert_eq!(
+        error.details.as_deref(),
+        Some("Attestation was not created by C-ATTS")
+    );
+}
@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use candid::{encode_args, encode_one, Nat, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{Batch, BatchId, Recipe, RecipeId, RecipeRecipientRule, RpcResult},
+};
+use ic_agent::Identity;
+use pocket_ic::PocketIc;
+
+const CHAIN_ID: u32 = 10;
+
+fn address(n: u32) -> String {
+    format!("0x{:040x}", n)
+}
+
+fn create_published_recipe(
+    ic: &PocketIc,
+    catts: Principal,
+    sender: Principal,
+    recipient_rule: Option<RecipeRecipientRule>,
+) -> Recipe {
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.recipient_rule = recipient_rule;
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    let recipe = response.unwrap_ok().clone();
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    response.unwrap_ok().clone()
+}
+
+fn create_batch(
+    ic: &PocketIc,
+    catts: Principal,
+    sender: Principal,
+    recipe_id: RecipeId,
+    recipients: Vec<String>,
+) -> RpcResult<Batch> {
+    catts_update(
+        ic,
+        catts,
+        sender,
+        "batch_create",
+        encode_args((
+            recipe_id,
+            CHAIN_ID,
+            recipients,
+            Nat::from(1_000_000_u64),
+            Nat::from(1_000_000_u64),
+            Nat::from(100_000_u64),
+        ))
+        .unwrap(),
+    )
+}
+
+#[test]
+fn test_batch_create() {
+    let (ic, siwe, catts) = setup();
+    let (creator, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        Some(RecipeRecipientRule::SubjectIsRecipient),
+    );
+
+    let recipients: Vec<String> = (1..=60).map(address).collect();
+    let response = create_batch(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        recipients.clone(),
+    );
+    let batch = response.unwrap_ok().clone();
+    assert_eq!(batch.recipe_id, recipe.id);
+    assert_eq!(batch.creator, creator.to_lowercase());
+    assert_eq!(batch.recipients.len(), 60);
+    assert_eq!(batch.recipients[0].address, recipients[0]);
+    assert!(batch.recipients[0].attestation_uid.is_none());
+
+    // Batch ids are derived from the creation time
+    ic.advance_time(Duration::from_secs(1));
+
+    // A single quote covers every recipient
+    let response = create_batch(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        recipients[..1].to_vec(),
+    );
+    assert!(batch.user_fee > response.unwrap_ok().user_fee);
+
+    let response: RpcResult<Batch> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "batch_get",
+        encode_one(batch.id).unwrap(),
+    );
+    assert_eq!(response.unwrap_ok().id, batch.id);
+}
+
+#[test]
+fn test_batch_create_payer_only_recipe() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap(), None);
+
+    let response = create_batch(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        vec![address(1), address(2)],
+    );
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    assert!(error
+        .details
+        .as_deref()
+        .unwrap()
+        .contains("only attests for the paying user"));
+}
+
+#[test]
+fn test_batch_create_invalid_recipients() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        Some(RecipeRecipientRule::Any),
+    );
+
+    for recipients in [
+        vec![],
+        vec![address(1), "0x123".to_string()],
+        vec![address(1), address(1)],
+        (0..=500).map(address).collect(),
+    ] {
+        let response = create_batch(
+            &ic,
+            catts,
+            identity.sender().unwrap(),
+            recipe.id,
+            recipients,
+        );
+        assert_eq!(response.unwrap_err().code, 400);
+    }
+}
+
+#[test]
+fn test_batch_cancel() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        Some(RecipeRecipientRule::Any),
+    );
+    let response = create_batch(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        vec![address(1)],
+    );
+    let batch = response.unwrap_ok().clone();
+
+    let response: RpcResult<Batch> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "batch_cancel",
+        encode_one(batch.id).unwrap(),
+    );
+    assert!(response.unwrap_ok().is_cancelled);
+}
+
+#[test]
+fn test_batch_get_not_found() {
+    let (ic, _, catts) = setup();
+    let batch_id: BatchId = [0; 12];
+    let response: RpcResult<Batch> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "batch_get",
+        encode_one(batch_id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
@@ -0,0 +1,78 @@
+use candid::{encode_args, encode_one};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{ProcessorLimits, Recipe, RecipeProcessorTest, RecipeTestResult, RpcResult},
+};
+use ic_agent::Identity;
+
+#[test]
+fn test_processor_default_limits_recorded() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+    assert!(recipe.processor_limits.is_some());
+}
+
+#[test]
+fn test_processor_limits_invalid() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.processor_limits = Some(ProcessorLimits {
+        max_instructions: 0,
+        max_memory_bytes: 1024,
+    });
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn test_processor_instruction_limit_exceeded() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.processor = "let i = 0; while (i < 1000000) { i++; } return \"[]\";".to_string();
+    details.processor_limits = Some(ProcessorLimits {
+        max_instructions: 1_000,
+        max_memory_bytes: 16 * 1024 * 1024,
+    });
+    details.tests = Some(vec![RecipeProcessorTest {
+        name: "loop".to_string(),
+        query_result: "[]".to_string(),
+        expected_output: "[]".to_string(),
+    }]);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+
+    let response: RpcResult<Vec<RecipeTestResult>> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_run_tests",
+        encode_one(recipe.id).unwrap(),
+    );
+    let results = response.unwrap_ok();
+    assert!(!results[0].passed);
+    assert!(results[0].error.as_ref().unwrap().contains("timed out"));
+}
@@ -0,0 +1,83 @@
+use candid::{encode_args, encode_one};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{Recipe, RecipeProcessorTest, RecipeTestResult, RpcResult},
+};
+use ic_agent::Identity;
+
+const QUERY_RESULT: &str = r#"[{"attestations":[{"decodedDataJson":"[{\"name\":\"score\",\"type\":\"uint256\",\"value\":{\"name\":\"score\",\"type\":\"uint256\",\"value\":{\"type\":\"BigNumber\",\"hex\":\"0x0f\"}}},{\"name\":\"scorer_id\",\"type\":\"uint32\",\"value\":{\"name\":\"scorer_id\",\"type\":\"uint32\",\"value\":335}},{\"name\":\"score_decimals\",\"type\":\"uint8\",\"value\":{\"name\":\"score_decimals\",\"type\":\"uint8\",\"value\":18}}]"}]}]"#;
+
+const STDLIB_PROCESSOR: &str = r#"
+    const data = catts.eas.decodedData(catts.eas.attestations(queryResult)[0]);
+    const recipient = catts.address.toChecksum("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+    console.log(recipient);
+    return catts.schema.stringify(
+        catts.schema.items("uint256 score,uint32 scorer_id,uint8 score_decimals", {
+            score: catts.bigint.toHex(data.score),
+            scorer_id: data.scorer_id,
+            score_decimals: data.score_decimals,
+        })
+    );
+"#;
+
+fn run_single_test(stdlib_version: Option<u32>) -> RecipeTestResult {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.processor = STDLIB_PROCESSOR.to_string();
+    details.stdlib_version = stdlib_version;
+    details.tests = Some(vec![RecipeProcessorTest {
+        name: "stdlib".to_string(),
+        query_result: QUERY_RESULT.to_string(),
+        expected_output: r#"[{"name":"score","type":"uint256","value":"0xf"},{"name":"scorer_id","type":"uint32","value":335},{"name":"score_decimals","type":"uint8","value":18}]"#.to_string(),
+    }]);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+    assert_eq!(recipe.stdlib_version, stdlib_version);
+
+    let response: RpcResult<Vec<RecipeTestResult>> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_run_tests",
+        encode_one(recipe.id).unwrap(),
+    );
+    response.unwrap_ok()[0].clone()
+}
+
+#[test]
+fn test_processor_stdlib_v1() {
+    let result = run_single_test(Some(1));
+    assert!(result.passed, "{:?}", result);
+}
+
+#[test]
+fn test_processor_without_stdlib() {
+    let result = run_single_test(None);
+    assert!(!result.passed);
+    assert!(result.error.unwrap().contains("catts"));
+}
+
+#[test]
+fn test_processor_stdlib_unsupported_version() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.stdlib_version = Some(999);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
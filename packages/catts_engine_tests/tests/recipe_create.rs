@@ -4,10 +4,12 @@ use catts_engine_tests::{
     common::{catts_update, setup},
     recipes::recipe_eu_gtc_passport_clone,
     siwe::full_login,
-    types::{Recipe, RpcResult},
+    types::{Recipe, RecipeOnchainQuery, RecipeQuery, RpcResult},
 };
 use ic_agent::Identity;
 
+const SCHEMA_UID: &str = "0x6ab5d34260fca0cfcf0e76e96d439cace6aa7c3c019d7c4580ed52c6845e9c89";
+
 #[test]
 fn test_recipe_create_unauthorized() {
     let (ic, _, catts) = setup();
@@ -35,6 +37,22 @@ fn test_recipe_create() {
     assert!(response.is_ok());
 }
 
+#[test]
+fn test_recipe_create_revokable() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.revokable = true;
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    assert!(response.unwrap_ok().revokable);
+}
+
 #[test]
 fn recipe_create_name_too_short() {
     let (ic, siwe, catts) = setup();
@@ -371,3 +389,169 @@ fn recipe_create_already_published() {
     let details = error.details.as_deref().expect("No error details found");
     assert_eq!(details, "Only drafts can be updated");
 }
+
+#[test]
+fn recipe_create_schema_invalid_type() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let mut args = recipe_eu_gtc_passport_clone();
+    args.0.schema = "uint256 score,uint7 scorer_id".to_string();
+    let args = encode_args(args).unwrap();
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        args,
+    );
+    assert!(response.is_err());
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    let details = error.details.as_deref().expect("No error details found");
+    assert!(
+        details.contains("Field 2 has an unsupported type \"uint7\""),
+        "{}",
+        details
+    );
+}
+
+#[test]
+fn recipe_create_delegated_attester_invalid_address() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let mut args = recipe_eu_gtc_passport_clone();
+    args.0.delegated_attester = Some("0x123".to_string());
+    let args = encode_args(args).unwrap();
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        args,
+    );
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    let details = error.details.as_deref().expect("No error details found");
+    assert_starts_with!(details, "delegated_attester: ".to_string());
+}
+
+#[test]
+fn recipe_create_chain_ids_unsupported_chain() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    for chain_ids in [vec![], vec![10, 1234], vec![10, 10]] {
+        let mut args = recipe_eu_gtc_passport_clone();
+        args.0.chain_ids = Some(chain_ids);
+        let response: RpcResult<Recipe> = catts_update(
+            &ic,
+            catts,
+            identity.sender().unwrap(),
+            "recipe_create",
+            encode_args(args).unwrap(),
+        );
+        let error = response.unwrap_err();
+        assert_eq!(error.code, 400);
+        let details = error.details.as_deref().expect("No error details found");
+        assert_starts_with!(details, "chain_ids: ".to_string());
+    }
+}
+
+#[test]
+fn recipe_create_onchain_query() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let mut args = recipe_eu_gtc_passport_clone();
+    args.0.queries = vec![RecipeQuery {
+        url: "".to_string(),
+        headers: None,
+        filter: None,
+        body: None,
+        onchain: Some(RecipeOnchainQuery {
+            chain_id: 10,
+            schema_uid: Some(SCHEMA_UID.to_string()),
+            recipient: Some("{user_eth_address}".to_string()),
+            take: Some(1),
+            ..Default::default()
+        }),
+    }];
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(args).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+    let onchain = recipe.queries[0].onchain.as_ref().unwrap();
+    assert_eq!(onchain.schema_uid.as_deref(), Some(SCHEMA_UID));
+}
+
+#[test]
+fn recipe_create_onchain_query_invalid() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let invalid_queries = [
+        // Both a uid and a schema uid
+        RecipeOnchainQuery {
+            chain_id: 10,
+            uid: Some(SCHEMA_UID.to_string()),
+            schema_uid: Some(SCHEMA_UID.to_string()),
+            ..Default::default()
+        },
+        // Neither a uid nor a schema uid
+        RecipeOnchainQuery {
+            chain_id: 10,
+            ..Default::default()
+        },
+        RecipeOnchainQuery {
+            chain_id: 1234,
+            uid: Some(SCHEMA_UID.to_string()),
+            ..Default::default()
+        },
+        RecipeOnchainQuery {
+            chain_id: 10,
+            uid: Some("0x01".to_string()),
+            ..Default::default()
+        },
+        RecipeOnchainQuery {
+            chain_id: 10,
+            schema_uid: Some(SCHEMA_UID.to_string()),
+            take: Some(11),
+            ..Default::default()
+        },
+    ];
+    for onchain in invalid_queries {
+        let mut args = recipe_eu_gtc_passport_clone();
+        args.0.queries = vec![RecipeQuery {
+            url: "".to_string(),
+            headers: None,
+            filter: None,
+            body: None,
+            onchain: Some(onchain),
+        }];
+        let response: RpcResult<Recipe> = catts_update(
+            &ic,
+            catts,
+            identity.sender().unwrap(),
+            "recipe_create",
+            encode_args(args).unwrap(),
+        );
+        assert_eq!(response.unwrap_err().code, 400);
+    }
+
+    // On-chain queries are not sent to the query proxy
+    let mut args = recipe_eu_gtc_passport_clone();
+    args.0.queries[0].onchain = Some(RecipeOnchainQuery {
+        chain_id: 10,
+        schema_uid: Some(SCHEMA_UID.to_string()),
+        ..Default::default()
+    });
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(args).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
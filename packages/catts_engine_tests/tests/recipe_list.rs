@@ -3,22 +3,26 @@ use catts_engine_tests::{
     common::{catts_query, catts_update, setup},
     recipes::recipe_eu_gtc_passport_clone,
     siwe::full_login,
-    types::{Recipe, RpcResult},
+    types::{
+        Recipe, RecipeListFilters, RecipeListOptions, RecipeListResponse, RecipeListSortBy,
+        RpcResult, SortOrder,
+    },
 };
 use ic_agent::Identity;
 
 #[test]
 fn test_recipe_empty_list() {
     let (ic, _, catts) = setup();
-    let response: RpcResult<Vec<Recipe>> = catts_query(
+    let response: RpcResult<RecipeListResponse> = catts_query(
         &ic,
         catts,
         Principal::anonymous(),
         "recipe_list",
-        encode_one(()).unwrap(),
+        encode_one(None::<RecipeListOptions>).unwrap(),
     );
-    assert!(response.is_ok());
-    assert_eq!(response.unwrap_ok().len(), 0);
+    let list = response.unwrap_ok();
+    assert_eq!(list.recipes.len(), 0);
+    assert_eq!(list.next_cursor, None);
 }
 
 #[test]
@@ -35,12 +39,142 @@ fn test_recipe_list() {
     );
     assert!(response.is_ok());
 
-    let response: RpcResult<Vec<Recipe>> =
-        catts_query(&ic, catts, sender, "recipe_list", encode_one(()).unwrap());
-    assert!(response.is_ok());
+    let response: RpcResult<RecipeListResponse> = catts_query(
+        &ic,
+        catts,
+        sender,
+        "recipe_list",
+        encode_one(None::<RecipeListOptions>).unwrap(),
+    );
     assert_eq!(
-        response.unwrap_ok().len(),
+        response.unwrap_ok().recipes.len(),
         1,
         "List should contain one recipe"
     );
 }
+
+#[test]
+fn test_recipe_list_paginated() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let sender = identity.sender().unwrap();
+    for name in ["recipe-a", "recipe-b", "recipe-c"] {
+        let (mut details, readme) = recipe_eu_gtc_passport_clone();
+        details.name = name.to_string();
+        let response: RpcResult<Recipe> = catts_update(
+            &ic,
+            catts,
+            sender,
+            "recipe_create",
+            encode_args((details, readme)).unwrap(),
+        );
+        assert!(response.is_ok());
+    }
+
+    let options = RecipeListOptions {
+        limit: Some(2),
+        sort_by: Some(RecipeListSortBy::Name),
+        sort_order: Some(SortOrder::Desc),
+        ..Default::default()
+    };
+    let response: RpcResult<RecipeListResponse> = catts_query(
+        &ic,
+        catts,
+        sender,
+        "recipe_list",
+        encode_one(Some(options.clone())).unwrap(),
+    );
+    let page = response.unwrap_ok();
+    let names: Vec<String> = page.recipes.iter().map(|r| r.name.clone()).collect();
+    assert_eq!(names, vec!["recipe-c", "recipe-b"]);
+    assert!(page.next_cursor.is_some());
+
+    let response: RpcResult<RecipeListResponse> = catts_query(
+        &ic,
+        catts,
+        sender,
+        "recipe_list",
+        encode_one(Some(RecipeListOptions {
+            cursor: page.next_cursor.clone(),
+            ..options
+        }))
+        .unwrap(),
+    );
+    let page = response.unwrap_ok();
+    let names: Vec<String> = page.recipes.iter().map(|r| r.name.clone()).collect();
+    assert_eq!(names, vec!["recipe-a"]);
+    assert_eq!(page.next_cursor, None);
+}
+
+#[test]
+fn test_recipe_list_filter_by_creator() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let (address2, _) = full_login(&ic, siwe, catts, None);
+    let sender = identity.sender().unwrap();
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        sender,
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    assert!(response.is_ok());
+
+    for (creator, expected) in [(address, 1), (address2, 0)] {
+        let options = RecipeListOptions {
+            filters: Some(RecipeListFilters {
+                creator: Some(creator),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let response: RpcResult<RecipeListResponse> = catts_query(
+            &ic,
+            catts,
+            sender,
+            "recipe_list",
+            encode_one(Some(options)).unwrap(),
+        );
+        assert_eq!(response.unwrap_ok().recipes.len(), expected);
+    }
+}
+
+#[test]
+fn test_recipe_list_filter_by_chain() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let sender = identity.sender().unwrap();
+    for (name, chain_ids) in [("recipe-a", Some(vec![10])), ("recipe-b", None)] {
+        let (mut details, readme) = recipe_eu_gtc_passport_clone();
+        details.name = name.to_string();
+        details.chain_ids = chain_ids;
+        let response: RpcResult<Recipe> = catts_update(
+            &ic,
+            catts,
+            sender,
+            "recipe_create",
+            encode_args((details, readme)).unwrap(),
+        );
+        assert!(response.is_ok());
+    }
+
+    // Recipes without chain ids can be run on every configured chain
+    for (chain_id, expected) in [(10, 2), (8453, 1), (1234, 0)] {
+        let options = RecipeListOptions {
+            filters: Some(RecipeListFilters {
+                chain_id: Some(chain_id),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let response: RpcResult<RecipeListResponse> = catts_query(
+            &ic,
+            catts,
+            sender,
+            "recipe_list",
+            encode_one(Some(options)).unwrap(),
+        );
+        assert_eq!(response.unwrap_ok().recipes.len(), expected);
+    }
+}
@@ -0,0 +1,169 @@
+use candid::{encode_args, encode_one};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{Recipe, RecipePublishState, RpcResult},
+};
+use ic_agent::Identity;
+
+#[test]
+fn recipe_transfer_and_accept_ownership() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (address2, identity2) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+
+    let transfer_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_transfer_ownership",
+        encode_args((recipe.id, address2.clone())).unwrap(),
+    );
+    let transferred_recipe = transfer_response.unwrap_ok();
+    assert_eq!(
+        transferred_recipe.pending_creator,
+        Some(address2.to_lowercase())
+    );
+
+    let accept_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity2.sender().unwrap(),
+        "recipe_accept_ownership",
+        encode_one(recipe.id).unwrap(),
+    );
+    let accepted_recipe = accept_response.unwrap_ok();
+    assert_eq!(accepted_recipe.creator, address2.to_lowercase());
+    assert_eq!(accepted_recipe.pending_creator, None);
+
+    // The previous creator can no longer publish
+    let publish_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    let error = publish_response.unwrap_err();
+    assert_eq!(error.code, 401);
+
+    // Nor recreate the recipe under the same name
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    assert_eq!(create_response.unwrap_err().code, 409);
+}
+
+#[test]
+fn recipe_accept_ownership_not_pending() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (address2, _) = full_login(&ic, siwe, catts, None);
+    let (_, identity3) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+
+    let transfer_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_transfer_ownership",
+        encode_args((recipe.id, address2)).unwrap(),
+    );
+    assert!(transfer_response.is_ok());
+
+    let accept_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity3.sender().unwrap(),
+        "recipe_accept_ownership",
+        encode_one(recipe.id).unwrap(),
+    );
+    let error = accept_response.unwrap_err();
+    assert_eq!(error.code, 403);
+}
+
+#[test]
+fn recipe_maintainer_can_publish() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (address2, identity2) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+
+    let maintainers_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_set_maintainers",
+        encode_args((recipe.id, vec![address2.clone()])).unwrap(),
+    );
+    let updated_recipe = maintainers_response.unwrap_ok();
+    assert_eq!(
+        updated_recipe.maintainers,
+        Some(vec![address2.to_lowercase()])
+    );
+
+    let publish_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity2.sender().unwrap(),
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    let published_recipe = publish_response.unwrap_ok();
+    assert_eq!(
+        published_recipe.publish_state,
+        RecipePublishState::Published
+    );
+}
+
+#[test]
+fn recipe_set_maintainers_unauthorized() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (address2, identity2) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+
+    let maintainers_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity2.sender().unwrap(),
+        "recipe_set_maintainers",
+        encode_args((recipe.id, vec![address2])).unwrap(),
+    );
+    let error = maintainers_response.unwrap_err();
+    assert_eq!(error.code, 401);
+}
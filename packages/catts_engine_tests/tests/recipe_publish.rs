@@ -1,6 +1,6 @@
 use candid::{encode_args, encode_one};
 use catts_engine_tests::{
-    common::{catts_update, setup},
+    common::{catts_query, catts_update, setup},
     recipes::recipe_eu_gtc_passport_clone,
     siwe::full_login,
     types::{Recipe, RecipePublishState, RpcResult},
@@ -69,3 +69,47 @@ fn recipe_publish_not_found() {
     let error = publish_response.unwrap_err();
     assert_eq!(error.code, 404);
 }
+
+#[test]
+fn recipe_publish_chain_ids_schema_not_verified() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.chain_ids = Some(vec![8453]);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+
+    // The SchemaRegistry can't be reached, the declared chain stays unverified
+    let publish_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    let error = publish_response.unwrap_err();
+    assert_eq!(error.code, 500);
+    assert!(error
+        .details
+        .as_deref()
+        .unwrap()
+        .contains("Schema could not be checked on chain 8453"));
+
+    let get_response: RpcResult<Recipe> = catts_query(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_get_by_id",
+        encode_one(recipe.id).unwrap(),
+    );
+    assert_eq!(
+        get_response.unwrap_ok().publish_state,
+        RecipePublishState::Draft
+    );
+}
@@ -0,0 +1,129 @@
+use candid::{encode_args, encode_one, Nat, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{Recipe, RecipeId, RpcResult},
+};
+use ic_agent::Identity;
+use pocket_ic::PocketIc;
+
+fn create_published_recipe(ic: &PocketIc, catts: Principal, sender: Principal) -> Recipe {
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = response.unwrap_ok().clone();
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    response.unwrap_ok().clone()
+}
+
+// Canisters created by PocketIc are controlled by the anonymous principal
+fn register_schema_with_gas(
+    ic: &PocketIc,
+    catts: Principal,
+    sender: Principal,
+    recipe_id: RecipeId,
+    chain_id: u32,
+    gas: u64,
+) -> RpcResult<Recipe> {
+    catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_register_schema",
+        encode_args((
+            recipe_id,
+            chain_id,
+            Nat::from(1_000_000_u64),
+            Nat::from(1_000_000_u64),
+            Nat::from(gas),
+        ))
+        .unwrap(),
+    )
+}
+
+fn register_schema(
+    ic: &PocketIc,
+    catts: Principal,
+    sender: Principal,
+    recipe_id: RecipeId,
+    chain_id: u32,
+) -> RpcResult<Recipe> {
+    register_schema_with_gas(ic, catts, sender, recipe_id, chain_id, 100_000)
+}
+
+#[test]
+fn recipe_register_schema_not_checked() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+    assert!(recipe.schema_registrations.is_none());
+
+    // The SchemaRegistry can't be reached in tests, registration stays unchecked
+    let response = register_schema(&ic, catts, Principal::anonymous(), recipe.id, 10);
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    assert!(error
+        .details
+        .as_deref()
+        .unwrap()
+        .contains("has not been checked"));
+}
+
+#[test]
+fn recipe_register_schema_unsupported_chain() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let response = register_schema(&ic, catts, Principal::anonymous(), recipe.id, 1234);
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn recipe_register_schema_forbidden() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    // The canister pays for registrations, recipe creators can't register
+    let response = register_schema(&ic, catts, identity.sender().unwrap(), recipe.id, 10);
+    assert_eq!(response.unwrap_err().code, 403);
+}
+
+#[test]
+fn recipe_register_schema_fee_too_high() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let response = register_schema_with_gas(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        recipe.id,
+        10,
+        1_000_000_000_000,
+    );
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    assert!(error.details.as_deref().unwrap().contains("exceeds"));
+}
+
+#[test]
+fn recipe_register_schema_not_found() {
+    let (ic, _, catts) = setup();
+    let recipe_id: RecipeId = [0; 12];
+    let response = register_schema(&ic, catts, Principal::anonymous(), recipe_id, 10);
+    assert_eq!(response.unwrap_err().code, 404);
+}
@@ -0,0 +1,285 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{Recipe, RecipeProcessorTest, RecipePublishState, RecipeTestResult, RpcResult},
+};
+use ic_agent::Identity;
+
+const QUERY_RESULT: &str = r#"[{"attestations":[{"decodedDataJson":"[{\"name\":\"score\",\"type\":\"uint256\",\"value\":{\"name\":\"score\",\"type\":\"uint256\",\"value\":{\"type\":\"BigNumber\",\"hex\":\"0x01\"}}},{\"name\":\"scorer_id\",\"type\":\"uint32\",\"value\":{\"name\":\"scorer_id\",\"type\":\"uint32\",\"value\":335}},{\"name\":\"score_decimals\",\"type\":\"uint8\",\"value\":{\"name\":\"score_decimals\",\"type\":\"uint8\",\"value\":18}}]"}]}]"#;
+
+fn processor_test(expected_output: &str) -> RecipeProcessorTest {
+    RecipeProcessorTest {
+        name: "score".to_string(),
+        query_result: QUERY_RESULT.to_string(),
+        expected_output: expected_output.to_string(),
+    }
+}
+
+#[test]
+fn test_recipe_run_tests_unauthorized() {
+    let (ic, _, catts) = setup();
+    let response: RpcResult<Vec<RecipeTestResult>> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "recipe_run_tests",
+        encode_one([0u8; 12]).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
+
+#[test]
+fn test_recipe_run_tests_draft_of_other_user() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+
+    let (_, identity2) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<Vec<RecipeTestResult>> = catts_update(
+        &ic,
+        catts,
+        identity2.sender().unwrap(),
+        "recipe_run_tests",
+        encode_one(recipe.id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
+
+#[test]
+fn test_recipe_run_tests_rate_limited() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let run_tests = || -> RpcResult<Vec<RecipeTestResult>> {
+        catts_update(
+            &ic,
+            catts,
+            identity.sender().unwrap(),
+            "recipe_run_tests",
+            encode_one([0u8; 12]).unwrap(),
+        )
+    };
+    for _ in 0..5 {
+        assert_eq!(run_tests().unwrap_err().code, 404);
+    }
+    assert_eq!(run_tests().unwrap_err().code, 429);
+}
+
+#[test]
+fn test_recipe_run_tests_passing() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.tests = Some(vec![processor_test(
+        r#"[{"name":"score","type":"uint256","value":"0x01"},{"name":"scorer_id","type":"uint32","value":335},{"name":"score_decimals","type":"uint8","value":18}]"#,
+    )]);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+
+    let response: RpcResult<Vec<RecipeTestResult>> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_run_tests",
+        encode_one(recipe.id).unwrap(),
+    );
+    let results = response.unwrap_ok();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].passed, "{:?}", results[0]);
+
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    assert_eq!(
+        response.unwrap_ok().publish_state,
+        RecipePublishState::Published
+    );
+}
+
+#[test]
+fn test_recipe_publish_blocked_by_failing_test() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.tests = Some(vec![processor_test(
+        r#"[{"name":"score","type":"uint256","value":"0x02"},{"name":"scorer_id","type":"uint32","value":335},{"name":"score_decimals","type":"uint8","value":18}]"#,
+    )]);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+fn run_single_test(processor: &str) -> RecipeTestResult {
+    let (details, _) = recipe_eu_gtc_passport_clone();
+    run_single_test_with_schema(processor, &details.schema, "[]")
+}
+
+fn run_single_test_with_schema(
+    processor: &str,
+    schema: &str,
+    expected_output: &str,
+) -> RecipeTestResult {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.processor = processor.to_string();
+    details.schema = schema.to_string();
+    details.tests = Some(vec![processor_test(expected_output)]);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+
+    let response: RpcResult<Vec<RecipeTestResult>> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_run_tests",
+        encode_one(recipe.id).unwrap(),
+    );
+    response.unwrap_ok()[0].clone()
+}
+
+#[test]
+fn test_recipe_run_tests_exception() {
+    let result = run_single_test("console.log('before'); throw new Error('boom');");
+    assert!(!result.passed);
+    assert!(result.error.unwrap().contains("boom"));
+}
+
+#[test]
+fn test_recipe_run_tests_not_a_string() {
+    let result = run_single_test("return 42;");
+    assert!(!result.passed);
+    assert!(result.error.unwrap().contains("must return a string"));
+}
+
+#[test]
+fn test_recipe_run_tests_invalid_json() {
+    let result = run_single_test("return 'not json';");
+    assert!(!result.passed);
+    assert!(result.error.unwrap().contains("invalid JSON"));
+}
+
+#[test]
+fn test_recipe_run_tests_schema_mismatch() {
+    let result = run_single_test(
+        r#"return JSON.stringify([{ name: "score", type: "bool", value: true }]);"#,
+    );
+    assert!(!result.passed);
+    let error = result.error.unwrap();
+    assert!(error.contains("does not match the schema"), "{}", error);
+}
+
+const ABI_TYPES_SCHEMA: &str =
+    "int16 delta,uint256 total,bytes4 selector,address[] members,uint8[2] pair,(string label,bool ok) info";
+
+const ABI_TYPES_OUTPUT: &str = r#"[
+    {"name":"delta","type":"int16","value":-300},
+    {"name":"total","type":"uint256","value":"115792089237316195423570985008687907853269984665640564039457584007913129639935"},
+    {"name":"selector","type":"bytes4","value":"0xa9059cbb"},
+    {"name":"members","type":"address[]","value":["0x0000000000000000000000000000000000000001"]},
+    {"name":"pair","type":"uint8[2]","value":[1,"0xff"]},
+    {"name":"info","type":"(string,bool)","value":{"label":"ok","ok":true}}
+]"#;
+
+#[test]
+fn test_recipe_run_tests_abi_types() {
+    let processor = format!("return JSON.stringify({});", ABI_TYPES_OUTPUT);
+    let result = run_single_test_with_schema(&processor, ABI_TYPES_SCHEMA, ABI_TYPES_OUTPUT);
+    assert!(result.passed, "{:?}", result);
+}
+
+#[test]
+fn test_recipe_run_tests_abi_value_out_of_range() {
+    let output = ABI_TYPES_OUTPUT.replace("[1,\"0xff\"]", "[1,256]");
+    let processor = format!("return JSON.stringify({});", output);
+    let result = run_single_test_with_schema(&processor, ABI_TYPES_SCHEMA, &output);
+    assert!(!result.passed);
+    let error = result.error.unwrap();
+    assert!(
+        error.contains("Field \"pair[1]\": 256 is out of range for uint8"),
+        "{}",
+        error
+    );
+}
+
+const EXPIRING_SCHEMA: &str = "bool active";
+
+fn expiring_output(expiration_time: u64) -> String {
+    format!(
+        r#"{{"data":[{{"name":"active","type":"bool","value":true}}],"expirationTime":{}}}"#,
+        expiration_time
+    )
+}
+
+#[test]
+fn test_recipe_run_tests_expiration_time() {
+    // 2100-01-01
+    let output = expiring_output(4_102_444_800);
+    let processor = format!("return JSON.stringify({});", output);
+    let result = run_single_test_with_schema(&processor, EXPIRING_SCHEMA, &output);
+    assert!(result.passed, "{:?}", result);
+}
+
+#[test]
+fn test_recipe_run_tests_expiration_time_in_past() {
+    let output = expiring_output(1);
+    let processor = format!("return JSON.stringify({});", output);
+    let result = run_single_test_with_schema(&processor, EXPIRING_SCHEMA, &output);
+    assert!(!result.passed);
+    let error = result.error.unwrap();
+    assert!(error.contains("not in the future"), "{}", error);
+}
+
+#[test]
+fn test_recipe_validity_seconds_invalid() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.validity_seconds = Some(0);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
@@ -0,0 +1,57 @@
+use candid::{encode_args, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{Recipe, RecipeListResponse, RpcResult},
+};
+use ic_agent::Identity;
+
+#[test]
+fn test_recipe_search() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+
+    // Prefix of a term in the recipe name
+    let response: RpcResult<RecipeListResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "recipe_search",
+        encode_args(("pass", None::<String>)).unwrap(),
+    );
+    let result = response.unwrap_ok();
+    assert_eq!(result.recipes.len(), 1);
+    assert_eq!(result.recipes[0].id, recipe.id);
+
+    // All query terms need to match
+    let response: RpcResult<RecipeListResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "recipe_search",
+        encode_args(("passport unknownterm", None::<String>)).unwrap(),
+    );
+    assert_eq!(response.unwrap_ok().recipes.len(), 0);
+}
+
+#[test]
+fn test_recipe_search_empty_query() {
+    let (ic, _, catts) = setup();
+    let response: RpcResult<RecipeListResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "recipe_search",
+        encode_args(("  ", None::<String>)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
@@ -0,0 +1,107 @@
+use candid::{decode_one, encode_args, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{RecipeSimulateInput, RecipeSimulateResult, RpcResult},
+};
+use ic_agent::Identity;
+use pocket_ic::{
+    common::rest::{CanisterHttpReply, CanisterHttpResponse, MockCanisterHttpResponse},
+    WasmResult,
+};
+
+const QUERY_RESULT: &str = r#"{"attestations":[{"decodedDataJson":"[{\"name\":\"score\",\"type\":\"uint256\",\"value\":{\"name\":\"score\",\"type\":\"uint256\",\"value\":{\"type\":\"BigNumber\",\"hex\":\"0x01\"}}},{\"name\":\"scorer_id\",\"type\":\"uint32\",\"value\":{\"name\":\"scorer_id\",\"type\":\"uint32\",\"value\":335}},{\"name\":\"score_decimals\",\"type\":\"uint8\",\"value\":{\"name\":\"score_decimals\",\"type\":\"uint8\",\"value\":18}}]"}]}"#;
+
+#[test]
+fn test_recipe_simulate_unauthorized() {
+    let (ic, siwe, catts) = setup();
+    let (address, _) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<RecipeSimulateResult> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "recipe_simulate",
+        encode_args((RecipeSimulateInput::RecipeId([0; 12]), address)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
+
+#[test]
+fn test_recipe_simulate_recipe_not_found() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<RecipeSimulateResult> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_simulate",
+        encode_args((RecipeSimulateInput::RecipeId([0; 12]), address)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
+
+#[test]
+fn test_recipe_simulate_rate_limited() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let simulate = || -> RpcResult<RecipeSimulateResult> {
+        catts_update(
+            &ic,
+            catts,
+            identity.sender().unwrap(),
+            "recipe_simulate",
+            encode_args((RecipeSimulateInput::RecipeId([0; 12]), address.clone())).unwrap(),
+        )
+    };
+    for _ in 0..5 {
+        assert_eq!(simulate().unwrap_err().code, 404);
+    }
+    assert_eq!(simulate().unwrap_err().code, 429);
+}
+
+#[test]
+fn test_recipe_simulate() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let (details, _) = recipe_eu_gtc_passport_clone();
+    let message_id = ic
+        .submit_call(
+            catts,
+            identity.sender().unwrap(),
+            "recipe_simulate",
+            encode_args((RecipeSimulateInput::Details(Box::new(details)), address)).unwrap(),
+        )
+        .unwrap();
+
+    // Answer the query outcall with a mocked query result
+    ic.tick();
+    ic.tick();
+    let requests = ic.get_canister_http();
+    assert_eq!(requests.len(), 1);
+    ic.mock_canister_http_response(MockCanisterHttpResponse {
+        subnet_id: requests[0].subnet_id,
+        request_id: requests[0].request_id,
+        response: CanisterHttpResponse::CanisterHttpReply(CanisterHttpReply {
+            status: 200,
+            headers: vec![],
+            body: QUERY_RESULT.as_bytes().to_vec(),
+        }),
+    });
+
+    let response: RpcResult<RecipeSimulateResult> = match ic.await_call(message_id).unwrap() {
+        WasmResult::Reply(data) => decode_one(&data).unwrap(),
+        WasmResult::Reject(message) => panic!("{}", message),
+    };
+    let result = response.unwrap_ok();
+    assert_eq!(result.error, None);
+    assert_eq!(result.query_results, vec![QUERY_RESULT.to_string()]);
+    assert_eq!(
+        result.processor_output.as_deref(),
+        Some(
+            r#"[{"name":"score","type":"uint256","value":"0x01"},{"name":"scorer_id","type":"uint32","value":335},{"name":"score_decimals","type":"uint8","value":18}]"#
+        )
+    );
+    assert!(result.attestation_data.is_some());
+    assert_eq!(result.schema_values.as_ref().unwrap().len(), 3);
+}
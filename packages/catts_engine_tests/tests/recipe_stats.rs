@@ -0,0 +1,61 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{Recipe, RecipeId, RecipeStats, RecipeTrendingItem, RpcResult},
+};
+use ic_agent::Identity;
+
+#[test]
+fn test_recipe_stats_no_runs() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = response.unwrap_ok();
+
+    let response: RpcResult<RecipeStats> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "recipe_stats",
+        encode_one(recipe.id).unwrap(),
+    );
+    let stats = response.unwrap_ok();
+    assert_eq!(stats.runs_created, 0);
+    assert_eq!(stats.unique_users, 0);
+    assert_eq!(stats.last_run, None);
+}
+
+#[test]
+fn test_recipe_stats_not_found() {
+    let (ic, _, catts) = setup();
+    let recipe_id: RecipeId = [0; 12];
+    let response: RpcResult<RecipeStats> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "recipe_stats",
+        encode_one(recipe_id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
+
+#[test]
+fn test_recipe_trending_invalid_window() {
+    let (ic, _, catts) = setup();
+    let response: RpcResult<Vec<RecipeTrendingItem>> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "recipe_trending",
+        encode_args((Some(365u32), None::<u32>)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
@@ -0,0 +1,514 @@
+use std::time::Duration;
+
+use candid::{encode_args, encode_one, Nat, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{
+        Recipe, RecipeDetailsInput, RecipeId, RecipeOnchainQuery, RecipeQuery, RecipeRecipientRule,
+        RpcResult, Run, RunChainInput, RunCreateOptions, RunId,
+    },
+};
+use ic_agent::Identity;
+use pocket_ic::PocketIc;
+
+const CHAIN_ID: u32 = 10;
+
+const OTHER_ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+
+fn create_published_recipe(ic: &PocketIc, catts: Principal, sender: Principal) -> Recipe {
+    publish_recipe(ic, catts, sender, recipe_eu_gtc_passport_clone())
+}
+
+fn publish_recipe(
+    ic: &PocketIc,
+    catts: Principal,
+    sender: Principal,
+    recipe: (RecipeDetailsInput, String),
+) -> Recipe {
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_create",
+        encode_args(recipe).unwrap(),
+    );
+    let recipe = response.unwrap_ok().clone();
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    response.unwrap_ok().clone()
+}
+
+// Gas settings quoted for an additional chain
+fn additional_chain(chain_id: u32, base_fee_per_gas: u64) -> RunChainInput {
+    RunChainInput {
+        chain_id,
+        base_fee_per_gas: Nat::from(base_fee_per_gas),
+        max_priority_fee_per_gas: Nat::from(1_000_000_u64),
+        gas: Nat::from(100_000_u64),
+    }
+}
+
+fn create_run(
+    ic: &PocketIc,
+    catts: Principal,
+    sender: Principal,
+    recipe_id: RecipeId,
+    options: Option<RunCreateOptions>,
+) -> RpcResult<Run> {
+    catts_update(
+        ic,
+        catts,
+        sender,
+        "run_create",
+        encode_args((
+            recipe_id,
+            CHAIN_ID,
+            Nat::from(1_000_000_u64),
+            Nat::from(1_000_000_u64),
+            Nat::from(100_000_u64),
+            options,
+        ))
+        .unwrap(),
+    )
+}
+
+#[test]
+fn test_run_create() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let response = create_run(&ic, catts, identity.sender().unwrap(), recipe.id, None);
+    let run = response.unwrap_ok();
+    assert_eq!(run.recipe_id, recipe.id);
+    assert_eq!(run.creator, address.to_lowercase());
+    assert_eq!(run.ref_run_id, None);
+}
+
+#[test]
+fn test_run_create_refresh_not_found() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let refresh_run_id: RunId = [0; 12];
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            refresh_run_id: Some(refresh_run_id),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
+
+#[test]
+fn test_run_create_refresh_not_attested() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let response = create_run(&ic, catts, identity.sender().unwrap(), recipe.id, None);
+    let refreshed_run = response.unwrap_ok().clone();
+
+    // Run ids are derived from the creation time
+    ic.advance_time(Duration::from_secs(1));
+
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            refresh_run_id: Some(refreshed_run.id),
+            ..Default::default()
+        }),
+    );
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    assert!(error
+        .details
+        .as_deref()
+        .unwrap()
+        .contains("no confirmed attestation"));
+}
+
+#[test]
+fn test_run_create_recipient_not_allowed() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            recipient: Some(OTHER_ADDRESS.to_string()),
+            ..Default::default()
+        }),
+    );
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    assert!(error
+        .details
+        .as_deref()
+        .unwrap()
+        .contains("only attests for the paying user"));
+}
+
+#[test]
+fn test_run_create_recipient() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.recipient_rule = Some(RecipeRecipientRule::SubjectIsRecipient);
+    let recipe = publish_recipe(&ic, catts, identity.sender().unwrap(), (details, readme));
+
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            recipient: Some(OTHER_ADDRESS.to_string()),
+            ..Default::default()
+        }),
+    );
+    let run = response.unwrap_ok();
+    assert_eq!(run.creator, address.to_lowercase());
+    assert_eq!(run.recipient.as_deref(), Some(OTHER_ADDRESS));
+    assert_eq!(run.subject, None);
+
+    // The subject can't differ from the recipient
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            recipient: Some(OTHER_ADDRESS.to_string()),
+            subject: Some(address.clone()),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn test_run_create_subject() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.recipient_rule = Some(RecipeRecipientRule::Any);
+    let recipe = publish_recipe(&ic, catts, identity.sender().unwrap(), (details, readme));
+
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            subject: Some(OTHER_ADDRESS.to_string()),
+            ..Default::default()
+        }),
+    );
+    let run = response.unwrap_ok();
+    assert_eq!(run.creator, address.to_lowercase());
+    assert_eq!(run.recipient, None);
+    assert_eq!(run.subject.as_deref(), Some(OTHER_ADDRESS));
+}
+
+#[test]
+fn test_run_create_recipient_invalid_address() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            recipient: Some("0x123".to_string()),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn test_run_create_offchain() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let response = create_run(&ic, catts, identity.sender().unwrap(), recipe.id, None);
+    let onchain_run = response.unwrap_ok().clone();
+
+    ic.advance_time(Duration::from_secs(1));
+
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            offchain: Some(true),
+            ..Default::default()
+        }),
+    );
+    let run = response.unwrap_ok();
+    assert_eq!(run.offchain, Some(true));
+    assert_eq!(run.gas, None);
+
+    // No gas is paid for off-chain attestations
+    assert!(run.user_fee.clone().unwrap() < onchain_run.user_fee.unwrap());
+}
+
+#[test]
+fn test_run_create_offchain_delegated_recipe() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.delegated_attester = Some(OTHER_ADDRESS.to_string());
+    let recipe = publish_recipe(&ic, catts, identity.sender().unwrap(), (details, readme));
+    assert_eq!(recipe.delegated_attester.as_deref(), Some(OTHER_ADDRESS));
+
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            offchain: Some(true),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn test_run_submit_delegation_signature_not_prepared() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.delegated_attester = Some(OTHER_ADDRESS.to_string());
+    let recipe = publish_recipe(&ic, catts, identity.sender().unwrap(), (details, readme));
+
+    let response = create_run(&ic, catts, identity.sender().unwrap(), recipe.id, None);
+    let run = response.unwrap_ok();
+
+    // The run is not paid, no attestation has been prepared for the attester
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "run_submit_delegation_signature",
+        encode_args((run.id, format!("0x{}", "00".repeat(65)))).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+
+    let run_id: RunId = [0; 12];
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "run_submit_delegation_signature",
+        encode_args((run_id, format!("0x{}", "00".repeat(65)))).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
+
+#[test]
+fn test_run_create_additional_chains() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let response = create_run(&ic, catts, identity.sender().unwrap(), recipe.id, None);
+    let single_chain_fee = response.unwrap_ok().user_fee.clone().unwrap();
+
+    // Run ids are derived from the creation time
+    ic.advance_time(Duration::from_secs(1));
+
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            additional_chains: Some(vec![additional_chain(8453, 1_000_000)]),
+            ..Default::default()
+        }),
+    );
+    let run = response.unwrap_ok();
+    assert_eq!(run.chain_id, CHAIN_ID);
+    let additional_chains = run.additional_chains.as_ref().unwrap();
+    assert_eq!(additional_chains.len(), 1);
+    assert_eq!(additional_chains[0].chain_id, 8453);
+    assert_eq!(additional_chains[0].attestation_transaction_hash, None);
+    assert_eq!(additional_chains[0].gas, Some(Nat::from(100_000_u64)));
+    let multi_chain_fee = run.user_fee.clone().unwrap();
+    assert!(multi_chain_fee > single_chain_fee);
+
+    // Each chain is charged for the gas settings quoted for it
+    ic.advance_time(Duration::from_secs(1));
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            additional_chains: Some(vec![additional_chain(8453, 1_000_000_000_000)]),
+            ..Default::default()
+        }),
+    );
+    assert!(response.unwrap_ok().user_fee.clone().unwrap() > multi_chain_fee);
+}
+
+#[test]
+fn test_run_create_revokable_fee() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+    let response = create_run(&ic, catts, identity.sender().unwrap(), recipe.id, None);
+    let fee = response.unwrap_ok().user_fee.clone().unwrap();
+
+    // The revocation transaction is paid for with the run
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.name = "revokable".to_string();
+    details.revokable = true;
+    let recipe = publish_recipe(&ic, catts, identity.sender().unwrap(), (details, readme));
+
+    // Run ids are derived from the creation time
+    ic.advance_time(Duration::from_secs(1));
+
+    let response = create_run(&ic, catts, identity.sender().unwrap(), recipe.id, None);
+    assert!(response.unwrap_ok().user_fee.clone().unwrap() > fee);
+}
+
+#[test]
+fn test_run_create_onchain_query_fee() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+    let response = create_run(&ic, catts, identity.sender().unwrap(), recipe.id, None);
+    let offchain_data_fee = response.unwrap_ok().user_fee.clone().unwrap();
+
+    // The EVM RPC calls of on-chain queries are paid for by the run
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.name = "onchain-query".to_string();
+    details.queries.push(RecipeQuery {
+        url: "".to_string(),
+        headers: None,
+        filter: None,
+        body: None,
+        onchain: Some(RecipeOnchainQuery {
+            chain_id: CHAIN_ID,
+            schema_uid: Some(format!("0x{:064x}", 1)),
+            take: Some(1),
+            ..Default::default()
+        }),
+    });
+    let recipe = publish_recipe(&ic, catts, identity.sender().unwrap(), (details, readme));
+
+    // Run ids are derived from the creation time
+    ic.advance_time(Duration::from_secs(1));
+
+    let response = create_run(&ic, catts, identity.sender().unwrap(), recipe.id, None);
+    assert!(response.unwrap_ok().user_fee.clone().unwrap() > offchain_data_fee);
+}
+
+#[test]
+fn test_run_create_additional_chains_invalid() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let invalid_options = [
+        // The run chain is not an additional chain
+        RunCreateOptions {
+            additional_chains: Some(vec![additional_chain(CHAIN_ID, 1_000_000)]),
+            ..Default::default()
+        },
+        RunCreateOptions {
+            additional_chains: Some(vec![
+                additional_chain(8453, 1_000_000),
+                additional_chain(8453, 1_000_000),
+            ]),
+            ..Default::default()
+        },
+        RunCreateOptions {
+            additional_chains: Some(vec![additional_chain(1234, 1_000_000)]),
+            ..Default::default()
+        },
+        RunCreateOptions {
+            offchain: Some(true),
+            additional_chains: Some(vec![additional_chain(8453, 1_000_000)]),
+            ..Default::default()
+        },
+    ];
+
+    for options in invalid_options {
+        let response = create_run(
+            &ic,
+            catts,
+            identity.sender().unwrap(),
+            recipe.id,
+            Some(options),
+        );
+        assert_eq!(response.unwrap_err().code, 400);
+    }
+}
+
+#[test]
+fn test_run_retry_chains_no_failed_chains() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let response = create_run(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        recipe.id,
+        Some(RunCreateOptions {
+            additional_chains: Some(vec![additional_chain(8453, 1_000_000)]),
+            ..Default::default()
+        }),
+    );
+    let run = response.unwrap_ok();
+
+    // The run is not paid, nothing has been attested or failed yet
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_retry_chains",
+        encode_one(run.id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+
+    let run_id: RunId = [0; 12];
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_retry_chains",
+        encode_one(run_id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
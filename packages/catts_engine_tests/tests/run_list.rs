@@ -0,0 +1,62 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_query, setup},
+    siwe::full_login,
+    types::{RecipeId, RpcResult, RunListOptions, RunListResponse},
+};
+
+#[test]
+fn test_run_list_by_user_empty() {
+    let (ic, siwe, catts) = setup();
+    let (address, _) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<RunListResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "run_list_by_user",
+        encode_args((address, None::<RunListOptions>)).unwrap(),
+    );
+    let list = response.unwrap_ok();
+    assert_eq!(list.runs.len(), 0);
+    assert_eq!(list.next_cursor, None);
+}
+
+#[test]
+fn test_run_list_by_user_invalid_address() {
+    let (ic, _, catts) = setup();
+    let response: RpcResult<RunListResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "run_list_by_user",
+        encode_args(("0x123", None::<RunListOptions>)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn test_run_list_by_recipe_not_found() {
+    let (ic, _, catts) = setup();
+    let recipe_id: RecipeId = [0; 12];
+    let response: RpcResult<RunListResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "run_list_by_recipe",
+        encode_args((recipe_id, None::<RunListOptions>)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
+
+#[test]
+fn test_run_list_recent_empty() {
+    let (ic, _, catts) = setup();
+    let response: RpcResult<RunListResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "run_list_recent",
+        encode_one(None::<RunListOptions>).unwrap(),
+    );
+    assert_eq!(response.unwrap_ok().runs.len(), 0);
+}
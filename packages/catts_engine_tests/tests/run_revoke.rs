@@ -0,0 +1,133 @@
+use candid::{encode_args, encode_one, Nat, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{Recipe, RpcResult, Run, RunCreateOptions, RunId},
+};
+use ic_agent::Identity;
+use pocket_ic::PocketIc;
+
+const CHAIN_ID: u32 = 10;
+
+fn create_run(ic: &PocketIc, catts: Principal, sender: Principal, revokable: bool) -> Run {
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.revokable = revokable;
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    let recipe = response.unwrap_ok().clone();
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    response.unwrap_ok();
+    let response: RpcResult<Run> = catts_update(
+        ic,
+        catts,
+        sender,
+        "run_create",
+        encode_args((
+            recipe.id,
+            CHAIN_ID,
+            Nat::from(1_000_000_u64),
+            Nat::from(1_000_000_u64),
+            Nat::from(100_000_u64),
+            None::<RunCreateOptions>,
+        ))
+        .unwrap(),
+    );
+    response.unwrap_ok().clone()
+}
+
+#[test]
+fn test_run_revoke_unauthorized() {
+    let (ic, _, catts) = setup();
+    let run_id: RunId = [0; 12];
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "run_revoke",
+        encode_one(run_id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
+
+#[test]
+fn test_run_revoke_not_found() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let run_id: RunId = [0; 12];
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_revoke",
+        encode_one(run_id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
+
+#[test]
+fn test_run_revoke_forbidden() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let run = create_run(&ic, catts, identity.sender().unwrap(), true);
+
+    // Neither the recipe creator nor the recipient
+    let (_, other_identity) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        other_identity.sender().unwrap(),
+        "run_revoke",
+        encode_one(run.id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 403);
+}
+
+#[test]
+fn test_run_revoke_not_revokable() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let run = create_run(&ic, catts, identity.sender().unwrap(), false);
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_revoke",
+        encode_one(run.id).unwrap(),
+    );
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    assert!(error.details.as_deref().unwrap().contains("not revokable"));
+}
+
+#[test]
+fn test_run_revoke_no_attestation() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let run = create_run(&ic, catts, identity.sender().unwrap(), true);
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_revoke",
+        encode_one(run.id).unwrap(),
+    );
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    assert!(error
+        .details
+        .as_deref()
+        .unwrap()
+        .contains("no confirmed attestation"));
+}
@@ -0,0 +1,38 @@
+use candid::{encode_args, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    siwe::full_login,
+    types::{RpcResult, Run, RunId},
+};
+use ic_agent::Identity;
+
+const SIGNATURE: &str = "0x00";
+
+#[test]
+fn test_run_submit_delegation_signature_unauthorized() {
+    let (ic, _, catts) = setup();
+    let run_id: RunId = [0; 12];
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "run_submit_delegation_signature",
+        encode_args((run_id, SIGNATURE)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
+
+#[test]
+fn test_run_submit_delegation_signature_not_found() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let run_id: RunId = [0; 12];
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_submit_delegation_signature",
+        encode_args((run_id, SIGNATURE)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}